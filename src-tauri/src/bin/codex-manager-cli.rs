@@ -0,0 +1,569 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::process::ExitCode;
+
+use tauri_app_lib::manager::accounts::{self, AccountInfo};
+use tauri_app_lib::manager::config::load_config;
+use tauri_app_lib::manager::credentials;
+use tauri_app_lib::manager::diagnostics;
+use tauri_app_lib::manager::error::AppError;
+use tauri_app_lib::manager::folder::FolderConfig;
+use tauri_app_lib::manager::git_repo::GitRepoConfig;
+use tauri_app_lib::manager::logging::{self, Console};
+use tauri_app_lib::manager::s3::S3Config;
+use tauri_app_lib::manager::sync::{self, CodexSyncConfig, SyncMode};
+use tauri_app_lib::manager::sync_backend::{BackendConfig, SyncTarget};
+use tauri_app_lib::manager::token;
+use tauri_app_lib::manager::usage::{self, RateLimitWindow, UsageUpdate};
+use tauri_app_lib::manager::usage_history::{self, UsageHistory, WindowForecast};
+use tauri_app_lib::manager::vault;
+use tauri_app_lib::manager::webdav::WebDavConfig;
+use tauri_app_lib::t;
+
+/// Codex 多账号管理（命令行版），与桌面端共用 ~/.myswitch 下的账号和配置
+#[derive(Parser)]
+#[command(name = "codex-manager-cli", version)]
+struct Cli {
+    /// 输出格式
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Table, global = true)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出所有账号
+    List,
+    /// 切换到指定账号（名称 / 邮箱 / account_id）
+    Switch { account: String },
+    /// 查询用量，不指定账号时查询全部
+    Usage { account: Option<String> },
+    /// 查看用量历史和用满时间预测，不指定账号时显示全部
+    Forecast {
+        account: Option<String>,
+        /// JSON 输出中包含最近几天的记录
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// 刷新 Token，不指定账号时刷新全部
+    Refresh { account: Option<String> },
+    /// 同步账号（WebDAV、S3、本地/NAS 目录或 git 仓库）
+    Sync {
+        #[arg(value_enum)]
+        direction: SyncDirection,
+        /// 同时同步 Codex 配置（prompts、skills、AGENTS.MD）
+        #[arg(long)]
+        codex: bool,
+        /// Codex 配置中单个文件的大小上限（MB），超过的跳过，0 表示不限制
+        #[arg(long, default_value_t = 20)]
+        max_file_size_mb: u64,
+        #[command(flatten)]
+        target: Box<SyncTargetArgs>,
+    },
+    /// 管理账号加密存储
+    Vault {
+        #[command(subcommand)]
+        action: VaultAction,
+    },
+    /// 导出诊断包（脱敏后的日志、运行环境和配置），用于问题报告
+    Diagnostics {
+        /// 输出的 zip 文件
+        #[arg(long, default_value = "codex-manager-diagnostics.zip")]
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum VaultAction {
+    /// 查看是否启用加密存储
+    Status,
+    /// 将明文账号迁移为加密存储（口令取自 CODEX_MANAGER_VAULT_PASSPHRASE 或标准输入）
+    Migrate,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SyncDirection {
+    Upload,
+    Download,
+    /// 双向同步账号，两端都修改时取 Token 较新的一方
+    Both,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum BackendKind {
+    Webdav,
+    S3,
+    /// 本地目录或已挂载的 NAS 共享
+    Folder,
+    Git,
+}
+
+/// 不指定 --backend（也没有 --url）时使用桌面端保存的同步目标，凭据取自系统钥匙串；
+/// 命令行中省略的密码、Secret Key 和同步口令同样从钥匙串补全
+#[derive(Args)]
+struct SyncTargetArgs {
+    /// 同步后端
+    #[arg(long, value_enum, env = "CODEX_MANAGER_SYNC_BACKEND")]
+    backend: Option<BackendKind>,
+    #[arg(long, env = "CODEX_MANAGER_WEBDAV_URL")]
+    url: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_WEBDAV_USERNAME")]
+    username: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_WEBDAV_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_WEBDAV_REMOTE_PATH", default_value = "/codex-manager/")]
+    remote_path: String,
+    /// S3 服务地址，如 http://127.0.0.1:9000
+    #[arg(long, env = "CODEX_MANAGER_S3_ENDPOINT")]
+    s3_endpoint: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_S3_REGION", default_value = "us-east-1")]
+    s3_region: String,
+    #[arg(long, env = "CODEX_MANAGER_S3_BUCKET")]
+    s3_bucket: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_S3_ACCESS_KEY_ID")]
+    s3_access_key_id: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_S3_SECRET_ACCESS_KEY", hide_env_values = true)]
+    s3_secret_access_key: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_S3_PREFIX", default_value = "codex-manager/")]
+    s3_prefix: String,
+    /// 使用 virtual-hosted 地址（bucket.endpoint），AWS S3 推荐；默认 path-style
+    #[arg(long)]
+    s3_virtual_hosted: bool,
+    /// 同步目录（本地路径或已挂载的 NAS 共享）
+    #[arg(long, env = "CODEX_MANAGER_SYNC_FOLDER")]
+    folder: Option<String>,
+    /// git 仓库地址，认证沿用本机 git 配置
+    #[arg(long, env = "CODEX_MANAGER_GIT_URL")]
+    git_url: Option<String>,
+    #[arg(long, env = "CODEX_MANAGER_GIT_BRANCH", default_value = "main")]
+    git_branch: String,
+    /// 同步口令，账号文件加密后再上传，各设备需一致
+    #[arg(long, env = "CODEX_MANAGER_SYNC_PASSPHRASE", hide_env_values = true)]
+    sync_passphrase: Option<String>,
+    /// 远程回收站保留已删除文件的天数，0 表示一直保留
+    #[arg(long, env = "CODEX_MANAGER_TRASH_RETENTION_DAYS", default_value_t = 30)]
+    trash_retention_days: u32,
+}
+
+impl SyncTargetArgs {
+    fn into_target(self) -> Result<SyncTarget, AppError> {
+        let kind = match (self.backend, &self.url) {
+            (Some(kind), _) => kind,
+            // 兼容只给出 WebDAV 参数的旧用法
+            (None, Some(_)) => BackendKind::Webdav,
+            (None, None) => {
+                credentials::migrate_legacy_target()?;
+                let mut target = credentials::load_sync_target()?;
+                if let Some(passphrase) = self.sync_passphrase {
+                    target.sync_passphrase = passphrase;
+                }
+                return Ok(target);
+            }
+        };
+        let backend = match kind {
+            BackendKind::Webdav => BackendConfig::Webdav(WebDavConfig {
+                url: required(self.url, "url")?,
+                username: required(self.username, "username")?,
+                password: self.password.unwrap_or_default(),
+                remote_path: self.remote_path,
+            }),
+            BackendKind::S3 => BackendConfig::S3(S3Config {
+                endpoint: required(self.s3_endpoint, "s3-endpoint")?,
+                region: self.s3_region,
+                bucket: required(self.s3_bucket, "s3-bucket")?,
+                access_key_id: required(self.s3_access_key_id, "s3-access-key-id")?,
+                secret_access_key: self.s3_secret_access_key.unwrap_or_default(),
+                prefix: self.s3_prefix,
+                path_style: !self.s3_virtual_hosted,
+            }),
+            BackendKind::Folder => BackendConfig::Folder(FolderConfig {
+                path: required(self.folder, "folder")?,
+            }),
+            BackendKind::Git => BackendConfig::Git(GitRepoConfig {
+                url: required(self.git_url, "git-url")?,
+                branch: self.git_branch,
+            }),
+        };
+        let mut target = SyncTarget {
+            backend,
+            sync_passphrase: self.sync_passphrase.unwrap_or_default(),
+            trash_retention_days: self.trash_retention_days,
+        };
+        credentials::fill_secrets(&mut target);
+        if target.sync_passphrase.is_empty() {
+            return Err(AppError::InvalidInput(t!(CliSyncPassphraseRequired)));
+        }
+        Ok(target)
+    }
+}
+
+fn required(value: Option<String>, flag: &str) -> Result<String, AppError> {
+    value.ok_or_else(|| AppError::InvalidInput(t!(CliFlagRequired, flag)))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logging::init(Console::Stderr);
+    let output = cli.output;
+    match run(cli).await {
+        Ok(code) => code,
+        Err(e) => {
+            // JSON 模式下输出 { "error": { code, message, ... } }，脚本按 code 判断错误类型
+            if output == OutputFormat::Json {
+                print_json(&serde_json::json!({ "error": e }));
+            } else {
+                eprintln!("{}", t!(CliError, e));
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// 加密存储口令的环境变量
+const VAULT_PASSPHRASE_ENV: &str = "CODEX_MANAGER_VAULT_PASSPHRASE";
+
+/// 读取口令：优先使用环境变量，否则从标准输入读取一行
+fn read_passphrase() -> Result<String, AppError> {
+    if let Ok(passphrase) = std::env::var(VAULT_PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    eprint!("{}", t!(CliEnterPassphrase));
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| t!(CliReadPassphraseFailed, e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn run(cli: Cli) -> Result<ExitCode, AppError> {
+    let output = cli.output;
+
+    // 已启用加密存储时，用环境变量中的口令自动解锁
+    if vault::is_enabled() && !matches!(cli.command, Command::Vault { .. } | Command::Diagnostics { .. }) {
+        match std::env::var(VAULT_PASSPHRASE_ENV) {
+            Ok(passphrase) => vault::unlock(&passphrase)?,
+            Err(_) => {
+                return Err(AppError::InvalidInput(t!(CliVaultNeedsPassphrase, VAULT_PASSPHRASE_ENV)))
+            }
+        }
+    }
+
+    match cli.command {
+        Command::List => {
+            let scan = accounts::scan_accounts()?;
+            if output == OutputFormat::Json {
+                print_json(&scan);
+            } else {
+                let rows = scan.accounts.iter().map(|a| {
+                    vec![
+                        if a.is_active { "*".to_string() } else { String::new() },
+                        a.name.clone(),
+                        a.display_email(),
+                        a.plan_type.clone(),
+                        a.expires_at.map(format_timestamp).unwrap_or_else(|| "-".to_string()),
+                    ]
+                });
+                print_table(&[String::new(), t!(CliColName), t!(CliColEmail), t!(CliColPlan), t!(CliColTokenExpires)], rows);
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+
+        Command::Switch { account } => {
+            let account = accounts::find_account(&account)?;
+            accounts::switch_account(&account.file_path)?;
+            if output == OutputFormat::Json {
+                print_json(&account);
+            } else {
+                println!("{}", t!(CliSwitched, account.name, account.display_email()));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+
+        Command::Usage { account } => {
+            let targets = select_accounts(account.as_deref())?;
+            let file_paths: Vec<String> = targets.iter().map(|a| a.file_path.clone()).collect();
+            let updates = usage::fetch_all(&file_paths, &load_config().usage_fetch, |_| {}).await;
+            let reports: Vec<UsageReport> = targets
+                .into_iter()
+                .zip(updates)
+                .map(|(account, update)| UsageReport { account, update })
+                .collect();
+
+            if output == OutputFormat::Json {
+                print_json(&reports);
+            } else {
+                let window = |w: Option<&RateLimitWindow>| {
+                    w.map(|w| format!("{:.0}%", w.used_percent)).unwrap_or_else(|| "-".to_string())
+                };
+                let reset = |w: Option<&RateLimitWindow>| {
+                    w.and_then(|w| w.resets_at).map(format_timestamp).unwrap_or_else(|| "-".to_string())
+                };
+                let rows = reports.iter().map(|r| {
+                    let usage = r.update.usage.as_ref();
+                    let primary = usage.and_then(|u| u.primary_window.as_ref());
+                    let secondary = usage.and_then(|u| u.secondary_window.as_ref());
+                    vec![
+                        r.account.name.clone(),
+                        window(primary),
+                        reset(primary),
+                        window(secondary),
+                        reset(secondary),
+                        r.update.error.as_ref().map(|e| e.to_string()).unwrap_or_default(),
+                    ]
+                });
+                print_table(
+                    &[t!(CliColName), t!(CliColPrimary), t!(CliColReset), t!(CliColWeekly), t!(CliColReset), t!(CliColError)],
+                    rows,
+                );
+            }
+
+            let failed = reports.iter().any(|r| r.update.error.is_some());
+            Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+        }
+
+        Command::Forecast { account, days } => {
+            let histories: Vec<UsageHistory> = select_accounts(account.as_deref())?
+                .iter()
+                .map(|account| usage_history::history(account, days))
+                .collect();
+
+            if output == OutputFormat::Json {
+                print_json(&histories);
+            } else {
+                let used = |w: Option<&WindowForecast>| {
+                    w.map(|w| format!("{:.0}%", w.used_percent)).unwrap_or_else(|| "-".to_string())
+                };
+                let rate = |w: Option<&WindowForecast>| {
+                    w.and_then(|w| w.burn_rate_per_hour)
+                        .map(|rate| format!("{:.1}%/h", rate))
+                        .unwrap_or_else(|| "-".to_string())
+                };
+                let exhausts = |w: Option<&WindowForecast>| match w {
+                    Some(w) => match (w.exhausts_at, w.burn_rate_per_hour) {
+                        (Some(at), _) => format_timestamp(at),
+                        (None, Some(_)) => t!(CliNeverExhausts),
+                        (None, None) => t!(CliNotEnoughSamples),
+                    },
+                    None => "-".to_string(),
+                };
+                let rows = histories.iter().map(|h| {
+                    let primary = h.forecast.primary.as_ref();
+                    let secondary = h.forecast.secondary.as_ref();
+                    vec![
+                        h.name.clone(),
+                        used(primary),
+                        rate(primary),
+                        exhausts(primary),
+                        used(secondary),
+                        rate(secondary),
+                        exhausts(secondary),
+                    ]
+                });
+                print_table(
+                    &[
+                        t!(CliColName),
+                        t!(CliColPrimary),
+                        t!(CliColRate),
+                        t!(CliColExhausts),
+                        t!(CliColWeekly),
+                        t!(CliColRate),
+                        t!(CliColExhausts),
+                    ],
+                    rows,
+                );
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+
+        Command::Refresh { account } => {
+            let targets = select_accounts(account.as_deref())?;
+            let mut reports = Vec::with_capacity(targets.len());
+            for account in targets {
+                let result = token::refresh_account_token(&account.file_path).await;
+                let (message, error) = match result {
+                    Ok(message) => (message, None),
+                    Err(e) => (e.to_string(), Some(e)),
+                };
+                reports.push(RefreshReport {
+                    display_email: account.display_email(),
+                    name: account.name,
+                    email: account.email,
+                    success: error.is_none(),
+                    message,
+                    error,
+                });
+            }
+
+            if output == OutputFormat::Json {
+                print_json(&reports);
+            } else {
+                let rows = reports.iter().map(|r| {
+                    vec![
+                        r.name.clone(),
+                        r.display_email.clone(),
+                        if r.success { t!(CliSuccess) } else { t!(CliFailed) },
+                        r.message.clone(),
+                    ]
+                });
+                print_table(&[t!(CliColName), t!(CliColEmail), t!(CliColResult), t!(CliColMessage)], rows);
+            }
+
+            let failed = reports.iter().any(|r| !r.success);
+            Ok(if failed { ExitCode::FAILURE } else { ExitCode::SUCCESS })
+        }
+
+        Command::Sync { direction, codex, max_file_size_mb, target } => {
+            let target = target.into_target()?;
+            let sync_config = CodexSyncConfig { max_file_size_mb, ..CodexSyncConfig::default() };
+            if codex && matches!(direction, SyncDirection::Both) {
+                return Err(AppError::InvalidInput(t!(CliCodexBothUnsupported)));
+            }
+
+            let mut result = match direction {
+                SyncDirection::Upload => sync::sync_accounts(&target, SyncMode::Upload).await?,
+                SyncDirection::Download => sync::sync_accounts(&target, SyncMode::Download).await?,
+                SyncDirection::Both => sync::sync_accounts(&target, SyncMode::Both).await?,
+            };
+            if codex {
+                let codex_result = match direction {
+                    SyncDirection::Upload => sync::sync_codex_upload(&target, &sync_config).await?,
+                    _ => sync::sync_codex_download(&target, &sync_config).await?,
+                };
+                result.merge(codex_result);
+            }
+
+            if output == OutputFormat::Json {
+                print_json(&result);
+            } else {
+                let rows = result.uploaded.iter().map(|f| vec![t!(CliUploaded), f.clone()])
+                    .chain(result.downloaded.iter().map(|f| vec![t!(CliDownloaded), f.clone()]))
+                    .chain(result.migrated.iter().map(|f| vec![t!(CliMigrated), f.clone()]))
+                    .chain(result.conflicts.iter().map(|c| vec![t!(CliConflict), c.clone()]))
+                    .chain(result.deleted.iter().map(|d| vec![t!(CliDeleted), d.clone()]))
+                    .chain(result.skipped.iter().map(|s| vec![t!(CliSkipped), s.clone()]))
+                    .chain(result.errors.iter().map(|e| vec![t!(CliColError), e.clone()]));
+                print_table(&[t!(CliColAction), t!(CliColFile)], rows);
+            }
+
+            let clean = result.errors.is_empty() && result.conflicts.is_empty();
+            Ok(if clean { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        }
+
+        Command::Vault { action: VaultAction::Status } => {
+            let status = vault::status();
+            if output == OutputFormat::Json {
+                print_json(&status);
+            } else {
+                println!("{}", t!(CliVaultStatus, if status.enabled { t!(CliEnabled) } else { t!(CliDisabled) }));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+
+        Command::Vault { action: VaultAction::Migrate } => {
+            let report = vault::migrate(&read_passphrase()?)?;
+            if output == OutputFormat::Json {
+                print_json(&report);
+            } else {
+                let rows = report.encrypted.iter().map(|f| vec![t!(CliEncrypted), f.clone()])
+                    .chain(report.skipped.iter().map(|f| vec![t!(CliAlreadyEncrypted), f.clone()]))
+                    .chain(report.errors.iter().map(|e| vec![t!(CliColError), e.clone()]));
+                print_table(&[t!(CliColResult), t!(CliColFile)], rows);
+            }
+            Ok(if report.errors.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+        }
+
+        Command::Diagnostics { file } => {
+            diagnostics::export(&file)?;
+            if output == OutputFormat::Json {
+                print_json(&serde_json::json!({ "file": file }));
+            } else {
+                println!("{}", t!(CliDiagnosticsExported, file.display()));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UsageReport {
+    account: AccountInfo,
+    #[serde(flatten)]
+    update: UsageUpdate,
+}
+
+#[derive(Serialize)]
+struct RefreshReport {
+    name: String,
+    /// 没有邮箱时为空
+    email: String,
+    /// 表格中显示的邮箱
+    #[serde(skip)]
+    display_email: String,
+    success: bool,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<AppError>,
+}
+
+/// 指定账号时只返回该账号，否则返回全部账号
+fn select_accounts(query: Option<&str>) -> Result<Vec<AccountInfo>, AppError> {
+    match query {
+        Some(query) => Ok(vec![accounts::find_account(query)?]),
+        None => Ok(accounts::scan_accounts()?.accounts),
+    }
+}
+
+fn format_timestamp(ts: i64) -> String {
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| ts.to_string())
+}
+
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("{}", t!(SerializeFailed, e)),
+    }
+}
+
+/// 终端显示宽度，中日韩字符按两列计算
+fn display_width(s: &str) -> usize {
+    s.chars()
+        .map(|c| if ('\u{1100}'..='\u{ffdc}').contains(&c) && !c.is_ascii() { 2 } else { 1 })
+        .sum()
+}
+
+fn print_table(headers: &[String], rows: impl Iterator<Item = Vec<String>>) {
+    let rows: Vec<Vec<String>> = rows.collect();
+    let mut widths: Vec<usize> = headers.iter().map(|h| display_width(h)).collect();
+    for row in &rows {
+        for (i, cell) in row.iter().enumerate() {
+            widths[i] = widths[i].max(display_width(cell));
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| format!("{}{}", cell, " ".repeat(widths[i] - display_width(cell))))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", format_row(headers.iter().map(String::as_str).collect()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
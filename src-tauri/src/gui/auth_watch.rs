@@ -0,0 +1,42 @@
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::manager::accounts;
+use crate::manager::config::get_codex_auth_file;
+use crate::manager::watch::{self, Snapshot};
+
+// ========== 跟踪 Codex CLI 刷新的 Token ==========
+//
+// Codex CLI 会自行刷新 ~/.codex/auth.json 并轮换 refresh_token，
+// 检测到变化后立即写回账号目录中的对应文件，避免账号文件里留下已失效的 Token。
+
+/// auth.json 的检查间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 启动 auth.json 监视任务（在 `setup` 中调用）
+pub fn spawn(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let path = get_codex_auth_file();
+        let mut snapshot = Snapshot::of_file(&path);
+        // 应用未运行期间的刷新也写回一次
+        write_back(&app);
+
+        loop {
+            watch::wait_for_change(&mut snapshot, WATCH_INTERVAL, || Snapshot::of_file(&path)).await;
+            write_back(&app);
+        }
+    });
+}
+
+/// 写回成功时通过 `account-tokens-updated` 事件通知前端重新扫描；
+/// auth.json 也可能被其他工具换成了别的账号，每次变化后都重建托盘菜单
+fn write_back(app: &AppHandle) {
+    match accounts::write_back_live_auth() {
+        Ok(Some(name)) => {
+            let _ = app.emit("account-tokens-updated", name);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "写回 Token 失败"),
+    }
+    super::tray::refresh(app);
+}
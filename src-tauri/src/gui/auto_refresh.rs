@@ -0,0 +1,152 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::Notify;
+
+use crate::manager::accounts::{self, AccountInfo};
+use crate::manager::config::{load_config, AutoRefreshConfig};
+use crate::manager::token::{self, RefreshFailure};
+use crate::manager::vault;
+
+// ========== 后台 Token 刷新 ==========
+//
+// 按 id_token 中的过期时间提前刷新各账号的 Token。当前使用中的账号由 Codex CLI 自行刷新，
+// 刷新结果经 auth_watch 写回，这里不再刷新，避免两边轮换同一个 refresh_token。
+
+/// 没有即将到期的账号时，最长隔多久重新扫描一次（账号可能被添加或同步下来）
+const MAX_SLEEP: Duration = Duration::from_secs(60 * 60);
+
+/// 网络错误等临时失败后的重试间隔
+const RETRY_DELAY: i64 = 5 * 60;
+
+/// 后台任务共享状态，配置变更时通过 `wake` 唤醒任务重新读取配置
+#[derive(Default)]
+pub struct AutoRefreshState {
+    pub wake: Arc<Notify>,
+}
+
+/// 永久失败时通过 `token-refresh-failed` 事件推送给前端
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenRefreshFailedEvent {
+    pub file_path: String,
+    pub email: String,
+    pub failure: RefreshFailure,
+}
+
+/// 启动后台刷新任务（在 `setup` 中调用）
+pub fn spawn(app: AppHandle) {
+    let wake = app.state::<AutoRefreshState>().wake.clone();
+
+    tauri::async_runtime::spawn(async move {
+        // 临时失败的账号在此之前不再尝试（秒级时间戳）
+        let mut retry_after: HashMap<String, i64> = HashMap::new();
+
+        loop {
+            let config = load_config().auto_refresh;
+            if !config.enabled {
+                wake.notified().await;
+                continue;
+            }
+
+            let next_due = run_refresh(&app, &config, &mut retry_after).await;
+            let now = chrono::Utc::now().timestamp();
+            let delay = next_due
+                .map(|due| Duration::from_secs(due.saturating_sub(now).max(1) as u64))
+                .map_or(MAX_SLEEP, |delay| delay.min(MAX_SLEEP));
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = wake.notified() => {}
+            }
+        }
+    });
+}
+
+/// 刷新已到期的账号，返回下一个账号到期的时间
+async fn run_refresh(
+    app: &AppHandle,
+    config: &AutoRefreshConfig,
+    retry_after: &mut HashMap<String, i64>,
+) -> Option<i64> {
+    let scan = match accounts::scan_accounts() {
+        Ok(scan) => scan,
+        Err(e) => {
+            tracing::warn!(error = %e, "扫描账号失败");
+            return None;
+        }
+    };
+
+    let mut next_due: Option<i64> = None;
+    let mut schedule = |due: i64| next_due = Some(next_due.map_or(due, |next| next.min(due)));
+    let mut changed = false;
+
+    for account in &scan.accounts {
+        if account.is_active || account.refresh_failure.is_some() {
+            continue;
+        }
+        let Ok(auth) = vault::read_auth(Path::new(&account.file_path)) else {
+            continue;
+        };
+        let Some(due) = token::refresh_due_at(&auth, config) else {
+            continue;
+        };
+        let due = retry_after.get(&account.file_path).map_or(due, |&retry| due.max(retry));
+        let now = chrono::Utc::now().timestamp();
+        if due > now {
+            schedule(due);
+            continue;
+        }
+
+        match token::refresh_account_token(&account.file_path).await {
+            Ok(_) => {
+                tracing::info!(account = %account.email, "已刷新 Token");
+                retry_after.remove(&account.file_path);
+                changed = true;
+                if let Some(due) = vault::read_auth(Path::new(&account.file_path))
+                    .ok()
+                    .and_then(|auth| token::refresh_due_at(&auth, config))
+                {
+                    schedule(due);
+                }
+            }
+            Err(e) => match token::failure_for(&auth) {
+                Some(failure) => {
+                    tracing::warn!(account = %account.email, error = %e, "Token 已失效");
+                    changed = true;
+                    notify_failure(app, account, failure);
+                }
+                None => {
+                    tracing::warn!(account = %account.email, error = %e, "刷新 Token 失败，稍后重试");
+                    retry_after.insert(account.file_path.clone(), now + RETRY_DELAY);
+                    schedule(now + RETRY_DELAY);
+                }
+            },
+        }
+    }
+
+    if changed {
+        let _ = app.emit("account-tokens-updated", ());
+        super::tray::refresh(app);
+    }
+    next_due
+}
+
+/// 推送事件并弹出系统通知
+fn notify_failure(app: &AppHandle, account: &AccountInfo, failure: RefreshFailure) {
+    let _ = app
+        .notification()
+        .builder()
+        .title("Codex Manager")
+        .body(format!("{}: {}", account.display_email(), failure.message))
+        .show();
+    let _ = app.emit("token-refresh-failed", TokenRefreshFailedEvent {
+        file_path: account.file_path.clone(),
+        email: account.email.clone(),
+        failure,
+    });
+}
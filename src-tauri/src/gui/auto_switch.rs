@@ -0,0 +1,112 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::manager::accounts::{self, AccountInfo};
+use crate::manager::config::{load_config, AutoSwitchConfig};
+use crate::manager::error::AppError;
+use crate::manager::strategy;
+use crate::manager::usage::{self, UsageUpdate};
+use crate::t;
+
+// ========== 后台自动切换 ==========
+
+/// 后台任务共享状态，配置变更时通过 `wake` 唤醒任务重新读取配置
+#[derive(Default)]
+pub struct AutoSwitchState {
+    pub wake: Arc<Notify>,
+}
+
+/// 自动切换完成后通过 `account-switched` 事件推送给前端
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountSwitchedEvent {
+    pub from: Option<String>,
+    pub to: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub reason: String,
+}
+
+/// 启动后台检查任务（在 `setup` 中调用，窗口隐藏到托盘后仍会运行）
+pub fn spawn(app: AppHandle) {
+    let wake = app.state::<AutoSwitchState>().wake.clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let config = load_config().auto_switch;
+            let enabled = (config.auto_check || config.enable_auto_switch) && config.check_interval > 0;
+
+            if enabled {
+                if let Err(e) = run_check(&app, &config).await {
+                    tracing::warn!(error = %e, "自动切换检查失败");
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_secs(config.check_interval * 60)) => {}
+                    _ = wake.notified() => {}
+                }
+            } else {
+                // 未启用时等待配置变更
+                wake.notified().await;
+            }
+        }
+    });
+}
+
+/// 查询所有账号用量，必要时切换到备用账号
+async fn run_check(app: &AppHandle, config: &AutoSwitchConfig) -> Result<(), AppError> {
+    let scan = accounts::scan_accounts()?;
+
+    let file_paths: Vec<String> = scan.accounts.iter().map(|a| a.file_path.clone()).collect();
+    let updates = usage::fetch_all(&file_paths, &load_config().usage_fetch, |update| {
+        let _ = app.emit("usage-updated", [update]);
+    })
+    .await;
+    super::tray::refresh(app);
+
+    if !config.enable_auto_switch {
+        return Ok(());
+    }
+
+    let Some(trigger) = switch_trigger(&scan.accounts, &updates, config.auto_switch_threshold) else {
+        return Ok(());
+    };
+    let candidates = strategy::candidates(&scan.accounts, &updates);
+    let Some(decision) = strategy::choose_backup(config, &candidates) else {
+        tracing::warn!(%trigger, "没有可用的备用账号");
+        return Ok(());
+    };
+
+    let from = scan.accounts.iter().find(|a| a.is_active).map(|a| a.email.clone());
+    accounts::switch_account(&decision.file_path)?;
+    tracing::info!(%trigger, to = %decision.email, reason = %decision.reason, "已自动切换账号");
+
+    let _ = app.emit("account-switched", AccountSwitchedEvent {
+        from,
+        to: decision.email,
+        file_path: decision.file_path,
+        reason: t!(SwitchReason, trigger, decision.reason),
+    });
+    super::tray::refresh(app);
+
+    Ok(())
+}
+
+/// 判断当前账号是否需要切换，返回触发原因
+fn switch_trigger(accounts: &[AccountInfo], updates: &[UsageUpdate], threshold: u32) -> Option<String> {
+    let threshold = threshold.clamp(1, 50);
+    let used_limit = 100.0 - threshold as f64;
+
+    let active = accounts.iter().find(|a| a.is_active)?;
+    let update = updates.iter().find(|u| u.file_path == active.file_path)?;
+    if update.is_token_expired {
+        return Some(t!(SwitchTokenExpired));
+    }
+
+    let usage = update.usage.as_ref()?;
+    let primary = usage.primary_window.as_ref().map_or(0.0, |w| w.used_percent);
+    let secondary = usage.secondary_window.as_ref().map_or(0.0, |w| w.used_percent);
+    (primary >= used_limit || secondary >= used_limit)
+        .then(|| t!(SwitchBelowThreshold, threshold))
+}
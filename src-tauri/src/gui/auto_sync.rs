@@ -0,0 +1,197 @@
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+
+use crate::manager::config::{get_accounts_dir, load_config, AutoSyncConfig, CodexDirection};
+use crate::manager::credentials;
+use crate::manager::error::AppError;
+use crate::manager::sync::{self, SyncMode, SyncResult};
+use crate::manager::watch::{self, Snapshot};
+use crate::t;
+
+// ========== 后台自动同步 ==========
+
+/// 账号目录的检查间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 同步失败后的首次重试间隔，之后每次翻倍
+const BACKOFF_BASE: Duration = Duration::from_secs(60);
+
+/// 重试间隔上限
+const BACKOFF_MAX: Duration = Duration::from_secs(60 * 60);
+
+/// 后台同步共享状态，配置变更时通过 `wake` 唤醒任务重新读取配置
+#[derive(Default)]
+pub struct AutoSyncState {
+    pub wake: Arc<Notify>,
+    /// 最近一次推送的状态，供前端打开时读取
+    pub status: Mutex<SyncStatus>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncPhase {
+    /// 未启用或未配置同步目标
+    #[default]
+    Disabled,
+    Syncing,
+    /// 上次同步成功，等待下一次
+    Idle,
+    /// 同步完成，但部分文件出错或冲突
+    Partial,
+    /// 整体失败：连接失败等临时错误按退避间隔重试，其他错误（设置、凭据、口令）等修改设置后再同步
+    Failed,
+}
+
+/// 通过 `sync-status` 事件推送给前端，同时显示在托盘提示中
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    /// 上次成功同步的时间（毫秒时间戳）
+    pub last_sync: Option<i64>,
+    /// 下次计划同步的时间（毫秒时间戳），失败后不再重试时为空
+    pub next_sync: Option<i64>,
+    /// 连续失败次数
+    pub failures: u32,
+    pub message: Option<String>,
+    pub result: Option<SyncResult>,
+}
+
+/// 启动后台同步任务（在 `setup` 中调用，窗口隐藏到托盘后仍会运行）
+pub fn spawn(app: AppHandle) {
+    let wake = app.state::<AutoSyncState>().wake.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let mut status = SyncStatus::default();
+
+        loop {
+            let app_config = load_config();
+            let config = app_config.auto_sync;
+            if !config.enabled || config.interval == 0 || app_config.sync_target.is_none() {
+                status = SyncStatus { last_sync: status.last_sync, ..SyncStatus::default() };
+                publish(&app, &status);
+                // 未启用时等待配置变更
+                wake.notified().await;
+                continue;
+            }
+
+            status.phase = SyncPhase::Syncing;
+            status.next_sync = None;
+            publish(&app, &status);
+
+            // 下次同步前的等待时间，为空时只等配置变更
+            let delay = match run_sync(&config).await {
+                Ok(result) => {
+                    let clean = result.errors.is_empty() && result.conflicts.is_empty();
+                    status.phase = if clean { SyncPhase::Idle } else { SyncPhase::Partial };
+                    status.last_sync = Some(chrono::Utc::now().timestamp_millis());
+                    status.failures = 0;
+                    status.message = result.errors.first().or(result.conflicts.first()).cloned();
+                    status.result = Some(result);
+                    Some(Duration::from_secs(config.interval * 60))
+                }
+                Err(e) => {
+                    status.phase = SyncPhase::Failed;
+                    status.failures += 1;
+                    status.message = Some(e.to_string());
+                    status.result = None;
+                    if e.is_transient() {
+                        tracing::warn!(error = %e, failures = status.failures, "自动同步失败，稍后重试");
+                        Some(backoff(status.failures))
+                    } else {
+                        // 设置、凭据或口令错误，重试也不会成功
+                        tracing::warn!(error = %e, "自动同步失败，等待修改设置");
+                        None
+                    }
+                }
+            };
+            status.next_sync = delay.map(|delay| chrono::Utc::now().timestamp_millis() + delay.as_millis() as i64);
+            publish(&app, &status);
+
+            // 同步本身会改动账号目录，以同步后的状态为基准检测变化
+            let accounts_dir = get_accounts_dir();
+            let mut snapshot = Snapshot::of_dir(&accounts_dir);
+            let watch_changes = config.on_change && config.sync_accounts && status.failures == 0;
+            tokio::select! {
+                _ = tokio::time::sleep(delay.unwrap_or_default()), if delay.is_some() => {}
+                _ = wake.notified() => {}
+                _ = watch::wait_for_change(&mut snapshot, WATCH_INTERVAL, || Snapshot::of_dir(&accounts_dir)), if watch_changes => {
+                    tracing::info!("账号目录有变化，开始同步");
+                }
+            }
+        }
+    });
+}
+
+/// 第 `failures` 次连续失败后的重试间隔
+fn backoff(failures: u32) -> Duration {
+    BACKOFF_BASE
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(BACKOFF_MAX)
+}
+
+/// 按配置同步账号和 Codex 配置，合并为一份结果
+async fn run_sync(config: &AutoSyncConfig) -> Result<SyncResult, AppError> {
+    let target = &credentials::load_sync_target()?;
+    let mut result = SyncResult::default();
+    if config.sync_accounts {
+        result.merge(sync::sync_accounts(target, SyncMode::Both).await?);
+    }
+    match config.codex_direction {
+        CodexDirection::Off => {}
+        CodexDirection::Upload => result.merge(sync::sync_codex_upload(target, &config.codex_config).await?),
+        CodexDirection::Download => result.merge(sync::sync_codex_download(target, &config.codex_config).await?),
+    }
+    Ok(result)
+}
+
+/// 保存状态，推送 `sync-status` 事件并更新托盘
+///
+/// 同步完成后账号可能被下载、删除或重命名，连同托盘菜单一起重建。
+fn publish(app: &AppHandle, status: &SyncStatus) {
+    if let Ok(mut current) = app.state::<AutoSyncState>().status.lock() {
+        *current = status.clone();
+    }
+    let _ = app.emit("sync-status", status);
+    match status.phase {
+        SyncPhase::Idle | SyncPhase::Partial => super::tray::refresh(app),
+        _ => super::tray::refresh_status(app),
+    }
+}
+
+/// 托盘提示中的同步状态，未启用同步时为空
+pub fn status_line(app: &AppHandle) -> Option<String> {
+    let status = app.state::<AutoSyncState>().status.lock().map(|s| s.clone()).unwrap_or_default();
+    let time = |ms: Option<i64>| {
+        ms.and_then(chrono::DateTime::from_timestamp_millis)
+            .map(|t| t.with_timezone(&chrono::Local).format("%H:%M").to_string())
+    };
+    Some(match status.phase {
+        SyncPhase::Disabled => return None,
+        SyncPhase::Syncing => t!(TraySyncing),
+        SyncPhase::Idle => t!(TrayLastSync, time(status.last_sync).unwrap_or_default()),
+        SyncPhase::Partial => t!(TrayLastSyncPartial, time(status.last_sync).unwrap_or_default()),
+        SyncPhase::Failed => match time(status.next_sync) {
+            Some(next) => t!(TraySyncFailed, next),
+            None => t!(TraySyncStopped),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        assert_eq!(backoff(1), BACKOFF_BASE);
+        assert_eq!(backoff(2), BACKOFF_BASE * 2);
+        assert_eq!(backoff(3), BACKOFF_BASE * 4);
+        assert_eq!(backoff(7), BACKOFF_MAX);
+        // 次数很大时不溢出
+        assert_eq!(backoff(u32::MAX), BACKOFF_MAX);
+    }
+}
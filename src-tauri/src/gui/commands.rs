@@ -0,0 +1,661 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{Emitter, Manager};
+
+use super::auto_refresh::AutoRefreshState;
+use super::auto_switch::AutoSwitchState;
+use super::auto_sync::{AutoSyncState, SyncStatus};
+use crate::manager::accounts::{self, ScanResult};
+use crate::manager::config::{get_accounts_dir, get_codex_dir, get_prompts_dir, get_skills_dir, load_config, update_config, AppConfig, AutoRefreshConfig, AutoSwitchConfig, AutoSyncConfig, NetworkConfig};
+use crate::manager::credentials::{self, SavedSyncTarget};
+use crate::manager::diagnostics;
+use crate::manager::error::AppError;
+use crate::manager::i18n::{self, Locale};
+use crate::manager::strategy::{self, SwitchDecision};
+use crate::manager::sync::{self, CodexSyncConfig, SyncMode, SyncResult};
+use crate::manager::sync_backend::SyncTarget;
+use crate::manager::token;
+use crate::manager::tombstone;
+use crate::manager::usage::{self, UsageInfo, UsageUpdate};
+use crate::manager::usage_history::{self, UsageHistory};
+use crate::manager::vault::{self, MigrationReport, VaultStatus};
+use crate::t;
+
+// ========== 账号命令 ==========
+
+/// 扫描账号目录，返回所有可用账号
+#[tauri::command]
+pub fn scan_accounts() -> Result<ScanResult, AppError> {
+    accounts::scan_accounts()
+}
+
+/// 切换到指定账号（复制认证文件到 ~/.codex/auth.json）
+#[tauri::command]
+pub fn switch_account(app: tauri::AppHandle, file_path: String) -> Result<(), AppError> {
+    accounts::switch_account(&file_path)?;
+    super::tray::refresh(&app);
+    Ok(())
+}
+
+/// 打开账号目录
+#[tauri::command]
+pub fn open_accounts_dir() -> Result<String, AppError> {
+    let dir = get_accounts_dir();
+    // 确保目录存在
+    if !dir.exists() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| AppError::io(&dir, e))?;
+    }
+    
+    // 使用系统命令打开目录
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    Ok(dir.to_string_lossy().to_string())
+}
+
+/// 获取账号目录路径
+#[tauri::command]
+pub fn get_accounts_dir_path() -> String {
+    let dir = get_accounts_dir();
+    let _ = fs::create_dir_all(&dir);
+    dir.to_string_lossy().to_string()
+}
+
+/// 获取应用配置
+#[tauri::command]
+pub fn get_app_config() -> AppConfig {
+    load_config()
+}
+
+/// 更新自动检查 / 自动切换设置，并唤醒后台任务立即生效
+#[tauri::command]
+pub fn set_auto_switch_config(app: tauri::AppHandle, config: AutoSwitchConfig) -> Result<(), AppError> {
+    update_config(|app_config| app_config.auto_switch = config)?;
+    app.state::<AutoSwitchState>().wake.notify_one();
+    Ok(())
+}
+
+/// 切换后端消息的语言，并按新语言重建托盘菜单
+#[tauri::command]
+pub fn set_locale(app: tauri::AppHandle, locale: Locale) -> Result<(), AppError> {
+    update_config(|app_config| app_config.locale = locale)?;
+    i18n::set_locale(locale);
+    super::tray::refresh(&app);
+    Ok(())
+}
+
+/// 更新后台 Token 刷新设置，并唤醒后台任务按新设置重新计算
+#[tauri::command]
+pub fn set_auto_refresh_config(app: tauri::AppHandle, config: AutoRefreshConfig) -> Result<(), AppError> {
+    update_config(|app_config| app_config.auto_refresh = config)?;
+    app.state::<AutoRefreshState>().wake.notify_one();
+    Ok(())
+}
+
+/// 更新代理与 CA 设置，之后创建的 HTTP 客户端即按新设置连接
+#[tauri::command]
+pub fn set_network_config(config: NetworkConfig) -> Result<(), AppError> {
+    update_config(|app_config| app_config.network = config)
+}
+
+/// 按当前策略预览下一个备用账号（前端传入已查询到的用量）
+#[tauri::command]
+pub fn preview_switch_candidate(usages: Vec<UsageUpdate>) -> Result<Option<SwitchDecision>, AppError> {
+    let scan = accounts::scan_accounts()?;
+    let config = load_config().auto_switch;
+    let candidates = strategy::candidates(&scan.accounts, &usages);
+    Ok(strategy::choose_backup(&config, &candidates))
+}
+
+/// 设置账号目录
+#[tauri::command]
+pub fn set_accounts_dir(app: tauri::AppHandle, path: String) -> Result<(), AppError> {
+    accounts::set_accounts_dir(&path)?;
+    super::tray::refresh(&app);
+    Ok(())
+}
+
+/// 重命名账号
+#[tauri::command]
+pub fn rename_account(app: tauri::AppHandle, old_path: String, new_name: String) -> Result<(), AppError> {
+    accounts::rename_account(&old_path, &new_name)?;
+    super::tray::refresh(&app);
+    Ok(())
+}
+
+/// 读取账号文件内容
+#[tauri::command]
+pub fn read_account_content(file_path: String) -> Result<String, AppError> {
+    accounts::read_account_content(&file_path)
+}
+
+/// 更新账号文件内容
+#[tauri::command]
+pub fn update_account_content(file_path: String, content: String) -> Result<(), AppError> {
+    accounts::update_account_content(&file_path, &content)
+}
+
+/// 添加账号 (Save raw JSON content)
+#[tauri::command]
+pub fn add_account(app: tauri::AppHandle, name: String, content: String) -> Result<(), AppError> {
+    accounts::add_account(&name, &content)?;
+    super::tray::refresh(&app);
+    Ok(())
+}
+
+// ========== 加密存储 ==========
+
+/// 获取加密存储状态
+#[tauri::command]
+pub fn get_vault_status() -> VaultStatus {
+    vault::status()
+}
+
+/// 使用口令解锁加密存储（密钥派生较慢，放在异步命令中避免阻塞界面）
+#[tauri::command]
+pub async fn unlock_vault(app: tauri::AppHandle, passphrase: String) -> Result<VaultStatus, AppError> {
+    vault::unlock(&passphrase)?;
+    // 解锁后加密账号才会出现在托盘菜单中
+    super::tray::refresh(&app);
+    Ok(vault::status())
+}
+
+/// 锁定加密存储，清除内存中的密钥
+#[tauri::command]
+pub fn lock_vault(app: tauri::AppHandle) -> VaultStatus {
+    vault::lock();
+    super::tray::refresh(&app);
+    vault::status()
+}
+
+/// 将现有明文账号迁移为加密存储
+#[tauri::command]
+pub async fn migrate_accounts_to_vault(passphrase: String) -> Result<MigrationReport, AppError> {
+    vault::migrate(&passphrase)
+}
+
+// ========== 用量 & Token ==========
+
+/// 获取账号的用量信息
+#[tauri::command]
+pub async fn fetch_usage(app: tauri::AppHandle, file_path: String) -> Result<UsageInfo, AppError> {
+    let usage = usage::fetch_usage(&file_path).await?;
+    super::tray::refresh(&app);
+    Ok(usage)
+}
+
+/// 并发查询所有账号的用量，每查完一个账号推送一次 `usage-updated` 事件，最后返回全部结果
+#[tauri::command]
+pub async fn fetch_all_usage(app: tauri::AppHandle) -> Result<Vec<UsageUpdate>, AppError> {
+    let scan = accounts::scan_accounts()?;
+    let file_paths: Vec<String> = scan.accounts.into_iter().map(|a| a.file_path).collect();
+    let config = load_config().usage_fetch;
+    let updates = usage::fetch_all(&file_paths, &config, |update| {
+        let _ = app.emit("usage-updated", [update]);
+    })
+    .await;
+    super::tray::refresh(&app);
+    Ok(updates)
+}
+
+/// 账号最近 `days` 天（默认 7 天）的用量历史和用满时间预测，不指定账号时返回全部
+#[tauri::command]
+pub fn get_usage_history(file_path: Option<String>, days: Option<u32>) -> Result<Vec<UsageHistory>, AppError> {
+    let days = days.unwrap_or(7);
+    let accounts = accounts::scan_accounts()?.accounts;
+    Ok(accounts
+        .iter()
+        .filter(|account| file_path.as_ref().is_none_or(|path| &account.file_path == path))
+        .map(|account| usage_history::history(account, days))
+        .collect())
+}
+
+/// 刷新指定账号的 Token
+#[tauri::command]
+pub async fn refresh_account_token(app: tauri::AppHandle, file_path: String) -> Result<String, AppError> {
+    let result = token::refresh_account_token(&file_path).await;
+    // 刷新前会先写回 Codex CLI 的 Token，无论成败都按账号目录的实际状态重建托盘
+    super::tray::refresh(&app);
+    result
+}
+
+// ========== 云端同步 ==========
+
+// 同步命令都使用后端保存的同步目标，凭据只在 save_sync_target 时传入一次
+
+/// 读取已保存的同步目标，凭据字段为空
+#[tauri::command]
+pub fn get_sync_target() -> SavedSyncTarget {
+    credentials::saved_sync_target()
+}
+
+/// 保存同步目标，凭据存入系统钥匙串；凭据字段为空时沿用已保存的值
+#[tauri::command]
+pub fn save_sync_target(app: tauri::AppHandle, target: SyncTarget) -> Result<SavedSyncTarget, AppError> {
+    let saved = credentials::save_sync_target(target)?;
+    app.state::<AutoSyncState>().wake.notify_one();
+    Ok(saved)
+}
+
+/// 同步账号文件，direction 为 upload / download / both
+#[tauri::command]
+pub async fn sync_accounts(app: tauri::AppHandle, direction: SyncMode) -> Result<SyncResult, AppError> {
+    let target = credentials::load_sync_target()?;
+    let result = sync::sync_accounts(&target, direction).await?;
+    // 下载、删除或重命名的账号同步到托盘菜单
+    super::tray::refresh(&app);
+    Ok(result)
+}
+
+/// 测试已保存的同步后端能否连接
+#[tauri::command]
+pub async fn test_sync_backend() -> Result<String, AppError> {
+    let target = credentials::load_sync_target()?;
+    sync::test_backend(&target.backend).await
+}
+
+/// 更新后台自动同步设置，并唤醒后台任务立即同步
+#[tauri::command]
+pub fn set_auto_sync_config(app: tauri::AppHandle, config: AutoSyncConfig) -> Result<(), AppError> {
+    update_config(|app_config| app_config.auto_sync = config)?;
+    app.state::<AutoSyncState>().wake.notify_one();
+    Ok(())
+}
+
+/// 读取后台自动同步的最新状态
+#[tauri::command]
+pub fn get_sync_status(state: tauri::State<'_, AutoSyncState>) -> SyncStatus {
+    state.status.lock().map(|status| status.clone()).unwrap_or_default()
+}
+
+/// 上传 Codex 配置 (prompts, skills, AGENTS.MD)
+#[tauri::command]
+pub async fn sync_codex_upload(sync_config: CodexSyncConfig) -> Result<SyncResult, AppError> {
+    let target = credentials::load_sync_target()?;
+    sync::sync_codex_upload(&target, &sync_config).await
+}
+
+/// 下载 Codex 配置
+#[tauri::command]
+pub async fn sync_codex_download(sync_config: CodexSyncConfig) -> Result<SyncResult, AppError> {
+    let target = credentials::load_sync_target()?;
+    sync::sync_codex_download(&target, &sync_config).await
+}
+
+// ========== Prompts & Skills 管理 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptInfo {
+    pub name: String,
+    pub description: String,
+    #[serde(rename = "argumentHint")]
+    pub argument_hint: Option<String>,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkillInfo {
+    pub name: String,
+    pub description: String,
+    pub compatibility: Option<String>,
+    #[serde(rename = "dirPath")]
+    pub dir_path: String,
+    #[serde(rename = "hasScripts")]
+    pub has_scripts: bool,
+    #[serde(rename = "hasAssets")]
+    pub has_assets: bool,
+    #[serde(rename = "hasReferences")]
+    pub has_references: bool,
+}
+
+/// 解析 Markdown frontmatter (YAML)
+fn parse_frontmatter(content: &str) -> Option<serde_json::Value> {
+    let content = content.trim();
+    if !content.starts_with("---") {
+        return None;
+    }
+    
+    let rest = &content[3..];
+    if let Some(end_idx) = rest.find("\n---") {
+        let yaml_str = &rest[..end_idx].trim();
+        // 简单解析 YAML 为 JSON
+        let mut map = serde_json::Map::new();
+        for line in yaml_str.lines() {
+            if let Some(colon_idx) = line.find(':') {
+                let key = line[..colon_idx].trim().to_string();
+                let value = line[colon_idx + 1..].trim();
+                // 去掉引号
+                let value = value.trim_matches('"').trim_matches('\'');
+                // 处理多行值 (以 > 开头)
+                if value == ">" || value == "|" {
+                    continue; // 跳过多行标记，后续行会被忽略
+                }
+                map.insert(key, serde_json::Value::String(value.to_string()));
+            }
+        }
+        if !map.is_empty() {
+            return Some(serde_json::Value::Object(map));
+        }
+    }
+    None
+}
+
+/// 递归扫描 prompts 目录
+fn scan_prompts_recursive(dir: &Path, prompts: &mut Vec<PromptInfo>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                // 递归扫描子目录
+                scan_prompts_recursive(&path, prompts);
+            } else if path.extension().and_then(|s| s.to_str()) == Some("md") {
+                if let Ok(content) = fs::read_to_string(&path) {
+                    let frontmatter = parse_frontmatter(&content);
+                    
+                    let name = path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .map(str::to_string)
+                        .unwrap_or_else(|| t!(Unnamed));
+                    
+                    let description = frontmatter.as_ref()
+                        .and_then(|fm| fm.get("description"))
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    
+                    let argument_hint = frontmatter.as_ref()
+                        .and_then(|fm| fm.get("argument-hint"))
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    
+                    prompts.push(PromptInfo {
+                        name,
+                        description,
+                        argument_hint,
+                        file_path: path.to_string_lossy().to_string(),
+                        content,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// 扫描所有 prompts
+#[tauri::command]
+pub fn scan_prompts() -> Result<Vec<PromptInfo>, AppError> {
+    let prompts_dir = get_prompts_dir();
+    let mut prompts = Vec::new();
+    
+    if prompts_dir.exists() {
+        scan_prompts_recursive(&prompts_dir, &mut prompts);
+    }
+    
+    Ok(prompts)
+}
+
+/// 扫描所有 skills
+#[tauri::command]
+pub fn scan_skills() -> Result<Vec<SkillInfo>, AppError> {
+    let skills_dir = get_skills_dir();
+    let mut skills = Vec::new();
+    
+    if !skills_dir.exists() {
+        return Ok(skills);
+    }
+    
+    if let Ok(entries) = fs::read_dir(&skills_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !path.is_dir() {
+                continue;
+            }
+            
+            // 跳过 .system 和 dist 目录
+            let dir_name = path.file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("");
+            if dir_name.starts_with('.') || dir_name == "dist" {
+                continue;
+            }
+            
+            let skill_md = path.join("SKILL.md");
+            if !skill_md.exists() {
+                continue;
+            }
+            
+            if let Ok(content) = fs::read_to_string(&skill_md) {
+                let frontmatter = parse_frontmatter(&content);
+                
+                let name = frontmatter.as_ref()
+                    .and_then(|fm| fm.get("name"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(dir_name)
+                    .to_string();
+                
+                let description = frontmatter.as_ref()
+                    .and_then(|fm| fm.get("description"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string();
+                
+                let compatibility = frontmatter.as_ref()
+                    .and_then(|fm| fm.get("compatibility"))
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string());
+                
+                skills.push(SkillInfo {
+                    name,
+                    description,
+                    compatibility,
+                    dir_path: path.to_string_lossy().to_string(),
+                    has_scripts: path.join("scripts").exists(),
+                    has_assets: path.join("assets").exists(),
+                    has_references: path.join("references").exists(),
+                });
+            }
+        }
+    }
+    
+    Ok(skills)
+}
+
+/// 读取 prompt 内容
+#[tauri::command]
+pub fn read_prompt_content(file_path: String) -> Result<String, AppError> {
+    fs::read_to_string(&file_path)
+        .map_err(|e| AppError::io(&file_path, e))
+}
+
+/// 保存 prompt 内容
+#[tauri::command]
+pub fn save_prompt_content(file_path: String, content: String) -> Result<(), AppError> {
+    fs::write(&file_path, content)
+        .map_err(|e| AppError::io(&file_path, e))
+}
+
+/// 创建新 prompt
+#[tauri::command]
+pub fn create_prompt(name: String, description: String, content: String) -> Result<String, AppError> {
+    let prompts_dir = get_prompts_dir();
+    if !prompts_dir.exists() {
+        fs::create_dir_all(&prompts_dir)
+            .map_err(|e| AppError::io(&prompts_dir, e))?;
+    }
+    
+    let file_name = format!("{}.md", name);
+    let file_path = prompts_dir.join(&file_name);
+    
+    if file_path.exists() {
+        return Err(AppError::AlreadyExists(format!("Prompt '{}'", name)));
+    }
+    
+    let full_content = format!(
+        "---\ndescription: {}\n---\n\n{}",
+        description, content
+    );
+    
+    fs::write(&file_path, full_content)
+        .map_err(|e| AppError::io(&file_path, e))?;
+    
+    Ok(file_path.to_string_lossy().to_string())
+}
+
+/// 删除 prompt，下次上传 Codex 配置时同步到远程
+#[tauri::command]
+pub fn delete_prompt(file_path: String) -> Result<(), AppError> {
+    tombstone::delete_codex_path(Path::new(&file_path))
+}
+
+/// 读取 skill 的 SKILL.md 内容
+#[tauri::command]
+pub fn read_skill_content(dir_path: String) -> Result<String, AppError> {
+    let skill_md = PathBuf::from(&dir_path).join("SKILL.md");
+    fs::read_to_string(&skill_md)
+        .map_err(|e| AppError::io(&skill_md, e))
+}
+
+/// 保存 skill 的 SKILL.md 内容
+#[tauri::command]
+pub fn save_skill_content(dir_path: String, content: String) -> Result<(), AppError> {
+    let skill_md = PathBuf::from(&dir_path).join("SKILL.md");
+    fs::write(&skill_md, content)
+        .map_err(|e| AppError::io(&skill_md, e))
+}
+
+/// 创建新 skill
+#[tauri::command]
+pub fn create_skill(name: String, description: String) -> Result<String, AppError> {
+    let skills_dir = get_skills_dir();
+    let skill_dir = skills_dir.join(&name);
+    
+    if skill_dir.exists() {
+        return Err(AppError::AlreadyExists(format!("Skill '{}'", name)));
+    }
+    
+    fs::create_dir_all(&skill_dir)
+        .map_err(|e| AppError::io(&skill_dir, e))?;
+    
+    let skill_md_content = format!(
+        "---\nname: {}\ndescription: {}\n---\n\n# {}\n\n## When to Use\n- TODO\n\n## When NOT to Use\n- TODO\n\n## Workflow\n1. TODO\n",
+        name, description, name
+    );
+    
+    let skill_md = skill_dir.join("SKILL.md");
+    fs::write(&skill_md, skill_md_content)
+        .map_err(|e| AppError::io(&skill_md, e))?;
+    
+    Ok(skill_dir.to_string_lossy().to_string())
+}
+
+/// 删除 skill，下次上传 Codex 配置时同步到远程
+#[tauri::command]
+pub fn delete_skill(dir_path: String) -> Result<(), AppError> {
+    tombstone::delete_codex_path(Path::new(&dir_path))
+}
+
+/// 读取 AGENTS.MD
+#[tauri::command]
+pub fn read_agents_md() -> Result<String, AppError> {
+    let agents_md = get_codex_dir().join("AGENTS.MD");
+    if agents_md.exists() {
+        fs::read_to_string(&agents_md)
+            .map_err(|e| AppError::io(&agents_md, e))
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// 保存 AGENTS.MD
+#[tauri::command]
+pub fn save_agents_md(content: String) -> Result<(), AppError> {
+    let agents_md = get_codex_dir().join("AGENTS.MD");
+    fs::write(&agents_md, content)
+        .map_err(|e| AppError::io(&agents_md, e))
+}
+
+/// 读取 config.toml
+#[tauri::command]
+pub fn read_config_toml() -> Result<String, AppError> {
+    let config_toml = get_codex_dir().join("config.toml");
+    if config_toml.exists() {
+        fs::read_to_string(&config_toml)
+            .map_err(|e| AppError::io(&config_toml, e))
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// 保存 config.toml
+#[tauri::command]
+pub fn save_config_toml(content: String) -> Result<(), AppError> {
+    let config_toml = get_codex_dir().join("config.toml");
+    fs::write(&config_toml, content)
+        .map_err(|e| AppError::io(&config_toml, e))
+}
+
+/// 打开 Codex 目录
+#[tauri::command]
+pub fn open_codex_dir() -> Result<String, AppError> {
+    let dir = get_codex_dir();
+    
+    #[cfg(target_os = "windows")]
+    {
+        std::process::Command::new("explorer")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        std::process::Command::new("open")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("xdg-open")
+            .arg(&dir)
+            .spawn()
+            .map_err(|e| AppError::Other(t!(OpenDirFailed, e)))?;
+    }
+    
+    Ok(dir.to_string_lossy().to_string())
+}
+
+// ========== 诊断 ==========
+
+/// 导出诊断包（脱敏后的日志、运行环境和配置）到指定的 zip 文件
+#[tauri::command]
+pub fn export_diagnostics(file_path: String) -> Result<String, AppError> {
+    diagnostics::export(Path::new(&file_path))?;
+    Ok(file_path)
+}
@@ -0,0 +1,101 @@
+use crate::manager::credentials;
+use crate::manager::logging::{self, Console};
+
+mod auth_watch;
+mod auto_refresh;
+mod auto_switch;
+mod auto_sync;
+mod commands;
+mod tray;
+
+// ========== 入口 ==========
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    logging::init(Console::Stdout);
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(auto_switch::AutoSwitchState::default())
+        .manage(auto_sync::AutoSyncState::default())
+        .manage(auto_refresh::AutoRefreshState::default())
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // 阻止默认关闭行为，改为隐藏窗口
+                api.prevent_close();
+                let _ = window.hide();
+            }
+        })
+        .setup(|app| {
+            // 托盘图标和菜单
+            tray::build(app)?;
+
+            // 启动后台用量检查 / 自动切换任务
+            auto_switch::spawn(app.handle().clone());
+            // 旧版本保存在 config.json 中的同步密码移入钥匙串
+            if let Err(e) = credentials::migrate_legacy_target() {
+                tracing::warn!(error = %e, "迁移同步凭据失败");
+            }
+            // 启动后台自动同步任务
+            auto_sync::spawn(app.handle().clone());
+            // 跟踪 Codex CLI 对 auth.json 的刷新
+            auth_watch::spawn(app.handle().clone());
+            // 启动后台 Token 刷新任务
+            auto_refresh::spawn(app.handle().clone());
+            
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            commands::scan_accounts,
+            commands::switch_account,
+            commands::get_accounts_dir_path,
+            commands::open_accounts_dir,
+            commands::fetch_usage,
+            commands::fetch_all_usage,
+            commands::get_usage_history,
+            commands::rename_account,
+            commands::get_app_config,
+            commands::set_auto_switch_config,
+            commands::set_locale,
+            commands::preview_switch_candidate,
+            commands::set_accounts_dir,
+            commands::add_account,
+            commands::read_account_content,
+            commands::update_account_content,
+            commands::get_vault_status,
+            commands::unlock_vault,
+            commands::lock_vault,
+            commands::migrate_accounts_to_vault,
+            commands::sync_accounts,
+            commands::test_sync_backend,
+            commands::get_sync_target,
+            commands::save_sync_target,
+            commands::set_auto_sync_config,
+            commands::set_auto_refresh_config,
+            commands::set_network_config,
+            commands::get_sync_status,
+            // Prompts & Skills
+            commands::scan_prompts,
+            commands::scan_skills,
+            commands::read_prompt_content,
+            commands::save_prompt_content,
+            commands::create_prompt,
+            commands::delete_prompt,
+            commands::read_skill_content,
+            commands::save_skill_content,
+            commands::create_skill,
+            commands::delete_skill,
+            commands::read_agents_md,
+            commands::save_agents_md,
+            commands::read_config_toml,
+            commands::save_config_toml,
+            commands::open_codex_dir,
+            commands::sync_codex_upload,
+            commands::sync_codex_download,
+            commands::refresh_account_token,
+            commands::export_diagnostics
+        ])
+        .run(tauri::generate_context!())
+        .expect("error while running tauri application");
+}
@@ -0,0 +1,295 @@
+use tauri::image::Image;
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, TrayIconBuilder, TrayIconEvent};
+use tauri::{App, AppHandle, Emitter, Manager, Wry};
+
+use super::auto_switch::AccountSwitchedEvent;
+use super::auto_sync;
+use crate::manager::accounts::{self, AccountInfo, ScanResult};
+use crate::manager::error::AppError;
+use crate::manager::usage::RateLimitWindow;
+use crate::manager::usage_history::{self, UsageSample};
+use crate::t;
+
+// ========== 托盘 ==========
+//
+// 菜单列出所有账号，点击即切换；图标右下角的圆点按当前账号 5 小时窗口的用量显示绿 / 黄 / 红，
+// 提示文字显示当前账号、两个窗口的用量和重置倒计时以及同步状态。
+// 用量取自用量历史中最近一次查询的结果，每次查询用量、切换账号后重建。
+
+/// 托盘图标 ID，后台任务通过它更新托盘
+const TRAY_ID: &str = "main";
+
+/// 账号菜单项的 ID 前缀，后接账号文件路径
+const ACCOUNT_ITEM_PREFIX: &str = "account:";
+
+/// 5 小时窗口用量达到这些百分比时图标标记变为黄色 / 红色
+const AMBER_PERCENT: f64 = 70.0;
+const RED_PERCENT: f64 = 90.0;
+
+/// 创建托盘图标（在 `setup` 中调用）
+pub fn build(app: &App) -> tauri::Result<()> {
+    let menu = Menu::new(app)?;
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| on_menu(app, event.id.as_ref()))
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, .. } = event {
+                if let Some(window) = tray.app_handle().get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    refresh(app.handle());
+    Ok(())
+}
+
+/// 重建托盘菜单、图标和提示文字
+///
+/// 切换账号（手动、托盘或自动切换）、查询用量、auth.json 变化和切换语言后调用。
+pub fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let scan = accounts::scan_accounts();
+    match menu(app, &scan) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => tracing::warn!(error = %e, "重建托盘菜单失败"),
+    }
+    update_status(app, &scan);
+}
+
+/// 只更新图标和提示文字（同步状态变化时调用）
+pub fn refresh_status(app: &AppHandle) {
+    update_status(app, &accounts::scan_accounts());
+}
+
+// ========== 菜单 ==========
+
+/// 处理托盘菜单点击
+fn on_menu(app: &AppHandle, id: &str) {
+    if let Some(file_path) = id.strip_prefix(ACCOUNT_ITEM_PREFIX) {
+        switch_from_tray(app, file_path);
+        return;
+    }
+    match id {
+        "show" => {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        "refresh" => {
+            refresh(app);
+            // 触发前端刷新，查询完成后会再次重建菜单
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.emit("tray-refresh", ());
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        "quit" => {
+            app.exit(0);
+        }
+        _ => {}
+    }
+}
+
+/// 切换到托盘菜单中点击的账号，通过 `account-switched` 事件通知前端
+fn switch_from_tray(app: &AppHandle, file_path: &str) {
+    let result = accounts::scan_accounts().and_then(|scan| {
+        let Some(target) = scan.accounts.iter().find(|a| a.file_path == file_path) else {
+            return Err(AppError::NotFound(t!(Account, file_path)));
+        };
+        if target.is_active {
+            return Ok(None);
+        }
+        accounts::switch_account(file_path)?;
+        Ok(Some(AccountSwitchedEvent {
+            from: scan.accounts.iter().find(|a| a.is_active).map(|a| a.email.clone()),
+            to: target.email.clone(),
+            file_path: file_path.to_string(),
+            reason: t!(SwitchFromTray),
+        }))
+    });
+    match result {
+        Ok(Some(event)) => {
+            tracing::info!(to = %event.to, "已从托盘菜单切换账号");
+            let _ = app.emit("account-switched", event);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(error = %e, "从托盘菜单切换账号失败"),
+    }
+    // 点击会改变勾选状态，无论是否切换都按实际状态重建
+    refresh(app);
+}
+
+/// 剩余额度百分比；最近一次记录之后窗口已重置时视为全部剩余
+fn remaining(window: Option<&RateLimitWindow>, now: i64) -> String {
+    match window {
+        None => "-".to_string(),
+        Some(w) => format!("{:.0}%", 100.0 - used_percent(w, now)),
+    }
+}
+
+/// 账号菜单项的标题：名称和最近一次查询到的剩余额度
+fn account_label(account: &AccountInfo, now: i64) -> String {
+    match usage_history::latest(&account.id, &account.email) {
+        Some(sample) => t!(
+            TrayAccountUsage,
+            account.name,
+            remaining(sample.usage.primary_window.as_ref(), now),
+            remaining(sample.usage.secondary_window.as_ref(), now)
+        ),
+        None => account.name.clone(),
+    }
+}
+
+/// 按当前语言构建托盘菜单：所有账号（勾选当前账号，点击切换）、显示窗口、刷新、退出
+fn menu(app: &AppHandle, scan: &Result<ScanResult, AppError>) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(app)?;
+    match scan {
+        Ok(scan) if !scan.accounts.is_empty() => {
+            let now = chrono::Utc::now().timestamp();
+            for account in &scan.accounts {
+                let id = format!("{}{}", ACCOUNT_ITEM_PREFIX, account.file_path);
+                let label = account_label(account, now);
+                menu.append(&CheckMenuItem::with_id(app, id, label, true, account.is_active, None::<&str>)?)?;
+            }
+        }
+        Ok(_) => menu.append(&MenuItem::with_id(app, "accounts", t!(TrayNoAccounts), false, None::<&str>)?)?,
+        Err(e) => {
+            tracing::warn!(error = %e, "托盘菜单读取账号失败");
+            menu.append(&MenuItem::with_id(app, "accounts", t!(TrayAccountsUnavailable), false, None::<&str>)?)?;
+        }
+    }
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "show", t!(TrayShow), true, None::<&str>)?)?;
+    menu.append(&MenuItem::with_id(app, "refresh", t!(TrayRefresh), true, None::<&str>)?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    menu.append(&MenuItem::with_id(app, "quit", t!(TrayQuit), true, None::<&str>)?)?;
+    Ok(menu)
+}
+
+// ========== 图标与提示 ==========
+
+/// 窗口的已用百分比；最近一次记录之后窗口已重置时为 0
+fn used_percent(window: &RateLimitWindow, now: i64) -> f64 {
+    if window.resets_at.is_some_and(|reset| reset <= now) {
+        0.0
+    } else {
+        window.used_percent.clamp(0.0, 100.0)
+    }
+}
+
+fn update_status(app: &AppHandle, scan: &Result<ScanResult, AppError>) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let now = chrono::Utc::now().timestamp();
+    let active = scan.as_ref().ok().and_then(|scan| scan.accounts.iter().find(|a| a.is_active));
+    let sample = active.and_then(|account| usage_history::latest(&account.id, &account.email));
+
+    let mut lines = vec!["Codex Manager".to_string()];
+    match active {
+        Some(account) => lines.extend(account_lines(account, sample.as_ref(), now)),
+        None => lines.push(t!(TrayNoActiveAccount)),
+    }
+    lines.extend(auto_sync::status_line(app));
+    let _ = tray.set_tooltip(Some(lines.join("\n")));
+
+    let badge = sample
+        .as_ref()
+        .and_then(|sample| sample.usage.primary_window.as_ref())
+        .map(|window| badge_color(used_percent(window, now)));
+    if let Some(icon) = app.default_window_icon() {
+        let icon = match badge {
+            Some(color) => with_badge(icon, color),
+            None => icon.clone(),
+        };
+        let _ = tray.set_icon(Some(icon));
+    }
+}
+
+/// 提示文字中当前账号的部分：邮箱和套餐、两个窗口的用量与重置倒计时
+fn account_lines(account: &AccountInfo, sample: Option<&UsageSample>, now: i64) -> Vec<String> {
+    let mut lines = vec![format!("{} · {}", account.display_email(), account.plan_type)];
+    let Some(sample) = sample else {
+        lines.push(t!(TrayUsageUnknown));
+        return lines;
+    };
+    let windows = [
+        (t!(TrayWindowPrimary), sample.usage.primary_window.as_ref()),
+        (t!(TrayWindowSecondary), sample.usage.secondary_window.as_ref()),
+    ];
+    for (name, window) in windows {
+        let Some(window) = window else { continue };
+        lines.push(match window.resets_at {
+            Some(reset) if reset <= now => t!(TrayWindowReset, name),
+            Some(reset) => t!(TrayWindowUsed, name, format!("{:.0}", window.used_percent), countdown(reset - now)),
+            None => t!(TrayWindowUsedNoReset, name, format!("{:.0}", window.used_percent)),
+        });
+    }
+    lines
+}
+
+/// 重置倒计时，精确到分钟
+fn countdown(seconds: i64) -> String {
+    let minutes = (seconds + 59) / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        t!(DurationDaysHours, days, hours)
+    } else if hours > 0 {
+        t!(DurationHoursMinutes, hours, minutes)
+    } else {
+        t!(DurationMinutes, minutes)
+    }
+}
+
+/// 按 5 小时窗口的用量选择标记颜色
+fn badge_color(used_percent: f64) -> [u8; 3] {
+    if used_percent >= RED_PERCENT {
+        [0xEF, 0x44, 0x44]
+    } else if used_percent >= AMBER_PERCENT {
+        [0xF5, 0x9E, 0x0B]
+    } else {
+        [0x22, 0xC5, 0x5E]
+    }
+}
+
+/// 在图标右下角画一个带白边的圆点
+fn with_badge(icon: &Image<'_>, color: [u8; 3]) -> Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    let size = width.min(height) as f64;
+    let radius = size * 0.25;
+    let border = (size * 0.05).max(1.0);
+    let (cx, cy) = (width as f64 - radius - 1.0, height as f64 - radius - 1.0);
+
+    for y in 0..height {
+        for x in 0..width {
+            let distance = ((x as f64 + 0.5 - cx).powi(2) + (y as f64 + 0.5 - cy).powi(2)).sqrt();
+            // 边缘一个像素内按距离渐变，避免锯齿
+            let coverage = (radius + border - distance + 0.5).clamp(0.0, 1.0);
+            if coverage <= 0.0 {
+                continue;
+            }
+            let fill = (radius - distance + 0.5).clamp(0.0, 1.0);
+            let paint: [f64; 3] = std::array::from_fn(|i| f64::from(color[i]) * fill + 255.0 * (1.0 - fill));
+            let pixel = &mut rgba[((y * width + x) * 4) as usize..][..4];
+            for i in 0..3 {
+                pixel[i] = (paint[i] * coverage + f64::from(pixel[i]) * (1.0 - coverage)).round() as u8;
+            }
+            pixel[3] = (255.0 * coverage + f64::from(pixel[3]) * (1.0 - coverage)).round() as u8;
+        }
+    }
+    Image::new_owned(rgba, width, height)
+}
@@ -0,0 +1,442 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fs;
+use std::path::PathBuf;
+
+use super::atomic;
+use super::auth::{extract_info_from_auth, CodexAuthFile};
+use super::config::{get_accounts_dir, get_codex_auth_file, update_config};
+use super::error::AppError;
+use super::sync_manifest::compare_freshness;
+use super::token::{self, RefreshFailure};
+use super::vault::{self, VaultStatus};
+
+use crate::t;
+
+// ========== 账号管理 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountInfo {
+    pub id: String,           // account_id
+    pub name: String,         // 文件名（不含扩展名）
+    pub email: String,        // 从 JWT 解析，没有时为空（显示用 `display_email`）
+    #[serde(rename = "planType")]
+    pub plan_type: String,
+    #[serde(rename = "subscriptionEnd")]
+    pub subscription_end: Option<String>,
+    #[serde(rename = "isActive")]
+    pub is_active: bool,
+    #[serde(rename = "filePath")]
+    pub file_path: String,    // 认证文件完整路径
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<i64>, // Token 过期时间戳
+    #[serde(rename = "lastRefresh")]
+    pub last_refresh: String,
+    /// 刷新 Token 时的永久失败，需要重新登录
+    #[serde(rename = "refreshFailure")]
+    pub refresh_failure: Option<RefreshFailure>,
+}
+
+impl AccountInfo {
+    /// 显示用的邮箱，Token 中没有邮箱时显示当前语言的“未知”
+    pub fn display_email(&self) -> String {
+        if self.email.is_empty() {
+            t!(Unknown)
+        } else {
+            self.email.clone()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub accounts: Vec<AccountInfo>,
+    #[serde(rename = "accountsDir")]
+    pub accounts_dir: String,
+    pub vault: VaultStatus,  // 加密存储状态，锁定时加密账号不会出现在列表中
+}
+
+/// 扫描账号目录，返回所有可用账号
+pub fn scan_accounts() -> Result<ScanResult, AppError> {
+    let accounts_dir = get_accounts_dir();
+    let codex_auth = get_codex_auth_file();
+    
+    // 确保目录存在
+    if !accounts_dir.exists() {
+        fs::create_dir_all(&accounts_dir)
+            .map_err(|e| AppError::io(&accounts_dir, e))?;
+    }
+    
+    // 读取当前激活的账号
+    let active_auth = if codex_auth.exists() {
+        fs::read_to_string(&codex_auth)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok())
+    } else {
+        None
+    };
+    
+    // 扫描目录中的所有 json 文件
+    let mut accounts = Vec::new();
+    
+    if let Ok(entries) = fs::read_dir(&accounts_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            
+            // 只处理 .json 文件
+            if path.extension().and_then(|s| s.to_str()) != Some("json") {
+                continue;
+            }
+            
+            // 读取并解析（加密文件在解锁后透明解密）
+            if let Ok(auth) = vault::read_auth(&path) {
+                let (email, plan_type, subscription_end, expires_at) = extract_info_from_auth(&auth);
+                let email = email.unwrap_or_default();
+                
+                let name = path.file_stem()
+                    .and_then(|s| s.to_str())
+                    .map(str::to_string)
+                    .unwrap_or_else(|| t!(Unnamed));
+                
+                let is_active = active_auth.as_ref().is_some_and(|active| same_seat(active, &auth));
+                
+                let refresh_failure = token::failure_for(&auth);
+                accounts.push(AccountInfo {
+                    id: auth.tokens.account_id,
+                    name,
+                    email,
+                    plan_type,
+                    subscription_end,
+                    is_active,
+                    file_path: path.to_string_lossy().to_string(),
+                    expires_at,
+                    last_refresh: auth.last_refresh.clone(),
+                    refresh_failure,
+                });
+            }
+        }
+    }
+    
+    Ok(ScanResult {
+        accounts,
+        accounts_dir: accounts_dir.to_string_lossy().to_string(),
+        vault: vault::status(),
+    })
+}
+
+/// 两份认证文件是否属于同一个席位
+///
+/// team 账号中多个成员共用 account_id，还需比较邮箱，否则同一团队的其他席位都会被当作当前账号。
+pub fn same_seat(a: &CodexAuthFile, b: &CodexAuthFile) -> bool {
+    a.tokens.account_id == b.tokens.account_id && extract_info_from_auth(a).0 == extract_info_from_auth(b).0
+}
+
+/// 切换到指定账号（复制认证文件到 ~/.codex/auth.json）
+///
+/// 覆盖前先把 Codex CLI 刷新过的 Token 写回当前账号的文件，
+/// 否则切回该账号时会恢复已失效的 refresh_token。
+pub fn switch_account(file_path: &str) -> Result<(), AppError> {
+    let source = PathBuf::from(file_path);
+    let target = get_codex_auth_file();
+    
+    if !source.exists() {
+        return Err(AppError::NotFound(file_path.to_string()));
+    }
+    
+    // 确保目标目录存在
+    if let Some(parent) = target.parent() {
+        if !parent.exists() {
+            fs::create_dir_all(parent)
+                .map_err(|e| AppError::io(parent, e))?;
+        }
+    }
+    
+    let _lock = atomic::lock()?;
+    if let Err(e) = write_back_locked() {
+        tracing::warn!(error = %e, "写回 Codex CLI 刷新的 Token 失败");
+    }
+
+    // 写入明文副本（加密账号先解密），原子替换，Codex CLI 不会读到写了一半的文件
+    let content = vault::read_plaintext(&source)?;
+    atomic::write_atomic(&target, content.as_bytes())?;
+    
+    Ok(())
+}
+
+/// Codex CLI 自行刷新 ~/.codex/auth.json 后，把新 Token 写回对应的账号文件，
+/// 返回被更新的账号名
+pub fn write_back_live_auth() -> Result<Option<String>, AppError> {
+    let _lock = atomic::lock()?;
+    write_back_locked()
+}
+
+/// 同 `write_back_live_auth`，调用方需已持有写入锁
+fn write_back_locked() -> Result<Option<String>, AppError> {
+    let live_path = get_codex_auth_file();
+    let Ok(live_content) = fs::read_to_string(&live_path) else {
+        return Ok(None);
+    };
+    let live: CodexAuthFile = serde_json::from_str(&live_content)
+        .map_err(|e| AppError::InvalidAuthJson(format!("{}: {}", live_path.display(), e)))?;
+
+    let entries = match fs::read_dir(get_accounts_dir()) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(None),
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        // 加密存储锁定时读不到，等解锁后再写回
+        let Ok(stored) = vault::read_auth(&path) else {
+            continue;
+        };
+        if !same_seat(&stored, &live) {
+            continue;
+        }
+        let same_tokens = stored.tokens.refresh_token == live.tokens.refresh_token
+            && stored.tokens.access_token == live.tokens.access_token
+            && stored.tokens.id_token == live.tokens.id_token;
+        // 本应用刷新过而 auth.json 仍是旧 Token 时，不能反向覆盖
+        if same_tokens || compare_freshness(&live, &stored) != Some(Ordering::Greater) {
+            return Ok(None);
+        }
+
+        atomic::write_atomic(&path, vault::encode_plaintext(&live_content)?.as_bytes())?;
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+        tracing::info!(account = %name, "已将 Codex CLI 刷新的 Token 写回账号");
+        return Ok(Some(name));
+    }
+    Ok(None)
+}
+
+/// 设置账号目录
+pub fn set_accounts_dir(path: &str) -> Result<(), AppError> {
+    let old_dir = get_accounts_dir();
+    let new_dir = PathBuf::from(path);
+
+    // Save configuration
+    update_config(|config| config.accounts_dir = Some(path.to_string()))?;
+
+    // Auto-copy specific logic
+    if old_dir != new_dir && old_dir.exists() {
+         // Create new directory if needed
+        if !new_dir.exists() {
+            fs::create_dir_all(&new_dir).map_err(|e| AppError::io(&new_dir, e))?;
+        }
+
+        // Iterate and copy
+        if let Ok(entries) = fs::read_dir(&old_dir) {
+            for entry in entries.flatten() {
+                let entry_path = entry.path();
+                let is_vault_meta = entry_path.file_name().is_some_and(|name| name == vault::VAULT_META_FILE);
+                if entry_path.is_file() && (is_vault_meta || entry_path.extension().is_some_and(|ext| ext == "json")) {
+                    if let Some(file_name) = entry_path.file_name() {
+                        let target_path = new_dir.join(file_name);
+                        // Copy but don't error if fail (e.g. exists)
+                        // Or maybe we want to preserve? Let's just copy.
+                        if !target_path.exists() {
+                            let _ = fs::copy(&entry_path, &target_path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+/// 重命名账号
+pub fn rename_account(old_path: &str, new_name: &str) -> Result<(), AppError> {
+    let source = PathBuf::from(old_path);
+    if !source.exists() {
+        return Err(AppError::NotFound(old_path.to_string()));
+    }
+    
+    let parent = source.parent().ok_or_else(|| AppError::InvalidInput(t!(InvalidPath, old_path)))?;
+    let target = parent.join(format!("{}.json", new_name));
+    
+    if target.exists() {
+        return Err(AppError::AlreadyExists(new_name.to_string()));
+    }
+    
+    fs::rename(source, &target)
+        .map_err(|e| AppError::io(&target, e))?;
+        
+    Ok(())
+}
+
+/// 读取账号文件内容
+pub fn read_account_content(file_path: &str) -> Result<String, AppError> {
+    let path = PathBuf::from(file_path);
+    if !path.exists() {
+        return Err(AppError::NotFound(file_path.to_string()));
+    }
+    
+    let content = vault::read_plaintext(&path)?;
+    
+    // 格式化 JSON
+    let parsed: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| AppError::InvalidAuthJson(e.to_string()))?;
+    
+    serde_json::to_string_pretty(&parsed)
+        .map_err(|e| AppError::Other(t!(SerializeFailed, e)))
+}
+
+/// 更新账号文件内容
+pub fn update_account_content(file_path: &str, content: &str) -> Result<(), AppError> {
+    let path = PathBuf::from(file_path);
+    if !path.exists() {
+        return Err(AppError::NotFound(file_path.to_string()));
+    }
+    
+    // 验证 JSON 格式
+    let auth: CodexAuthFile = serde_json::from_str(content)
+        .map_err(|e| AppError::InvalidAuthJson(e.to_string()))?;
+    
+    // 格式化并写入（启用加密存储时自动加密）
+    vault::write_auth(&path, &auth)
+}
+
+/// 添加账号 (Save raw JSON content)
+pub fn add_account(name: &str, content: &str) -> Result<(), AppError> {
+    // 1. 验证 JSON 格式
+    let auth: CodexAuthFile = serde_json::from_str(content)
+        .map_err(|e| AppError::InvalidAuthJson(e.to_string()))?;
+    
+    // 2. 决定文件名
+    let file_name = if !name.trim().is_empty() {
+        name.trim().to_string()
+    } else {
+        // 尝试从 Token 提取 Email
+        match extract_info_from_auth(&auth).0 {
+            Some(email) => email,
+            // 随机或默认
+            None => format!("account_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()),
+        }
+    };
+
+    // 3. 构建路径
+    let accounts_dir = get_accounts_dir();
+    if !accounts_dir.exists() {
+        fs::create_dir_all(&accounts_dir)
+            .map_err(|e| AppError::io(&accounts_dir, e))?;
+    }
+    
+    let target_path = accounts_dir.join(format!("{}.json", file_name));
+    
+    // 4. 检查是否存在
+    if target_path.exists() {
+        return Err(AppError::AlreadyExists(t!(Account, file_name)));
+    }
+    
+    // 5. 写入文件 (Pretty Print，启用加密存储时自动加密)
+    vault::write_auth(&target_path, &auth)
+}
+
+/// 按名称、邮箱或 account_id 查找账号（命令行使用）
+pub fn find_account(query: &str) -> Result<AccountInfo, AppError> {
+    let scan = scan_accounts()?;
+    let query = query.trim();
+    scan.accounts
+        .into_iter()
+        .find(|a| {
+            a.name.eq_ignore_ascii_case(query)
+                || (!a.email.is_empty() && a.email.eq_ignore_ascii_case(query))
+                || a.id == query
+        })
+        .ok_or_else(|| AppError::NotFound(t!(Account, query)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::crypto::Envelope;
+    use crate::manager::test_support::{self, auth, write_auth as write};
+
+    #[test]
+    fn only_the_live_seat_is_active() {
+        let home = test_support::home();
+        let accounts = home.path.join(".myswitch/accounts");
+        write(&accounts.join("seat-a.json"), &auth("team", "a@corp.com", "rt-a", "2026-01-01T00:00:00Z"));
+        write(&accounts.join("seat-b.json"), &auth("team", "b@corp.com", "rt-b", "2026-01-01T00:00:00Z"));
+        write(&accounts.join("other.json"), &auth("solo", "a@corp.com", "rt-c", "2026-01-01T00:00:00Z"));
+        write(&get_codex_auth_file(), &auth("team", "a@corp.com", "rt-a", "2026-01-01T00:00:00Z"));
+
+        let mut active: Vec<String> = scan_accounts()
+            .unwrap()
+            .accounts
+            .into_iter()
+            .filter(|a| a.is_active)
+            .map(|a| a.name)
+            .collect();
+        active.sort();
+        assert_eq!(active, ["seat-a"]);
+    }
+
+    #[test]
+    fn writes_back_only_newer_live_tokens() {
+        let home = test_support::home();
+        let account = home.path.join(".myswitch/accounts/a.json");
+        write(&account, &auth("solo", "a@example.com", "rt-1", "2026-01-01T00:00:00Z"));
+
+        // Codex CLI 刷新后 auth.json 较新：写回账号
+        write(&get_codex_auth_file(), &auth("solo", "a@example.com", "rt-2", "2026-01-02T00:00:00Z"));
+        assert_eq!(write_back_live_auth().unwrap().as_deref(), Some("a"));
+        assert_eq!(vault::read_auth(&account).unwrap().tokens.refresh_token, "rt-2");
+
+        // 本应用刷新过账号而 auth.json 仍是旧 Token：不能反向覆盖
+        write(&account, &auth("solo", "a@example.com", "rt-3", "2026-01-03T00:00:00Z"));
+        assert_eq!(write_back_live_auth().unwrap(), None);
+        assert_eq!(vault::read_auth(&account).unwrap().tokens.refresh_token, "rt-3");
+    }
+
+    #[test]
+    fn writes_back_to_the_matching_team_seat_only() {
+        let home = test_support::home();
+        let accounts = home.path.join(".myswitch/accounts");
+        write(&accounts.join("seat-a.json"), &auth("team", "a@corp.com", "rt-a", "2026-01-01T00:00:00Z"));
+        write(&accounts.join("seat-b.json"), &auth("team", "b@corp.com", "rt-b", "2026-01-01T00:00:00Z"));
+        write(&get_codex_auth_file(), &auth("team", "b@corp.com", "rt-b2", "2026-01-02T00:00:00Z"));
+
+        assert_eq!(write_back_live_auth().unwrap().as_deref(), Some("seat-b"));
+        assert_eq!(vault::read_auth(&accounts.join("seat-a.json")).unwrap().tokens.refresh_token, "rt-a");
+        assert_eq!(vault::read_auth(&accounts.join("seat-b.json")).unwrap().tokens.refresh_token, "rt-b2");
+    }
+
+    #[test]
+    fn waits_for_unlock_before_writing_back_to_vault() {
+        let home = test_support::home();
+        let account = home.path.join(".myswitch/accounts/a.json");
+        write(&account, &auth("solo", "a@example.com", "rt-1", "2026-01-01T00:00:00Z"));
+        vault::migrate("correct horse battery").unwrap();
+        vault::lock();
+        let sealed = fs::read(&account).unwrap();
+
+        write(&get_codex_auth_file(), &auth("solo", "a@example.com", "rt-2", "2026-01-02T00:00:00Z"));
+        assert_eq!(write_back_live_auth().unwrap(), None);
+        assert_eq!(fs::read(&account).unwrap(), sealed);
+
+        // 解锁后写回，账号文件仍保持加密
+        vault::unlock("correct horse battery").unwrap();
+        assert_eq!(write_back_live_auth().unwrap().as_deref(), Some("a"));
+        assert!(Envelope::parse(&fs::read(&account).unwrap()).is_some());
+        assert_eq!(vault::read_auth(&account).unwrap().tokens.refresh_token, "rt-2");
+    }
+
+    #[test]
+    fn missing_email_stays_empty() {
+        let _home = test_support::home();
+        let content = serde_json::to_string(&auth("solo", "", "rt-a", "2026-01-01T00:00:00Z")).unwrap();
+        add_account("", &content).unwrap();
+
+        let account = scan_accounts().unwrap().accounts.remove(0);
+        assert!(account.name.starts_with("account_"));
+        assert_eq!(account.email, "");
+        assert_eq!(account.display_email(), t!(Unknown));
+        // 空邮箱不能匹配任何查询
+        assert!(find_account("").is_err());
+    }
+}
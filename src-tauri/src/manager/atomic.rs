@@ -0,0 +1,178 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use super::config::get_write_lock_file;
+use super::error::AppError;
+
+use crate::t;
+
+// ========== 原子写入与文件锁 ==========
+//
+// ~/.codex/auth.json 和账号文件可能同时被后台刷新、自动切换、同步和命令行进程写入，
+// Codex CLI 也会随时读取或刷新 auth.json。写入时先写同目录下的临时文件并 fsync，
+// 再 rename 覆盖目标，读取方只会看到完整的旧文件或新文件。
+//
+// 应用内（包括命令行进程）的写入者另外共用 ~/.myswitch/auth.lock 上的咨询锁，
+// 避免互相覆盖；Codex CLI 不理会这把锁，只能依赖 rename 的原子性。
+
+/// 同一进程内区分临时文件
+static TMP_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// 持有期间其他写入者等待，drop 时释放
+pub struct WriteLock {
+    _file: File,
+}
+
+/// 获取写入锁，其他进程持有时阻塞等待
+pub fn lock() -> Result<WriteLock, AppError> {
+    let path = get_write_lock_file();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+    }
+    let file = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| AppError::io(&path, e))?;
+    file.lock().map_err(|e| AppError::io(&path, e))?;
+    Ok(WriteLock { _file: file })
+}
+
+/// 在阻塞线程池中持锁执行 `f`，供异步任务使用，等待锁时不占用运行时的工作线程
+pub async fn with_lock<T, F>(f: F) -> Result<T, AppError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, AppError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || {
+        let _lock = lock()?;
+        f()
+    })
+    .await
+    .map_err(|e| AppError::Other(e.to_string()))?
+}
+
+/// 持锁原子写入文件
+pub fn write_file(path: &Path, content: &[u8]) -> Result<(), AppError> {
+    let _lock = lock()?;
+    write_atomic(path, content)
+}
+
+/// 原子写入文件，新文件仅当前用户可读写（0600）；调用方需已持有 `lock()`
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), AppError> {
+    let parent = path.parent().ok_or_else(|| AppError::InvalidInput(t!(InvalidPath, path.display())))?;
+    fs::create_dir_all(parent).map_err(|e| AppError::io(parent, e))?;
+
+    let tmp = tmp_path(path);
+    let result = write_tmp(&tmp, content)
+        .and_then(|_| fs::rename(&tmp, path).map_err(|e| AppError::io(path, e)));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+        return result;
+    }
+
+    // 目录项也落盘，避免断电后 rename 丢失（Windows 不支持打开目录，忽略）
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// 与目标同目录的临时文件，以 `.` 开头且不以 .json 结尾，不会被当作账号扫描
+fn tmp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().and_then(|s| s.to_str()).unwrap_or("file");
+    let seq = TMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}-{}.tmp", name, std::process::id(), seq))
+}
+
+fn write_tmp(tmp: &Path, content: &[u8]) -> Result<(), AppError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(tmp)
+        .map_err(|e| AppError::io(tmp, e))?;
+    file.write_all(content).map_err(|e| AppError::io(tmp, e))?;
+    file.sync_all().map_err(|e| AppError::io(tmp, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support;
+    use std::time::Duration;
+
+    #[cfg(unix)]
+    #[test]
+    fn new_files_are_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let home = test_support::home();
+        let path = home.path.join("dir/auth.json");
+        write_file(&path, b"{}").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn failed_write_keeps_original() {
+        let home = test_support::home();
+        // 文件名本身合法，但加上临时文件的前后缀后超过文件系统的长度上限，写临时文件失败
+        let path = home.path.join(format!("{}.json", "a".repeat(240)));
+        fs::write(&path, b"original").unwrap();
+
+        assert!(write_file(&path, b"new").is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"original");
+        let leftovers: Vec<_> = fs::read_dir(&home.path)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn lock_serializes_writers() {
+        let home = test_support::home();
+        let counter = home.path.join("counter");
+        fs::write(&counter, b"0").unwrap();
+
+        // 每个写入者读取、等待、再写回；没有锁时会互相覆盖
+        let writers: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    let _lock = lock().unwrap();
+                    let value: u32 = fs::read_to_string(&counter).unwrap().parse().unwrap();
+                    std::thread::sleep(Duration::from_millis(10));
+                    write_atomic(&counter, (value + 1).to_string().as_bytes()).unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        assert_eq!(fs::read_to_string(&counter).unwrap(), "8");
+    }
+
+    #[tokio::test]
+    async fn with_lock_waits_off_the_runtime() {
+        let _home = test_support::home();
+        let held = lock().unwrap();
+        let waiting = tokio::spawn(with_lock(|| Ok(())));
+
+        // 单线程运行时：等待锁的任务不占用线程，计时器照常运行
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+        drop(held);
+        waiting.await.unwrap().unwrap();
+    }
+}
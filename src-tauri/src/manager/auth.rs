@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+
+// ========== 认证文件 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexTokens {
+    pub access_token: String,
+    pub account_id: String,
+    pub id_token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexAuthFile {
+    #[serde(rename = "OPENAI_API_KEY")]
+    pub openai_api_key: Option<String>,
+    pub last_refresh: String,
+    pub tokens: CodexTokens,
+}
+
+// ========== JWT 解析 ==========
+
+pub fn decode_jwt_payload(token: &str) -> Option<serde_json::Value> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    
+    // Base64 URL 解码
+    let payload = parts[1];
+    let payload = payload.replace('-', "+").replace('_', "/");
+    
+    // 添加 padding
+    let padding = match payload.len() % 4 {
+        0 => 0,
+        2 => 2,
+        3 => 1,
+        _ => return None,
+    };
+    let payload = format!("{}{}", payload, "=".repeat(padding));
+    
+    // 解码
+    use std::io::Read;
+    let mut decoder = base64::read::DecoderReader::new(
+        payload.as_bytes(),
+        &base64::engine::general_purpose::STANDARD,
+    );
+    let mut decoded = Vec::new();
+    decoder.read_to_end(&mut decoded).ok()?;
+    
+    serde_json::from_slice(&decoded).ok()
+}
+
+/// 从 id_token 解析邮箱、套餐、订阅到期时间和 Token 过期时间
+///
+/// 没有邮箱时返回 `None`，由显示的地方决定如何展示，不要把占位文字当作数据保存或比较。
+pub fn extract_info_from_auth(auth: &CodexAuthFile) -> (Option<String>, String, Option<String>, Option<i64>) {
+    // 尝试从 id_token 解析
+    if let Some(payload) = decode_jwt_payload(&auth.tokens.id_token) {
+        let email = payload.get("email")
+            .and_then(|v| v.as_str())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+            
+        let expires_at = payload.get("exp")
+            .and_then(|v| v.as_i64());
+        
+        let auth_data = payload.get("https://api.openai.com/auth");
+        
+        let plan_type = auth_data
+            .and_then(|a| a.get("chatgpt_plan_type"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        let subscription_end = auth_data
+            .and_then(|a| a.get("chatgpt_subscription_active_until"))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+        
+        return (email, plan_type, subscription_end, expires_at);
+    }
+    
+    (None, "unknown".to_string(), None, None)
+}
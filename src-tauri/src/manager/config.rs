@@ -0,0 +1,373 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::atomic;
+use super::error::AppError;
+use super::i18n::Locale;
+use super::strategy::SwitchStrategyKind;
+use super::sync::CodexSyncConfig;
+use super::sync_backend::SyncTarget;
+
+// ========== 应用配置 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AppConfig {
+    pub accounts_dir: Option<String>,
+    #[serde(default)]
+    pub auto_switch: AutoSwitchConfig,
+    #[serde(default)]
+    pub auto_sync: AutoSyncConfig,
+    #[serde(default)]
+    pub auto_refresh: AutoRefreshConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub usage_fetch: UsageFetchConfig,
+    /// 托盘菜单、错误提示等后端文字的语言
+    #[serde(default)]
+    pub locale: Locale,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// 同步目标，密码类字段为空，实际值保存在系统钥匙串（见 credentials 模块）
+    #[serde(default)]
+    pub sync_target: Option<SyncTarget>,
+}
+
+/// 后台定时检查与自动切换设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoSwitchConfig {
+    #[serde(rename = "autoCheck")]
+    pub auto_check: bool,
+    #[serde(rename = "checkInterval")]
+    pub check_interval: u64,         // 分钟
+    #[serde(rename = "enableAutoSwitch")]
+    pub enable_auto_switch: bool,
+    #[serde(rename = "autoSwitchThreshold")]
+    pub auto_switch_threshold: u32,  // 剩余额度百分比，低于该值触发切换
+    pub strategy: SwitchStrategyKind,
+    pub priority: Vec<String>,       // Priority 策略使用，账号名称 / 邮箱 / account_id
+}
+
+impl Default for AutoSwitchConfig {
+    fn default() -> Self {
+        Self {
+            auto_check: true,
+            check_interval: 30,
+            enable_auto_switch: false,
+            auto_switch_threshold: 5,
+            strategy: SwitchStrategyKind::default(),
+            priority: Vec::new(),
+        }
+    }
+}
+
+/// 后台 Token 刷新设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoRefreshConfig {
+    pub enabled: bool,
+    /// 在 Token 过期前多久刷新（分钟）
+    #[serde(rename = "leadMinutes")]
+    pub lead_minutes: u64,
+    /// 在提前量基础上再随机提前至多这么久（分钟），避免多个账号同时刷新
+    #[serde(rename = "jitterMinutes")]
+    pub jitter_minutes: u64,
+}
+
+impl Default for AutoRefreshConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lead_minutes: 30,
+            jitter_minutes: 10,
+        }
+    }
+}
+
+/// Codex CLI 使用的 Client ID（公开的）
+pub const DEFAULT_CLIENT_ID: &str = "app_EMoamEEZ73f0CkXaXp7hrann";
+pub const DEFAULT_OAUTH_TOKEN_URL: &str = "https://auth.openai.com/oauth/token";
+
+/// 用量接口，按顺序尝试直到成功
+pub const DEFAULT_USAGE_URLS: [&str; 4] = [
+    "https://chatgpt.com/backend-api/wham/usage",
+    "https://api.openai.com/backend-api/wham/usage",
+    "https://api.openai.com/api/codex/usage",
+    "https://chat.openai.com/backend-api/wham/usage",
+];
+
+/// 网络设置：接口地址、代理和自定义 CA，应用创建的所有 HTTP 客户端共用（见 http 模块）
+///
+/// 接口地址一般无需修改，可指向本地模拟服务器用于测试。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    #[serde(rename = "oauthTokenUrl")]
+    pub oauth_token_url: String,
+    #[serde(rename = "clientId")]
+    pub client_id: String,
+    #[serde(rename = "usageUrls")]
+    pub usage_urls: Vec<String>,
+    /// 代理地址，支持 http://、https://、socks5://、socks5h://；为空时沿用 HTTPS_PROXY 等环境变量
+    pub proxy: Option<String>,
+    /// 不走代理的主机，逗号分隔（与 NO_PROXY 环境变量格式相同）
+    #[serde(rename = "noProxy")]
+    pub no_proxy: Option<String>,
+    /// 额外信任的 CA 证书文件（PEM，可包含多个证书），用于会替换 TLS 证书的企业代理
+    #[serde(rename = "caBundle")]
+    pub ca_bundle: Option<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            oauth_token_url: DEFAULT_OAUTH_TOKEN_URL.to_string(),
+            client_id: DEFAULT_CLIENT_ID.to_string(),
+            usage_urls: DEFAULT_USAGE_URLS.iter().map(|url| url.to_string()).collect(),
+            proxy: None,
+            no_proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+/// 批量查询用量的设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageFetchConfig {
+    /// 同时进行的查询数
+    pub concurrency: usize,
+    /// 单个账号的查询超时（秒），包括依次尝试多个用量接口的时间
+    #[serde(rename = "timeoutSeconds")]
+    pub timeout_seconds: u64,
+}
+
+impl Default for UsageFetchConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 8,
+            timeout_seconds: 30,
+        }
+    }
+}
+
+/// 日志设置（见 logging 模块）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// 日志级别，可写 tracing 的过滤指令，如 `info` 或 `info,tauri_app_lib::manager::webdav=debug`
+    pub level: String,
+    /// 日志文件按天滚动，最多保留的天数
+    #[serde(rename = "retentionDays")]
+    pub retention_days: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            retention_days: 14,
+        }
+    }
+}
+
+/// 后台自动同步设置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoSyncConfig {
+    pub enabled: bool,
+    pub interval: u64,               // 分钟
+    /// 账号目录有变化时立即同步
+    #[serde(rename = "onChange")]
+    pub on_change: bool,
+    #[serde(rename = "syncAccounts")]
+    pub sync_accounts: bool,
+    /// Codex 配置没有双向合并，需指定本机是上传方还是下载方
+    #[serde(rename = "codexDirection")]
+    pub codex_direction: CodexDirection,
+    #[serde(rename = "codexConfig")]
+    pub codex_config: CodexSyncConfig,
+    /// 旧版本在这里保存含明文密码的同步目标，启动时迁移到 `AppConfig::sync_target`
+    #[serde(skip_serializing)]
+    pub target: Option<SyncTarget>,
+}
+
+impl Default for AutoSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: 30,
+            on_change: true,
+            sync_accounts: true,
+            codex_direction: CodexDirection::Off,
+            codex_config: CodexSyncConfig::default(),
+            target: None,
+        }
+    }
+}
+
+/// 自动同步 Codex 配置的方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CodexDirection {
+    #[default]
+    Off,
+    Upload,
+    Download,
+}
+
+// ========== 路径辅助函数 ==========
+
+pub fn get_config_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("config.json")
+}
+
+/// 同步清单，记录上次同步时各账号文件的状态
+pub fn get_sync_manifest_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("sync-manifest.json")
+}
+
+/// 本地删除、尚未同步到远程的 prompts 和 skills
+pub fn get_pending_deletions_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("sync-deletions.json")
+}
+
+/// 刷新 Token 时遇到的永久失败，按 refresh_token 记录
+pub fn get_token_failures_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("token-failures.json")
+}
+
+/// 写入账号文件和 ~/.codex/auth.json 时共用的锁文件
+pub fn get_write_lock_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("auth.lock")
+}
+
+/// 系统钥匙串不可用时保存凭据的文件（用 credentials.key 混淆）
+pub fn get_credentials_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("credentials.json")
+}
+
+/// 加密凭据文件所用的本机密钥
+pub fn get_credentials_key_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("credentials.key")
+}
+
+/// 用量历史，每个账号一个 JSONL 文件
+pub fn get_usage_history_dir() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("usage-history")
+}
+
+/// 日志文件目录，按天滚动
+pub fn get_logs_dir() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("logs")
+}
+
+/// git 同步后端的本地工作副本所在目录
+pub fn get_git_sync_dir() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("sync-git")
+}
+
+pub fn load_config() -> AppConfig {
+    let config_path = get_config_file();
+    if config_path.exists() {
+        if let Ok(content) = fs::read_to_string(&config_path) {
+            if let Ok(config) = serde_json::from_str(&content) {
+                return config;
+            }
+        }
+    }
+    AppConfig::default()
+}
+
+/// 整体覆盖 config.json；只修改部分设置时用 `update_config`，避免覆盖其他任务同时写入的设置
+pub fn save_config(config: &AppConfig) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    atomic::write_file(&get_config_file(), content.as_bytes())
+}
+
+/// 持锁读取、修改并原子写回 config.json
+///
+/// 后台任务和各设置命令会同时修改不同字段，读取和写回之间不能有其他写入者。
+pub fn update_config<T>(update: impl FnOnce(&mut AppConfig) -> T) -> Result<T, AppError> {
+    let _lock = atomic::lock()?;
+    let mut config = load_config();
+    let value = update(&mut config);
+    let content = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+    atomic::write_atomic(&get_config_file(), content.as_bytes())?;
+    Ok(value)
+}
+
+pub fn get_accounts_dir() -> PathBuf {
+    let config = load_config();
+    if let Some(dir) = config.accounts_dir {
+        let path = PathBuf::from(dir);
+        if path.exists() {
+            return path;
+        }
+    }
+    // 默认路径
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".myswitch").join("accounts")
+}
+
+pub fn get_codex_auth_file() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".codex").join("auth.json")
+}
+
+pub fn get_codex_dir() -> PathBuf {
+    let home = dirs::home_dir().expect("无法获取用户目录");
+    home.join(".codex")
+}
+
+pub fn get_prompts_dir() -> PathBuf {
+    get_codex_dir().join("prompts")
+}
+
+pub fn get_skills_dir() -> PathBuf {
+    get_codex_dir().join("skills")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support;
+
+    #[test]
+    fn concurrent_updates_keep_each_field() {
+        let _home = test_support::home();
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    update_config(|config| {
+                        if i % 2 == 0 {
+                            config.auto_refresh.lead_minutes += 1;
+                        } else {
+                            config.auto_refresh.jitter_minutes += 1;
+                        }
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        let config = load_config().auto_refresh;
+        let defaults = AutoRefreshConfig::default();
+        assert_eq!(config.lead_minutes, defaults.lead_minutes + 4);
+        assert_eq!(config.jitter_minutes, defaults.jitter_minutes + 4);
+    }
+}
@@ -0,0 +1,389 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+use super::atomic;
+use super::config::{get_credentials_file, get_credentials_key_file, load_config, update_config};
+use super::crypto::{Envelope, SecretKey};
+use super::error::AppError;
+use super::logging;
+use super::sync_backend::{BackendConfig, SyncTarget};
+
+use crate::t;
+
+// ========== 凭据存储 ==========
+//
+// 同步后端的密码、S3 Secret Key 和同步口令由后端保存，不写入 config.json，也不保存在前端：
+// 优先放在系统钥匙串（Linux 为 Secret Service / libsecret，macOS 为登录钥匙串），
+// 钥匙串不可用时（没有桌面会话的服务器、Windows 等）退回 ~/.myswitch/credentials.json。
+// 该文件用同目录下随机生成的 credentials.key 加密，两个文件都只允许当前用户读写（0600）；
+// 密钥和密文放在一起，只能防止凭据以明文出现在备份、日志或截图中，保护强度等同于文件权限。
+//
+// 设置环境变量 CODEX_MANAGER_KEYRING=off 可跳过钥匙串，直接使用凭据文件。
+// 凭据文件的读取、修改、写回都在 `atomic::lock()` 内进行，同时保存的多个凭据不会互相覆盖。
+
+/// 钥匙串条目的服务名
+const SERVICE: &str = "codex-manager";
+
+const KEYRING_ENV: &str = "CODEX_MANAGER_KEYRING";
+
+pub const WEBDAV_PASSWORD: &str = "webdav-password";
+pub const S3_SECRET_ACCESS_KEY: &str = "s3-secret-access-key";
+pub const SYNC_PASSPHRASE: &str = "sync-passphrase";
+
+/// 凭据实际保存的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SecretStore {
+    Keyring,
+    File,
+}
+
+/// 返回给前端的同步目标：密码类字段为空，只标明哪些凭据已保存、保存在哪里
+#[derive(Debug, Clone, Serialize)]
+pub struct SavedSyncTarget {
+    pub target: Option<SyncTarget>,
+    /// 凭据名（如 `webdav-password`）到保存位置
+    pub saved: BTreeMap<String, SecretStore>,
+}
+
+// ========== 读写单个凭据 ==========
+
+fn keyring_enabled() -> bool {
+    !matches!(
+        std::env::var(KEYRING_ENV).as_deref(),
+        Ok("off") | Ok("0") | Ok("false")
+    )
+}
+
+/// 读取凭据，钥匙串优先
+pub fn get(name: &str) -> Option<(String, SecretStore)> {
+    if keyring_enabled() {
+        if let Some(secret) = keyring::get(name) {
+            return Some((secret, SecretStore::Keyring));
+        }
+    }
+    let secrets = load_file();
+    let envelope = secrets.get(name)?;
+    match file_key(&secrets).and_then(|key| envelope.open(&key)) {
+        Ok(secret) => Some((String::from_utf8(secret).ok()?, SecretStore::File)),
+        Err(e) => {
+            tracing::error!(name, error = %e, "读取凭据文件失败");
+            None
+        }
+    }
+}
+
+/// 保存凭据，钥匙串写入失败时退回凭据文件
+pub fn set(name: &str, secret: &str) -> Result<SecretStore, AppError> {
+    if keyring_enabled() {
+        match keyring::set(name, secret) {
+            Ok(()) => {
+                // 之前退回过凭据文件的，移除旧副本
+                remove_from_file(name)?;
+                return Ok(SecretStore::Keyring);
+            }
+            Err(e) => tracing::warn!(error = %e, "系统钥匙串不可用，改用凭据文件"),
+        }
+    }
+    let _lock = atomic::lock()?;
+    let mut secrets = load_file();
+    let key = file_key(&secrets)?;
+    secrets.insert(name.to_string(), Envelope::seal(&key, secret.as_bytes())?);
+    save_file(&secrets)?;
+    Ok(SecretStore::File)
+}
+
+/// 删除凭据，不存在时视为成功
+pub fn delete(name: &str) -> Result<(), AppError> {
+    if keyring_enabled() {
+        keyring::delete(name);
+    }
+    remove_from_file(name)
+}
+
+// ========== 凭据文件 ==========
+
+/// 从凭据文件中移除一项，不存在时不写入
+fn remove_from_file(name: &str) -> Result<(), AppError> {
+    let _lock = atomic::lock()?;
+    let mut secrets = load_file();
+    if secrets.remove(name).is_some() {
+        save_file(&secrets)?;
+    }
+    Ok(())
+}
+
+fn load_file() -> BTreeMap<String, Envelope> {
+    fs::read(get_credentials_file())
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 调用方需已持有 `atomic::lock()`
+fn save_file(secrets: &BTreeMap<String, Envelope>) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(secrets).map_err(|e| t!(SerializeFailed, e))?;
+    atomic::write_atomic(&get_credentials_file(), content.as_bytes())
+}
+
+/// 读取本机密钥，凭据文件中还没有凭据时生成（生成时调用方需已持有 `atomic::lock()`）
+///
+/// 已有凭据而密钥文件丢失时报错：重新生成的密钥解不开旧凭据，还会让它们在下次保存时被悄悄覆盖。
+fn file_key(secrets: &BTreeMap<String, Envelope>) -> Result<SecretKey, AppError> {
+    let path = get_credentials_key_file();
+    match fs::read_to_string(&path) {
+        Ok(content) => SecretKey::from_base64(&content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && secrets.is_empty() => {
+            let key = SecretKey::generate();
+            atomic::write_atomic(&path, key.to_base64().as_bytes())?;
+            Ok(key)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(AppError::Other(t!(
+            CredentialsKeyMissing,
+            path.display(),
+            get_credentials_file().display()
+        ))),
+        Err(e) => Err(AppError::io(&path, e)),
+    }
+}
+
+// ========== 系统钥匙串 ==========
+
+/// 访问系统钥匙串，工具不存在或会话不可用时返回错误
+mod keyring {
+    use super::AppError;
+    use crate::t;
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    use super::SERVICE;
+    #[cfg(target_os = "linux")]
+    use std::process::{Command, Stdio};
+
+    /// libsecret 的 secret-tool：密码经标准输入传递，读取时原样输出
+    #[cfg(target_os = "linux")]
+    pub fn get(name: &str) -> Option<String> {
+        let output = Command::new("secret-tool")
+            .args(["lookup", "service", SERVICE, "name", name])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() || output.stdout.is_empty() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set(name: &str, secret: &str) -> Result<(), AppError> {
+        use std::io::Write;
+
+        let mut child = Command::new("secret-tool")
+            .args(["store", "--label", &format!("Codex Manager: {}", name)])
+            .args(["service", SERVICE, "name", name])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| AppError::Keyring(t!(KeyringToolMissing, "secret-tool (libsecret-tools)", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(secret.as_bytes())
+                .map_err(|e| AppError::Keyring(t!(KeyringWriteFailed, e)))?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| AppError::Keyring(t!(KeyringWriteFailed, e)))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(AppError::Keyring(t!(
+                KeyringWriteFailed,
+                format!("secret-tool: {}", String::from_utf8_lossy(&output.stderr).trim())
+            )))
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn delete(name: &str) {
+        let _ = Command::new("secret-tool")
+            .args(["clear", "service", SERVICE, "name", name])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    }
+
+    /// macOS 通过 Security framework 读写登录钥匙串；`security` 命令只能从参数读取密码，
+    /// 写入期间会出现在进程列表中，因此不使用
+    #[cfg(target_os = "macos")]
+    pub fn get(name: &str) -> Option<String> {
+        let secret = security_framework::passwords::get_generic_password(SERVICE, name).ok()?;
+        String::from_utf8(secret).ok()
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn set(name: &str, secret: &str) -> Result<(), AppError> {
+        security_framework::passwords::set_generic_password(SERVICE, name, secret.as_bytes())
+            .map_err(|e| AppError::Keyring(t!(KeyringWriteFailed, e)))
+    }
+
+    #[cfg(target_os = "macos")]
+    pub fn delete(name: &str) {
+        let _ = security_framework::passwords::delete_generic_password(SERVICE, name);
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn get(_name: &str) -> Option<String> {
+        None
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn set(_name: &str, _secret: &str) -> Result<(), AppError> {
+        Err(AppError::Keyring(t!(KeyringUnsupported)))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    pub fn delete(_name: &str) {}
+}
+
+// ========== 同步目标 ==========
+
+/// 同步目标中需要保密的字段
+fn secret_fields(target: &mut SyncTarget) -> Vec<(&'static str, &mut String)> {
+    let mut fields = Vec::new();
+    match &mut target.backend {
+        BackendConfig::Webdav(config) => fields.push((WEBDAV_PASSWORD, &mut config.password)),
+        BackendConfig::S3(config) => fields.push((S3_SECRET_ACCESS_KEY, &mut config.secret_access_key)),
+        BackendConfig::Folder(_) | BackendConfig::Git(_) => {}
+    }
+    fields.push((SYNC_PASSPHRASE, &mut target.sync_passphrase));
+    fields
+}
+
+/// 把非空的密码类字段存入钥匙串并清空；为空的字段表示沿用已保存的值
+fn store_secrets(target: &mut SyncTarget) -> Result<(), AppError> {
+    for (name, value) in secret_fields(target) {
+        if !value.is_empty() {
+            logging::register_secret(value);
+            set(name, value)?;
+            value.clear();
+        }
+    }
+    Ok(())
+}
+
+/// 为空的密码类字段填入已保存的值
+pub fn fill_secrets(target: &mut SyncTarget) {
+    for (name, value) in secret_fields(target) {
+        if value.is_empty() {
+            if let Some((secret, _)) = get(name) {
+                *value = secret;
+            }
+        }
+        logging::register_secret(value);
+    }
+}
+
+/// 保存同步目标，凭据存入钥匙串，config.json 中只保留其余参数
+pub fn save_sync_target(mut target: SyncTarget) -> Result<SavedSyncTarget, AppError> {
+    store_secrets(&mut target)?;
+    update_config(|config| config.sync_target = Some(target))?;
+    Ok(saved_sync_target())
+}
+
+/// 读取已保存的同步目标（不含凭据）
+pub fn saved_sync_target() -> SavedSyncTarget {
+    let mut target = load_config().sync_target;
+    let mut saved = BTreeMap::new();
+    if let Some(target) = &mut target {
+        for (name, _) in secret_fields(target) {
+            if let Some((_, store)) = get(name) {
+                saved.insert(name.to_string(), store);
+            }
+        }
+    }
+    SavedSyncTarget { target, saved }
+}
+
+/// 读取同步目标并填入凭据，供同步使用
+pub fn load_sync_target() -> Result<SyncTarget, AppError> {
+    let mut target = load_config().sync_target.ok_or(AppError::SyncNotConfigured)?;
+    fill_secrets(&mut target);
+    Ok(target)
+}
+
+/// 旧版本把含明文密码的同步目标保存在 auto_sync.target 中，启动时迁移到钥匙串
+pub fn migrate_legacy_target() -> Result<(), AppError> {
+    let config = load_config();
+    let Some(mut target) = config.auto_sync.target else {
+        return Ok(());
+    };
+    // 钥匙串操作较慢，在锁外完成
+    let migrated = match config.sync_target {
+        None => {
+            store_secrets(&mut target)?;
+            Some(target)
+        }
+        Some(_) => None,
+    };
+    update_config(|config| {
+        config.auto_sync.target = None;
+        if config.sync_target.is_none() {
+            config.sync_target = migrated;
+        }
+    })?;
+    tracing::info!("已将同步凭据从 config.json 迁移到安全存储");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support;
+
+    #[test]
+    fn file_store_round_trip() {
+        let _home = test_support::home();
+        std::env::set_var(KEYRING_ENV, "off");
+
+        assert_eq!(set(SYNC_PASSPHRASE, "correct horse battery").unwrap(), SecretStore::File);
+        assert_eq!(get(SYNC_PASSPHRASE), Some(("correct horse battery".to_string(), SecretStore::File)));
+        let content = fs::read_to_string(get_credentials_file()).unwrap();
+        assert!(!content.contains("correct horse battery"));
+
+        delete(SYNC_PASSPHRASE).unwrap();
+        assert_eq!(get(SYNC_PASSPHRASE), None);
+    }
+
+    #[test]
+    fn concurrent_saves_keep_every_secret() {
+        let _home = test_support::home();
+        std::env::set_var(KEYRING_ENV, "off");
+
+        let names = [WEBDAV_PASSWORD, S3_SECRET_ACCESS_KEY, SYNC_PASSPHRASE];
+        let writers: Vec<_> = names
+            .into_iter()
+            .map(|name| std::thread::spawn(move || set(name, &format!("{}-secret", name)).unwrap()))
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        for name in names {
+            assert_eq!(get(name).map(|(secret, _)| secret), Some(format!("{}-secret", name)));
+        }
+    }
+
+    #[test]
+    fn missing_key_is_an_error_not_a_new_key() {
+        let _home = test_support::home();
+        std::env::set_var(KEYRING_ENV, "off");
+        set(SYNC_PASSPHRASE, "correct horse battery").unwrap();
+        fs::remove_file(get_credentials_key_file()).unwrap();
+
+        assert_eq!(get(SYNC_PASSPHRASE), None);
+        assert!(matches!(set(WEBDAV_PASSWORD, "password"), Err(AppError::Other(_))));
+        assert!(!get_credentials_key_file().exists());
+        // 旧凭据仍在，恢复密钥文件后可以读取
+        assert!(load_file().contains_key(SYNC_PASSPHRASE));
+    }
+}
@@ -0,0 +1,225 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use super::error::AppError;
+
+use crate::t;
+
+// ========== 口令派生密钥 + AEAD 加密 ==========
+
+/// 32 字节对称密钥
+#[derive(Clone)]
+pub struct SecretKey([u8; 32]);
+
+impl SecretKey {
+    /// 随机生成密钥（用于不依赖口令的本机加密）
+    pub fn generate() -> Self {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        Self(key)
+    }
+
+    pub fn to_base64(&self) -> String {
+        BASE64.encode(self.0)
+    }
+
+    pub fn from_base64(encoded: &str) -> Result<Self, AppError> {
+        let bytes = BASE64.decode(encoded.trim()).map_err(|e| AppError::Corrupt(t!(CorruptEncryptedData, e)))?;
+        let key: [u8; 32] = bytes
+            .try_into()
+            .map_err(|_| AppError::Corrupt(t!(CorruptEncryptedData, "key length")))?;
+        Ok(Self(key))
+    }
+}
+
+/// Argon2id 参数，随密文元数据一起保存，便于日后调整强度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub algorithm: String,
+    #[serde(rename = "memoryKib")]
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String, // base64
+}
+
+impl KdfParams {
+    /// 使用 OWASP 推荐的 Argon2id 参数和随机盐
+    pub fn generate() -> Self {
+        let mut salt = [0u8; 16];
+        OsRng.fill_bytes(&mut salt);
+        Self {
+            algorithm: "argon2id".to_string(),
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+            salt: BASE64.encode(salt),
+        }
+    }
+
+    /// 由口令派生密钥（耗时约数百毫秒，调用方应缓存结果）
+    pub fn derive_key(&self, passphrase: &str) -> Result<SecretKey, AppError> {
+        if self.algorithm != "argon2id" {
+            return Err(AppError::InvalidInput(t!(UnsupportedKdf, self.algorithm)));
+        }
+        let salt = BASE64.decode(&self.salt).map_err(|e| AppError::Corrupt(t!(CorruptEncryptedData, e)))?;
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| t!(DeriveKeyFailed, e))?;
+
+        let mut key = [0u8; 32];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+            .map_err(|e| t!(DeriveKeyFailed, e))?;
+        Ok(SecretKey(key))
+    }
+}
+
+/// 加密后的数据块，以 JSON 形式落盘或上传
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    #[serde(rename = "codexManagerEncrypted")]
+    pub version: u32,
+    pub nonce: String,      // base64, 24 字节
+    pub ciphertext: String, // base64
+}
+
+impl Envelope {
+    /// 使用 XChaCha20-Poly1305 加密，每次加密生成随机 nonce
+    pub fn seal(key: &SecretKey, plaintext: &[u8]) -> Result<Self, AppError> {
+        let cipher = XChaCha20Poly1305::new((&key.0).into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| t!(EncryptFailed))?;
+        Ok(Self {
+            version: 1,
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    /// 解密并校验完整性，口令错误或数据被篡改时返回错误
+    pub fn open(&self, key: &SecretKey) -> Result<Vec<u8>, AppError> {
+        let nonce = BASE64.decode(&self.nonce).map_err(|e| AppError::Corrupt(t!(CorruptEncryptedData, e)))?;
+        if nonce.len() != 24 {
+            return Err(AppError::Corrupt(t!(CorruptEncryptedData, "nonce length")));
+        }
+        let ciphertext = BASE64
+            .decode(&self.ciphertext)
+            .map_err(|e| AppError::Corrupt(t!(CorruptEncryptedData, e)))?;
+        XChaCha20Poly1305::new((&key.0).into())
+            .decrypt(XNonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| AppError::DecryptFailed)
+    }
+
+    /// 判断内容是否为加密数据块
+    pub fn parse(content: &[u8]) -> Option<Self> {
+        serde_json::from_slice::<Self>(content).ok()
+    }
+
+    pub fn to_json(&self) -> Result<String, AppError> {
+        serde_json::to_string_pretty(self).map_err(|e| AppError::Other(t!(SerializeFailed, e)))
+    }
+}
+
+/// 口令校验元数据：KDF 参数 + 用派生密钥加密的已知明文，
+/// 用于在不接触真实数据的情况下判断口令是否正确
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyCheck {
+    pub version: u32,
+    pub kdf: KdfParams,
+    pub check: Envelope,
+}
+
+impl KeyCheck {
+    /// 用新口令生成校验元数据，同时返回派生出的密钥
+    pub fn create(passphrase: &str, known: &[u8]) -> Result<(Self, SecretKey), AppError> {
+        let kdf = KdfParams::generate();
+        let key = kdf.derive_key(passphrase)?;
+        let check = Envelope::seal(&key, known)?;
+        Ok((Self { version: 1, kdf, check }, key))
+    }
+
+    /// 校验口令并返回密钥
+    pub fn unlock(&self, passphrase: &str) -> Result<SecretKey, AppError> {
+        let key = self.kdf.derive_key(passphrase)?;
+        self.check.open(&key).map_err(|_| AppError::WrongPassphrase)?;
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support::flip_first_byte;
+
+    #[test]
+    fn envelope_round_trip() {
+        let key = SecretKey::generate();
+        let sealed = Envelope::seal(&key, b"secret").unwrap();
+        let again = Envelope::seal(&key, b"secret").unwrap();
+        // 每次加密使用新的 nonce
+        assert_ne!(sealed.nonce, again.nonce);
+
+        let parsed = Envelope::parse(sealed.to_json().unwrap().as_bytes()).unwrap();
+        assert_eq!(parsed.open(&key).unwrap(), b"secret");
+        assert!(Envelope::parse(br#"{"tokens":{}}"#).is_none());
+    }
+
+    #[test]
+    fn envelope_rejects_wrong_key_and_tampering() {
+        let key = SecretKey::generate();
+        let sealed = Envelope::seal(&key, b"secret").unwrap();
+        assert_eq!(sealed.open(&SecretKey::generate()), Err(AppError::DecryptFailed));
+
+        let mut tampered = sealed.clone();
+        tampered.ciphertext = flip_first_byte(&sealed.ciphertext);
+        assert_eq!(tampered.open(&key), Err(AppError::DecryptFailed));
+
+        let mut tampered = sealed.clone();
+        tampered.nonce = flip_first_byte(&sealed.nonce);
+        assert_eq!(tampered.open(&key), Err(AppError::DecryptFailed));
+
+        let mut truncated = sealed;
+        truncated.nonce = BASE64.encode([0u8; 12]);
+        assert!(matches!(truncated.open(&key), Err(AppError::Corrupt(_))));
+    }
+
+    #[test]
+    fn derives_the_same_key_from_the_same_salt() {
+        let kdf = KdfParams::generate();
+        let key = kdf.derive_key("correct horse battery").unwrap();
+        let sealed = Envelope::seal(&key, b"secret").unwrap();
+        assert_eq!(sealed.open(&kdf.derive_key("correct horse battery").unwrap()).unwrap(), b"secret");
+
+        let other_salt = KdfParams::generate();
+        assert!(sealed.open(&other_salt.derive_key("correct horse battery").unwrap()).is_err());
+
+        let mut unsupported = kdf;
+        unsupported.algorithm = "scrypt".to_string();
+        assert!(matches!(unsupported.derive_key("correct horse battery"), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn key_check_rejects_wrong_passphrase_and_tampered_header() {
+        let (check, key) = KeyCheck::create("correct horse battery", b"known").unwrap();
+        let sealed = Envelope::seal(&key, b"secret").unwrap();
+        let unlocked = check.unlock("correct horse battery").unwrap();
+        assert_eq!(sealed.open(&unlocked).unwrap(), b"secret");
+
+        assert!(matches!(check.unlock("wrong passphrase"), Err(AppError::WrongPassphrase)));
+
+        let mut tampered = check.clone();
+        tampered.kdf.salt = flip_first_byte(&check.kdf.salt);
+        assert!(matches!(tampered.unlock("correct horse battery"), Err(AppError::WrongPassphrase)));
+
+        let mut tampered = check;
+        tampered.check.ciphertext = flip_first_byte(&tampered.check.ciphertext);
+        assert!(matches!(tampered.unlock("correct horse battery"), Err(AppError::WrongPassphrase)));
+    }
+}
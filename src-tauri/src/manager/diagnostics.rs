@@ -0,0 +1,70 @@
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use super::atomic;
+use super::config::{get_logs_dir, load_config};
+use super::error::AppError;
+use super::logging::{self, LOG_FILE_PREFIX};
+use super::vault;
+
+use crate::t;
+
+// ========== 诊断包 ==========
+//
+// 把日志文件、运行环境和（去掉凭据的）应用配置打包成 zip，方便附在问题报告中。
+// 日志写入时已经脱敏，打包时按当前规则再处理一遍，旧版本写下的日志也不会泄露密钥。
+
+/// 把诊断信息写入 `dest`（zip 文件）
+pub fn export(dest: &Path) -> Result<(), AppError> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+
+    add(&mut zip, "system.txt", &system_info(), options)?;
+    let config = serde_json::to_string_pretty(&load_config()).map_err(|e| AppError::Other(t!(SerializeFailed, e)))?;
+    add(&mut zip, "config.json", &logging::redact(&config), options)?;
+
+    let logs_dir = get_logs_dir();
+    let mut logs: Vec<_> = fs::read_dir(&logs_dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+        .unwrap_or_default();
+    logs.retain(|path| {
+        path.is_file()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+    });
+    logs.sort();
+    for path in logs {
+        let content = fs::read(&path).map_err(|e| AppError::io(&path, e))?;
+        let name = format!("logs/{}", path.file_name().unwrap_or_default().to_string_lossy());
+        add(&mut zip, &name, &logging::redact(&String::from_utf8_lossy(&content)), options)?;
+    }
+
+    let content = zip.finish().map_err(|e| AppError::Io(format!("{}: {}", dest.display(), e)))?.into_inner();
+    atomic::write_file(dest, &content)?;
+    tracing::info!(path = %dest.display(), bytes = content.len(), "已导出诊断包");
+    Ok(())
+}
+
+fn add(zip: &mut ZipWriter<Cursor<Vec<u8>>>, name: &str, content: &str, options: SimpleFileOptions) -> Result<(), AppError> {
+    zip.start_file(name, options).map_err(|e| AppError::Io(format!("{}: {}", name, e)))?;
+    zip.write_all(content.as_bytes()).map_err(|e| AppError::io(name, e))
+}
+
+/// 版本、系统和存储状态，不含账号信息
+fn system_info() -> String {
+    let vault = vault::status();
+    [
+        format!("version: {}", env!("CARGO_PKG_VERSION")),
+        format!("os: {} {}", std::env::consts::OS, std::env::consts::ARCH),
+        format!("generated: {}", chrono::Local::now().to_rfc3339()),
+        format!("vault: enabled={} unlocked={}", vault.enabled, vault.unlocked),
+        format!("logs: {}", get_logs_dir().display()),
+    ]
+    .join("\n")
+        + "\n"
+}
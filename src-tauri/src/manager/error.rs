@@ -0,0 +1,297 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::path::Path;
+
+use crate::t;
+
+// ========== 错误类型 ==========
+//
+// 命令、后台任务和命令行共用的错误。序列化为 `{ code, message, status?, detail? }`：
+// `code` 是稳定的错误码，前端和命令行据此判断错误类型，不要匹配 `message` 中的文字；
+// `message` 是显示给用户的说明；`detail` 是底层错误原文（路径、系统错误等）。
+//
+// 尚未细分的错误统一为 `Other`，其 `message` 即原来的错误文字。`message` 按当前语言生成（见 i18n），
+// 带文字的变体在创建时就应使用 `t!` 取得文字。
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// 账号或文件不存在，附带查找的名称或路径
+    NotFound(String),
+    /// 目标名称已被占用
+    AlreadyExists(String),
+    /// auth.json 内容无法解析
+    InvalidAuthJson(String),
+    /// 参数或设置不完整、不合法，附带完整说明
+    InvalidInput(String),
+    /// 加密存储尚未解锁
+    VaultLocked,
+    /// 账号目录未启用加密存储
+    VaultNotEnabled,
+    WrongPassphrase,
+    /// 解密未通过校验：密钥不对，或密文被篡改、损坏
+    DecryptFailed,
+    /// 数据格式损坏或未通过校验（加密数据、远程清单等），附带完整说明
+    Corrupt(String),
+    /// 连接失败、超时等
+    Network(String),
+    /// 服务器返回非成功状态码
+    HttpStatus(u16),
+    /// Refresh Token 已过期
+    TokenExpired,
+    /// Refresh Token 已被使用过（通常是另一处已刷新）
+    RefreshTokenReused,
+    /// Refresh Token 已被吊销
+    RefreshTokenInvalidated,
+    /// WebDAV 用户名或密码错误
+    WebDavAuth,
+    /// S3 Access Key 或签名错误
+    S3Auth,
+    /// 尚未保存同步目标
+    SyncNotConfigured,
+    /// 系统钥匙串不可用或读写失败，附带完整说明
+    Keyring(String),
+    /// 远程数据已被其他设备修改，附带完整说明
+    Conflict(String),
+    /// 本地文件读写失败
+    Io(String),
+    Other(String),
+}
+
+impl AppError {
+    /// 文件操作失败，附带路径和系统错误
+    pub fn io(path: impl AsRef<Path>, e: std::io::Error) -> Self {
+        Self::Io(format!("{}: {}", path.as_ref().display(), e))
+    }
+
+    /// 稳定的错误码
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "notFound",
+            Self::AlreadyExists(_) => "alreadyExists",
+            Self::InvalidAuthJson(_) => "invalidAuthJson",
+            Self::InvalidInput(_) => "invalidInput",
+            Self::VaultLocked => "vaultLocked",
+            Self::VaultNotEnabled => "vaultNotEnabled",
+            Self::WrongPassphrase => "wrongPassphrase",
+            Self::DecryptFailed => "decryptFailed",
+            Self::Corrupt(_) => "corrupt",
+            Self::Network(_) => "network",
+            Self::HttpStatus(_) => "httpStatus",
+            Self::TokenExpired => "tokenExpired",
+            Self::RefreshTokenReused => "refreshTokenReused",
+            Self::RefreshTokenInvalidated => "refreshTokenInvalidated",
+            Self::WebDavAuth => "webDavAuth",
+            Self::S3Auth => "s3Auth",
+            Self::SyncNotConfigured => "syncNotConfigured",
+            Self::Keyring(_) => "keyring",
+            Self::Conflict(_) => "conflict",
+            Self::Io(_) => "io",
+            Self::Other(_) => "other",
+        }
+    }
+
+    fn status(&self) -> Option<u16> {
+        match self {
+            Self::HttpStatus(status) => Some(*status),
+            _ => None,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            Self::NotFound(detail)
+            | Self::AlreadyExists(detail)
+            | Self::InvalidAuthJson(detail)
+            | Self::InvalidInput(detail)
+            | Self::Network(detail)
+            | Self::Corrupt(detail)
+            | Self::Keyring(detail)
+            | Self::Conflict(detail)
+            | Self::Io(detail) => Some(detail),
+            _ => None,
+        }
+    }
+
+    /// 账号凭据已失效，需要重新登录（用量接口返回 401/403 或 Refresh Token 不可用）
+    pub fn is_auth_expired(&self) -> bool {
+        matches!(
+            self,
+            Self::HttpStatus(401 | 403) | Self::TokenExpired | Self::RefreshTokenReused | Self::RefreshTokenInvalidated
+        )
+    }
+
+    /// 稍后重试可能成功的错误：连接失败、超时、服务器暂时不可用，以及与其他设备同时写入的冲突
+    pub fn is_transient(&self) -> bool {
+        matches!(self, Self::Network(_) | Self::HttpStatus(408 | 429 | 500..=599) | Self::Conflict(_))
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Self::NotFound(what) => t!(ErrNotFound, what),
+            Self::AlreadyExists(what) => t!(ErrAlreadyExists, what),
+            Self::InvalidAuthJson(detail) => t!(ErrInvalidAuthJson, detail),
+            Self::InvalidInput(message)
+            | Self::Corrupt(message)
+            | Self::Keyring(message)
+            | Self::Conflict(message)
+            | Self::Other(message) => return f.write_str(message),
+            Self::VaultLocked => t!(ErrVaultLocked),
+            Self::VaultNotEnabled => t!(VaultNotEnabled),
+            Self::WrongPassphrase => t!(ErrWrongPassphrase),
+            Self::DecryptFailed => t!(DecryptFailed),
+            Self::Network(detail) => t!(ErrNetwork, detail),
+            Self::HttpStatus(status) => t!(ErrHttpStatus, status),
+            Self::TokenExpired => t!(ErrTokenExpired),
+            Self::RefreshTokenReused => t!(ErrRefreshTokenReused),
+            Self::RefreshTokenInvalidated => t!(ErrRefreshTokenInvalidated),
+            Self::WebDavAuth => t!(ErrWebDavAuth),
+            Self::S3Auth => t!(ErrS3Auth),
+            Self::SyncNotConfigured => t!(ErrSyncNotConfigured),
+            Self::Io(detail) => t!(ErrIo, detail),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// 尚未细分的错误文字
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+}
+
+/// 请求未得到响应（连接失败、超时、TLS 错误等）
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => Self::HttpStatus(status.as_u16()),
+            None => Self::Network(e.to_string()),
+        }
+    }
+}
+
+// ========== 序列化 ==========
+
+#[derive(Serialize, Deserialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ErrorBody {
+            code: self.code().to_string(),
+            message: self.to_string(),
+            status: self.status(),
+            detail: self.detail().map(str::to_string),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// 前端回传用量结果（含错误）时使用；未知错误码还原为 `Other`
+impl<'de> Deserialize<'de> for AppError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let body = ErrorBody::deserialize(deserializer)?;
+        let detail = body.detail.unwrap_or_default();
+        Ok(match body.code.as_str() {
+            "notFound" => Self::NotFound(detail),
+            "alreadyExists" => Self::AlreadyExists(detail),
+            "invalidAuthJson" => Self::InvalidAuthJson(detail),
+            "invalidInput" => Self::InvalidInput(detail),
+            "vaultLocked" => Self::VaultLocked,
+            "vaultNotEnabled" => Self::VaultNotEnabled,
+            "wrongPassphrase" => Self::WrongPassphrase,
+            "decryptFailed" => Self::DecryptFailed,
+            "corrupt" => Self::Corrupt(detail),
+            "network" => Self::Network(detail),
+            "httpStatus" => match body.status {
+                Some(status) => Self::HttpStatus(status),
+                None => Self::Other(body.message),
+            },
+            "tokenExpired" => Self::TokenExpired,
+            "refreshTokenReused" => Self::RefreshTokenReused,
+            "refreshTokenInvalidated" => Self::RefreshTokenInvalidated,
+            "webDavAuth" => Self::WebDavAuth,
+            "s3Auth" => Self::S3Auth,
+            "syncNotConfigured" => Self::SyncNotConfigured,
+            "keyring" => Self::Keyring(detail),
+            "conflict" => Self::Conflict(detail),
+            "io" => Self::Io(detail),
+            _ => Self::Other(body.message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_message() {
+        let error = AppError::HttpStatus(401);
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "httpStatus", "message": error.to_string(), "status": 401 }));
+
+        let json = serde_json::to_value(AppError::Other("出错了".to_string())).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "other", "message": "出错了" }));
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        for error in [
+            AppError::NotFound("a.json".to_string()),
+            AppError::HttpStatus(503),
+            AppError::RefreshTokenReused,
+            AppError::DecryptFailed,
+            AppError::Corrupt("清单已损坏".to_string()),
+            AppError::VaultNotEnabled,
+            AppError::Keyring("secret-tool".to_string()),
+            AppError::Other("出错了".to_string()),
+        ] {
+            let json = serde_json::to_string(&error).unwrap();
+            assert_eq!(serde_json::from_str::<AppError>(&json).unwrap(), error);
+        }
+        let unknown: AppError = serde_json::from_str(r#"{"code":"future","message":"新错误"}"#).unwrap();
+        assert_eq!(unknown, AppError::Other("新错误".to_string()));
+    }
+
+    #[test]
+    fn only_transport_errors_are_transient() {
+        for error in [
+            AppError::Network("timeout".to_string()),
+            AppError::HttpStatus(429),
+            AppError::HttpStatus(503),
+            AppError::Conflict("412".to_string()),
+        ] {
+            assert!(error.is_transient(), "{:?}", error);
+        }
+        for error in [
+            AppError::HttpStatus(401),
+            AppError::HttpStatus(404),
+            AppError::WrongPassphrase,
+            AppError::WebDavAuth,
+            AppError::S3Auth,
+            AppError::SyncNotConfigured,
+            AppError::InvalidInput("--url".to_string()),
+            AppError::Io("accounts".to_string()),
+        ] {
+            assert!(!error.is_transient(), "{:?}", error);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::error::AppError;
+use super::sync::SyncResult;
+use super::sync_backend::{PutCondition, PutOutcome, RemoteEntry, RemoteFile, SyncBackend};
+
+use crate::t;
+
+// ========== 本地 / NAS 目录同步 ==========
+//
+// 把同步目录指向本机路径或已挂载的网络共享（SMB、NFS、网盘客户端目录等）。
+// ETag 取文件内容的 SHA-256，不依赖各种文件系统精度不一的修改时间。
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderConfig {
+    /// 同步目录的绝对路径
+    pub path: String,
+}
+
+pub struct FolderBackend {
+    root: PathBuf,
+}
+
+impl FolderBackend {
+    pub fn new(config: &FolderConfig) -> Self {
+        Self::at(PathBuf::from(config.path.trim()))
+    }
+
+    /// 以指定目录为根，供 git 后端复用
+    pub fn at(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf, AppError> {
+        let mut resolved = self.root.clone();
+        for segment in path.split('/').filter(|s| !s.is_empty()) {
+            if segment == "." || segment == ".." {
+                return Err(AppError::InvalidInput(t!(InvalidPath, path)));
+            }
+            resolved.push(segment);
+        }
+        Ok(resolved)
+    }
+}
+
+fn file_etag(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// 读取文件，不存在时返回 `None`
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, AppError> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(AppError::io(path, e)),
+    }
+}
+
+#[async_trait]
+impl SyncBackend for FolderBackend {
+    fn id(&self) -> String {
+        format!("file://{}/", self.root.display().to_string().trim_end_matches(['/', '\\']))
+    }
+
+    /// 根目录必须已存在，避免 NAS 未挂载时把文件写到本地挂载点下
+    async fn prepare(&self, _result: &mut SyncResult) -> Result<(), AppError> {
+        if self.root.as_os_str().is_empty() {
+            return Err(AppError::InvalidInput(t!(SyncDirNotSet)));
+        }
+        if !self.root.is_dir() {
+            return Err(AppError::InvalidInput(t!(SyncDirMissing, self.root.display())));
+        }
+        Ok(())
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>, AppError> {
+        let dir = self.resolve(dir)?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(AppError::io(&dir, e)),
+        };
+
+        let mut items = Vec::new();
+        for entry in entries.flatten() {
+            let Some(name) = entry.file_name().to_str().map(|s| s.to_string()) else {
+                continue;
+            };
+            let path = entry.path();
+            if path.is_dir() {
+                items.push(RemoteEntry { name, is_dir: true, size: None, etag: None });
+            } else if let Ok(content) = fs::read(&path) {
+                items.push(RemoteEntry {
+                    name,
+                    is_dir: false,
+                    size: Some(content.len() as u64),
+                    etag: Some(file_etag(&content)),
+                });
+            }
+        }
+        Ok(items)
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<RemoteFile>, AppError> {
+        Ok(read_optional(&self.resolve(path)?)?.map(|content| RemoteFile {
+            etag: Some(file_etag(&content)),
+            content,
+        }))
+    }
+
+    /// 条件检查和写入之间没有跨设备的锁，只能尽量缩小覆盖他人修改的窗口
+    async fn put(&self, path: &str, content: &[u8], condition: PutCondition<'_>) -> Result<PutOutcome, AppError> {
+        let target = self.resolve(path)?;
+        let current = read_optional(&target)?;
+        let allowed = match condition {
+            PutCondition::Always => true,
+            PutCondition::IfMatch(etag) => current.as_deref().map(file_etag).as_deref() == Some(etag),
+            PutCondition::IfAbsent => current.is_none(),
+        };
+        if !allowed {
+            return Ok(PutOutcome::Conflict);
+        }
+
+        // 先写临时文件再重命名，同步中断时不会留下半个文件
+        let file_name = target.file_name().and_then(|s| s.to_str()).unwrap_or("file");
+        let temp = target.with_file_name(format!(".{}.tmp", file_name));
+        fs::write(&temp, content).map_err(|e| AppError::io(&target, e))?;
+        fs::rename(&temp, &target).map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            AppError::io(&target, e)
+        })?;
+        Ok(PutOutcome::Written(Some(file_etag(content))))
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), AppError> {
+        let target = self.resolve(path)?;
+        let result = if target.is_dir() {
+            fs::remove_dir_all(&target)
+        } else {
+            fs::remove_file(&target)
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::io(&target, e)),
+        }
+    }
+
+    async fn mkdir(&self, dir: &str) -> Result<(), AppError> {
+        let target = self.resolve(dir)?;
+        fs::create_dir_all(&target).map_err(|e| AppError::io(&target, e))
+    }
+}
@@ -0,0 +1,287 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+use std::process::Output;
+use tokio::process::Command;
+
+use super::config::get_git_sync_dir;
+use super::error::AppError;
+use super::folder::FolderBackend;
+use super::sync::SyncResult;
+use super::sync_backend::{PutCondition, PutOutcome, RemoteEntry, RemoteFile, SyncBackend};
+
+use crate::t;
+
+// ========== git 仓库同步 ==========
+//
+// 在 ~/.myswitch/sync-git/ 下维护仓库的工作副本：同步前强制对齐远程分支，
+// 读写都落在工作副本上，同步后提交并推送。认证沿用本机 git 的 SSH 密钥或凭据管理器。
+//
+// 推送失败时同步清单不会更新，下次同步会丢弃未推送的提交并重新比较，不会丢失本地修改。
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitRepoConfig {
+    /// 仓库地址，如 git@github.com:me/codex-sync.git
+    pub url: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+pub struct GitBackend {
+    config: GitRepoConfig,
+    folder: FolderBackend,
+}
+
+impl GitBackend {
+    pub fn new(config: &GitRepoConfig) -> Self {
+        // 不同仓库使用不同的工作副本
+        let digest = hex::encode(Sha256::digest(config.url.trim().as_bytes()));
+        Self {
+            config: config.clone(),
+            folder: FolderBackend::at(get_git_sync_dir().join(&digest[..16])),
+        }
+    }
+
+    fn dir(&self) -> PathBuf {
+        self.folder.root().to_path_buf()
+    }
+
+    fn branch(&self) -> &str {
+        match self.config.branch.trim() {
+            "" => "main",
+            branch => branch,
+        }
+    }
+
+    /// 在工作副本中运行 git，返回完整输出，由调用方判断退出码
+    async fn run(&self, args: &[&str]) -> Result<Output, AppError> {
+        Command::new("git")
+            .arg("-C")
+            .arg(self.dir())
+            .args(args)
+            // 没有可用凭据时直接失败，而不是等待终端输入
+            .env("GIT_TERMINAL_PROMPT", "0")
+            // 固定输出语言，错误信息和提示不随系统语言变化
+            .env("LC_ALL", "C")
+            .env("LANGUAGE", "C")
+            .output()
+            .await
+            .map_err(|e| AppError::Other(t!(GitNotInstalled, e)))
+    }
+
+    /// 在工作副本中运行 git，返回标准输出
+    async fn git(&self, args: &[&str]) -> Result<String, AppError> {
+        let output = self.run(args).await?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(failed(args, &output))
+        }
+    }
+
+    /// 检查用户填写的分支名，避免被 git 当作选项或非法引用
+    async fn check_branch(&self) -> Result<(), AppError> {
+        let branch = self.branch();
+        let valid = !branch.starts_with('-')
+            && self.run(&["check-ref-format", &format!("refs/heads/{}", branch)]).await?.status.success();
+        if valid {
+            Ok(())
+        } else {
+            Err(AppError::InvalidInput(t!(GitBranchInvalid, branch)))
+        }
+    }
+
+    /// 远程是否已有同步分支（空仓库或分支尚未创建时没有）
+    async fn remote_branch_exists(&self) -> Result<bool, AppError> {
+        let pattern = format!("refs/heads/{}", self.branch());
+        let args = ["ls-remote", "--exit-code", "--heads", "origin", &pattern];
+        let output = self.run(&args).await?;
+        // --exit-code：没有匹配的引用时退出码为 2
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(2) => Ok(false),
+            _ => Err(failed(&args, &output)),
+        }
+    }
+
+    /// 未配置提交者时使用的身份
+    async fn commit_identity(&self) -> Vec<&'static str> {
+        if self.git(&["config", "user.email"]).await.is_ok() {
+            Vec::new()
+        } else {
+            vec!["-c", "user.name=Codex Manager", "-c", "user.email=codex-manager@localhost"]
+        }
+    }
+}
+
+#[async_trait]
+impl SyncBackend for GitBackend {
+    fn id(&self) -> String {
+        format!("git+{}#{}/", self.config.url.trim(), self.branch())
+    }
+
+    async fn prepare(&self, _result: &mut SyncResult) -> Result<(), AppError> {
+        let url = self.config.url.trim();
+        if url.is_empty() {
+            return Err(AppError::InvalidInput(t!(GitUrlNotSet)));
+        }
+        // 以 - 开头的地址会被 git 当作选项（如 --upload-pack）
+        if url.starts_with('-') || url.chars().any(char::is_control) {
+            return Err(AppError::InvalidInput(t!(GitUrlInvalid, url)));
+        }
+
+        let dir = self.dir();
+        fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+        self.check_branch().await?;
+        // 地址放在 -- 之后，只会被当作位置参数
+        if !dir.join(".git").exists() {
+            self.git(&["init", "-q"]).await?;
+            self.git(&["symbolic-ref", "HEAD", &format!("refs/heads/{}", self.branch())]).await?;
+            self.git(&["remote", "add", "--", "origin", url]).await?;
+        } else {
+            self.git(&["remote", "set-url", "--", "origin", url]).await?;
+        }
+
+        // 空仓库或分支尚未创建，首次推送时创建
+        if self.remote_branch_exists().await? {
+            self.git(&["fetch", "-q", "origin", &format!("refs/heads/{}", self.branch())]).await?;
+            // 工作副本只是缓存，以远程为准
+            self.git(&["reset", "-q", "--hard", "FETCH_HEAD"]).await?;
+            self.git(&["clean", "-q", "-fd"]).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(&self, _result: &mut SyncResult) -> Result<(), AppError> {
+        self.git(&["add", "-A"]).await?;
+        if !self.git(&["status", "--porcelain"]).await?.trim().is_empty() {
+            let message = t!(GitCommitMessage, chrono::Local::now().format("%Y-%m-%d %H:%M:%S"));
+            let mut args = self.commit_identity().await;
+            args.extend(["commit", "-q", "-m", &message]);
+            self.git(&args).await?;
+        }
+        if self.git(&["rev-parse", "-q", "--verify", "HEAD"]).await.is_err() {
+            // 没有任何提交，无需推送
+            return Ok(());
+        }
+
+        let refspec = format!("HEAD:refs/heads/{}", self.branch());
+        let args = ["push", "--porcelain", "origin", &refspec];
+        let output = self.run(&args).await?;
+        if output.status.success() {
+            Ok(())
+        } else if push_rejected(&String::from_utf8_lossy(&output.stdout)) {
+            Err(AppError::Conflict(t!(GitPushRejected)))
+        } else {
+            Err(failed(&args, &output))
+        }
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>, AppError> {
+        let mut items = self.folder.list(dir).await?;
+        if dir.is_empty() {
+            items.retain(|item| item.name != ".git");
+        }
+        Ok(items)
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<RemoteFile>, AppError> {
+        self.folder.get(path).await
+    }
+
+    async fn put(&self, path: &str, content: &[u8], condition: PutCondition<'_>) -> Result<PutOutcome, AppError> {
+        self.folder.put(path, content, condition).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), AppError> {
+        self.folder.delete(path).await
+    }
+
+    async fn mkdir(&self, dir: &str) -> Result<(), AppError> {
+        self.folder.mkdir(dir).await
+    }
+}
+
+/// git 命令失败时的错误，附带标准错误输出
+fn failed(args: &[&str], output: &Output) -> AppError {
+    AppError::Other(t!(GitFailed, args[0], String::from_utf8_lossy(&output.stderr).trim()))
+}
+
+/// `git push --porcelain` 的输出中是否有被拒绝的引用（标志位为 `!`，不随语言变化）
+fn push_rejected(porcelain: &str) -> bool {
+    porcelain.lines().any(|line| line.starts_with('!'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support;
+
+    /// 指向同一个裸仓库的两台设备（地址写法不同，工作副本也不同）
+    fn devices(home: &std::path::Path) -> (GitBackend, GitBackend) {
+        let bare = home.join("remote.git");
+        let status = std::process::Command::new("git").args(["init", "-q", "--bare"]).arg(&bare).status().unwrap();
+        assert!(status.success());
+        let config = |url: String| GitRepoConfig { url, branch: "sync".to_string() };
+        (
+            GitBackend::new(&config(bare.display().to_string())),
+            GitBackend::new(&config(format!("file://{}", bare.display()))),
+        )
+    }
+
+    #[tokio::test]
+    async fn syncs_through_empty_remote_and_detects_rejected_push() {
+        let home = test_support::home();
+        let (a, b) = devices(&home.path);
+        let mut result = SyncResult::default();
+
+        // 远程分支尚不存在
+        a.prepare(&mut result).await.unwrap();
+        a.mkdir("accounts").await.unwrap();
+        a.put("accounts/a.json", b"one", PutCondition::Always).await.unwrap();
+        a.finish(&mut result).await.unwrap();
+
+        b.prepare(&mut result).await.unwrap();
+        assert_eq!(b.get("accounts/a.json").await.unwrap().unwrap().content, b"one");
+
+        // A 先推送，B 基于旧版本的推送被拒绝
+        a.prepare(&mut result).await.unwrap();
+        a.put("accounts/a.json", b"two", PutCondition::Always).await.unwrap();
+        a.finish(&mut result).await.unwrap();
+        b.put("accounts/b.json", b"three", PutCondition::Always).await.unwrap();
+        assert!(matches!(b.finish(&mut result).await, Err(AppError::Conflict(_))));
+
+        // 重新对齐后再推送成功
+        b.prepare(&mut result).await.unwrap();
+        assert_eq!(b.get("accounts/a.json").await.unwrap().unwrap().content, b"two");
+        b.put("accounts/b.json", b"three", PutCondition::Always).await.unwrap();
+        b.finish(&mut result).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn rejects_option_like_url_and_branch() {
+        let _home = test_support::home();
+        let mut result = SyncResult::default();
+        let cases = [
+            ("--upload-pack=touch /tmp/pwned", "main"),
+            ("git@example.com:me/sync.git", "--orphan"),
+            ("git@example.com:me/sync.git", "a..b"),
+        ];
+        for (url, branch) in cases {
+            let backend = GitBackend::new(&GitRepoConfig { url: url.to_string(), branch: branch.to_string() });
+            assert!(matches!(backend.prepare(&mut result).await, Err(AppError::InvalidInput(_))), "{url} {branch}");
+        }
+    }
+
+    #[test]
+    fn reads_rejection_from_porcelain_flags() {
+        assert!(push_rejected("To /tmp/r.git\n!\tHEAD:refs/heads/sync\t[rejected] (fetch first)\nDone\n"));
+        assert!(!push_rejected("To /tmp/r.git\n \tHEAD:refs/heads/sync\t3f2a..9c1b\nDone\n"));
+    }
+}
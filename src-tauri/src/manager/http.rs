@@ -0,0 +1,96 @@
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::config::{load_config, NetworkConfig};
+use super::error::AppError;
+
+use crate::t;
+
+// ========== HTTP 客户端 ==========
+//
+// 用量查询、Token 刷新和 WebDAV / S3 同步共用同一个 reqwest 客户端（内部有连接池，
+// 查询几十个账号的用量时复用到同一主机的连接），统一应用 config.json 中的代理和自定义 CA 设置。
+
+/// 建立连接的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 单个请求的总超时，坚果云 PROPFIND 可能较慢
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 共享客户端及创建它时使用的代理 / CA 设置，设置变化后重建
+static SHARED: Mutex<Option<(ClientKey, reqwest::Client)>> = Mutex::new(None);
+
+/// 影响客户端本身的网络设置（接口地址在请求时读取，不需要重建客户端）
+#[derive(PartialEq, Eq)]
+struct ClientKey {
+    proxy: Option<String>,
+    no_proxy: Option<String>,
+    ca_bundle: Option<String>,
+}
+
+impl ClientKey {
+    fn of(network: &NetworkConfig) -> Self {
+        Self {
+            proxy: network.proxy.clone(),
+            no_proxy: network.no_proxy.clone(),
+            ca_bundle: network.ca_bundle.clone(),
+        }
+    }
+}
+
+/// 按当前配置获取共享客户端
+pub fn client() -> Result<reqwest::Client, AppError> {
+    client_for(&load_config().network)
+}
+
+/// 按指定网络设置获取共享客户端，设置与上次相同时直接复用
+///
+/// CA 证书文件只在创建客户端时读取，替换文件内容后需修改设置或重启应用才会生效。
+pub fn client_for(network: &NetworkConfig) -> Result<reqwest::Client, AppError> {
+    let key = ClientKey::of(network);
+    let mut shared = SHARED.lock().map_err(|_| t!(InternalState))?;
+    if let Some((current, client)) = shared.as_ref() {
+        if *current == key {
+            return Ok(client.clone());
+        }
+    }
+    let client = builder_for(network)?
+        .build()
+        .map_err(|e| t!(CreateClientFailed, e))?;
+    *shared = Some((key, client.clone()));
+    Ok(client)
+}
+
+/// 按指定网络设置创建客户端构建器（带默认超时），调用方可再调整参数
+pub fn builder_for(network: &NetworkConfig) -> Result<reqwest::ClientBuilder, AppError> {
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT);
+
+    if let Some(proxy) = non_empty(&network.proxy) {
+        let mut proxy = reqwest::Proxy::all(proxy).map_err(|e| AppError::InvalidInput(t!(InvalidProxy, e)))?;
+        if let Some(no_proxy) = non_empty(&network.no_proxy) {
+            proxy = proxy.no_proxy(reqwest::NoProxy::from_string(no_proxy));
+        }
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = non_empty(&network.ca_bundle) {
+        let pem = fs::read(path).map_err(|e| AppError::InvalidInput(t!(ReadCaFailed, path, e)))?;
+        let certs = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| AppError::InvalidInput(t!(ParseCaFailed, path, e)))?;
+        if certs.is_empty() {
+            return Err(AppError::InvalidInput(t!(CaBundleEmpty, path)));
+        }
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    Ok(builder)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|s| !s.is_empty())
+}
@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::config::load_config;
+
+// ========== 多语言 ==========
+//
+// 托盘菜单、命令错误、同步结果等显示给用户的文字集中在下面的消息表中，按 config.json 的
+// `locale` 选择语言。每条消息同时给出中文和英文，`{}` 按顺序替换为参数，用 `t!` 宏取用：
+//
+//     t!(TrayShow)                      // "显示窗口" / "Show window"
+//     t!(SyncDirMissing, path.display()) // 带参数
+//
+// 日志（tracing）不做翻译。
+
+/// 显示语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    Zh,
+    En,
+}
+
+/// 当前语言：0 表示尚未从配置读取，其余为 `Locale` 的序号加一
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 当前语言，首次调用时从 config.json 读取
+pub fn locale() -> Locale {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Locale::Zh,
+        2 => Locale::En,
+        _ => {
+            let locale = load_config().locale;
+            set_locale(locale);
+            locale
+        }
+    }
+}
+
+/// 切换语言（只影响之后生成的文字，已经显示的托盘菜单需要重建）
+pub fn set_locale(locale: Locale) {
+    let value = match locale {
+        Locale::Zh => 1,
+        Locale::En => 2,
+    };
+    CURRENT.store(value, Ordering::Relaxed);
+}
+
+/// 取用消息：`t!(Key)` 或 `t!(Key, 参数...)`，返回 `String`
+#[macro_export]
+macro_rules! t {
+    ($key:ident) => {
+        $crate::manager::i18n::Msg::$key.text().to_string()
+    };
+    ($key:ident, $($arg:expr),+ $(,)?) => {
+        $crate::manager::i18n::Msg::$key.format(&[$(&$arg),+])
+    };
+}
+
+macro_rules! catalog {
+    ($($key:ident => $zh:literal, $en:literal;)*) => {
+        /// 消息表的键
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($key,)*
+        }
+
+        impl Msg {
+            #[cfg(test)]
+            const ALL: &'static [Msg] = &[$(Self::$key,)*];
+
+            /// 指定语言的消息模板
+            pub fn template(self, locale: Locale) -> &'static str {
+                match (self, locale) {
+                    $(
+                        (Self::$key, Locale::Zh) => $zh,
+                        (Self::$key, Locale::En) => $en,
+                    )*
+                }
+            }
+        }
+    };
+}
+
+impl Msg {
+    /// 当前语言的文字
+    pub fn text(self) -> &'static str {
+        self.template(locale())
+    }
+
+    /// 当前语言的文字，`{}` 依次替换为 `args`
+    pub fn format(self, args: &[&dyn Display]) -> String {
+        fill(self.text(), args)
+    }
+}
+
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        out.push_str(&rest[..pos]);
+        if let Some(arg) = args.next() {
+            out.push_str(&arg.to_string());
+        }
+        rest = &rest[pos + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+catalog! {
+    // 托盘
+    TrayAccountUsage => "{}（剩余：5 小时 {} / 每周 {}）", "{} (left: 5h {} / week {})";
+    TrayNoAccounts => "没有账号", "No accounts";
+    TrayAccountsUnavailable => "无法读取账号", "Cannot read accounts";
+    TrayNoActiveAccount => "当前未使用账号目录中的账号", "No managed account is active";
+    TrayUsageUnknown => "尚未查询用量", "Usage not fetched yet";
+    TrayWindowPrimary => "5 小时", "5-hour";
+    TrayWindowSecondary => "每周", "Weekly";
+    TrayWindowUsed => "{}：已用 {}%，{}后重置", "{}: {}% used, resets in {}";
+    TrayWindowUsedNoReset => "{}：已用 {}%", "{}: {}% used";
+    TrayWindowReset => "{}：已重置", "{}: reset";
+    DurationDaysHours => "{} 天 {} 小时", "{}d {}h";
+    DurationHoursMinutes => "{} 小时 {} 分钟", "{}h {}m";
+    DurationMinutes => "{} 分钟", "{}m";
+    TrayShow => "显示窗口", "Show window";
+    TrayRefresh => "刷新", "Refresh";
+    TrayQuit => "退出", "Quit";
+    TraySyncing => "正在同步…", "Syncing…";
+    TrayLastSync => "上次同步 {}", "Last synced {}";
+    TrayLastSyncPartial => "上次同步 {}，部分文件未同步", "Last synced {}, some files were not synced";
+    TraySyncFailed => "同步失败，{} 重试", "Sync failed, retrying at {}";
+    TraySyncStopped => "同步失败，请检查同步设置", "Sync failed, check the sync settings";
+
+    // 占位文字
+    Unknown => "未知", "Unknown";
+    Unnamed => "未命名", "Unnamed";
+    Account => "账号 {}", "account {}";
+
+    // 错误
+    ErrNotFound => "未找到: {}", "Not found: {}";
+    ErrAlreadyExists => "{} 已存在", "{} already exists";
+    ErrInvalidAuthJson => "无效的 auth.json: {}", "Invalid auth.json: {}";
+    ErrVaultLocked => "账号库已锁定，请先输入口令解锁", "The account vault is locked, enter the passphrase to unlock it";
+    ErrWrongPassphrase => "口令错误", "Wrong passphrase";
+    ErrNetwork => "网络请求失败: {}", "Network request failed: {}";
+    ErrHttpStatus => "请求失败: HTTP {}", "Request failed: HTTP {}";
+    ErrTokenExpired => "Refresh Token 已过期，请重新登录 Codex CLI", "The refresh token has expired, log in to Codex CLI again";
+    ErrRefreshTokenReused => "Refresh Token 已被使用，请重新登录 Codex CLI", "The refresh token has already been used, log in to Codex CLI again";
+    ErrRefreshTokenInvalidated => "Refresh Token 已被撤销，请重新登录 Codex CLI", "The refresh token has been revoked, log in to Codex CLI again";
+    ErrWebDavAuth => "WebDAV 认证失败，请检查用户名和密码（坚果云需使用应用密码）", "WebDAV authentication failed, check the username and password (Jianguoyun requires an app password)";
+    ErrS3Auth => "S3 认证失败，请检查 Access Key 和 Secret Key", "S3 authentication failed, check the access key and secret key";
+    ErrSyncNotConfigured => "尚未配置同步目标，请先在设置中保存", "No sync target is configured, save one in Settings first";
+    ErrIo => "文件读写失败: {}", "File operation failed: {}";
+    InternalState => "内部状态异常", "Internal state error";
+    InvalidPath => "非法路径: {}", "Invalid path: {}";
+    SerializeFailed => "序列化失败: {}", "Serialization failed: {}";
+    ParseResponseFailed => "解析 {} 响应失败: {}", "Failed to parse {} response: {}";
+    OpenDirFailed => "打开目录失败: {}", "Failed to open directory: {}";
+
+    // 加密
+    CorruptEncryptedData => "加密数据已损坏: {}", "Encrypted data is corrupted: {}";
+    UnsupportedKdf => "不支持的密钥派生算法: {}", "Unsupported key derivation algorithm: {}";
+    DeriveKeyFailed => "派生密钥失败: {}", "Key derivation failed: {}";
+    EncryptFailed => "加密失败", "Encryption failed";
+    DecryptFailed => "解密失败：口令错误或数据已损坏", "Decryption failed: wrong passphrase or corrupted data";
+    DecryptedNotText => "解密结果不是有效的文本: {}", "Decrypted data is not valid text: {}";
+    VaultNotEnabled => "账号目录未启用加密存储", "Encrypted storage is not enabled for the accounts directory";
+    PassphraseTooShort => "口令至少需要 8 个字符", "The passphrase must be at least 8 characters";
+    KeyringUnsupported => "当前系统暂不支持钥匙串", "The system keychain is not supported on this platform";
+    KeyringToolMissing => "无法运行 {}: {}", "Cannot run {}: {}";
+    KeyringWriteFailed => "写入钥匙串失败: {}", "Failed to write to the keychain: {}";
+    CredentialsKeyMissing => "凭据密钥 {} 丢失，无法读取 {} 中已保存的凭据；请删除该文件后重新填写同步凭据", "The credentials key {} is missing, so the credentials saved in {} cannot be read; delete that file and enter the sync credentials again";
+
+    // 网络设置
+    CreateClientFailed => "创建 HTTP 客户端失败: {}", "Failed to create the HTTP client: {}";
+    InvalidProxy => "代理地址无效: {}", "Invalid proxy address: {}";
+    ReadCaFailed => "读取 CA 证书 {} 失败: {}", "Failed to read CA bundle {}: {}";
+    ParseCaFailed => "解析 CA 证书 {} 失败: {}", "Failed to parse CA bundle {}: {}";
+    CaBundleEmpty => "CA 证书文件 {} 中没有证书", "CA bundle {} contains no certificates";
+
+    // 用量与 Token
+    NoUsageUrls => "未配置用量接口", "No usage endpoints are configured";
+    UsageTimeout => "查询超时（{} 秒）", "Query timed out ({} s)";
+    UsageTaskAborted => "查询任务异常退出", "The query task exited unexpectedly";
+    TokenChangedDuringRefresh => "刷新期间账号 Token 已被其他操作更新，已放弃本次刷新结果", "The account's token was updated by another operation during the refresh; the refresh result was discarded";
+    TokenRefreshed => "Token 刷新成功", "Token refreshed";
+    SwitchTokenExpired => "当前账号 Token 已失效", "The current account's token is no longer valid";
+    SwitchBelowThreshold => "当前账号剩余额度低于 {}%", "The current account has less than {}% quota left";
+    SwitchReason => "{}，{}", "{}; {}";
+    SwitchFromTray => "从托盘菜单切换", "Switched from the tray menu";
+
+    // 切换策略
+    ReasonMostRemaining => "5 小时窗口剩余 {}%，为所有候选中最多（套餐 {}）", "{}% left in the 5-hour window, the most of all candidates (plan {})";
+    ReasonEarliestReset => "额度将于 {} 最早重置，优先用掉即将重置的额度（5 小时窗口剩余 {}%）", "Quota resets first at {}, use it up before it resets ({}% left in the 5-hour window)";
+    ReasonNoResetTime => "所有候选均无重置时间，选择剩余额度最多的账号（{}%）", "No candidate reports a reset time, picked the account with the most quota left ({}%)";
+    ReasonRoundRobinNext => "按名称轮询，{} 之后的下一个可用账号", "Round robin by name, next available account after {}";
+    ReasonRoundRobinWrap => "按名称轮询，已到末尾，从第一个可用账号重新开始", "Round robin by name, wrapped around to the first available account";
+    ReasonRoundRobinFirst => "按名称轮询，选择第一个可用账号", "Round robin by name, picked the first available account";
+    ReasonPriorityRank => "优先级列表第 {} 位中第一个可用的账号", "First available account in the priority list (position {})";
+    ReasonPriorityFallback => "优先级列表中的账号均不可用，按套餐（{}）和名称选择", "No account in the priority list is available, picked by plan ({}) and name";
+
+    // 同步
+    ConnectionOk => "连接成功", "Connected";
+    ConnectionOkCreated => "连接成功，已创建远程目录", "Connected, created the remote directory";
+    SyncDirNotSet => "请先设置同步目录", "Set the sync directory first";
+    SyncDirMissing => "同步目录不存在: {}（网络共享请确认已挂载）", "Sync directory does not exist: {} (make sure network shares are mounted)";
+    GitUrlNotSet => "请先设置 git 仓库地址", "Set the git repository URL first";
+    GitUrlInvalid => "git 仓库地址无效: {}", "Invalid git repository URL: {}";
+    GitBranchInvalid => "git 分支名无效: {}", "Invalid git branch name: {}";
+    GitNotInstalled => "无法运行 git，请确认已安装: {}", "Cannot run git, make sure it is installed: {}";
+    GitFailed => "git {} 失败: {}", "git {} failed: {}";
+    GitCommitMessage => "Codex Manager 同步 {}", "Codex Manager sync {}";
+    GitPushRejected => "推送失败：远程仓库已被其他设备更新，请重新同步", "Push rejected: the remote repository was updated by another device, sync again";
+    S3BucketNotSet => "请先设置 S3 存储桶", "Set the S3 bucket first";
+    S3InvalidEndpoint => "S3 服务地址无效: {}", "Invalid S3 endpoint: {}";
+    S3EndpointNoHost => "S3 服务地址无效: 缺少主机名", "Invalid S3 endpoint: missing host name";
+    SyncPassphraseRequired => "请先在设置中填写同步口令，账号文件会加密后再上传", "Enter a sync passphrase in Settings first, account files are encrypted before upload";
+    SyncPassphraseRace => "其他设备刚刚设置了同步口令，请重新同步", "Another device just set the sync passphrase, sync again";
+    RemoteKeyFileCorrupt => "远程同步口令文件已损坏: {}", "The remote sync passphrase file is corrupted: {}";
+    RemoteManifestCorrupt => "远程同步清单已损坏: {}", "The remote sync manifest is corrupted: {}";
+    RemoteManifestTampered => "远程同步清单未通过校验，可能已被篡改", "The remote sync manifest failed verification and may have been tampered with";
+    ManifestContention => "同步清单被其他设备频繁修改，删除记录未能写入，请稍后重新同步", "The sync manifest keeps being changed by other devices, deletions were not recorded, sync again later";
+    MigrateLegacyFailed => "迁移旧目录结构 {}: {}", "Migrating legacy layout {}: {}";
+
+    // 同步结果中的单个文件，第一个参数为文件名
+    FileLocalNotAuth => "{}: 本地文件不是有效的认证文件 {}", "{}: the local file is not a valid auth file {}";
+    FileBothModified => "{}: 本地与远程都已修改，无法判断哪份 Token 更新（本地 {}，远程 {}）", "{}: modified both locally and remotely, cannot tell which token is newer (local {}, remote {})";
+    FileRemoteGone => "{}: 远程文件已不存在", "{}: the remote file no longer exists";
+    FileNotAuth => "{}: 不是有效的认证文件", "{}: not a valid auth file";
+    FileNotAuthDetail => "{}: 不是有效的认证文件 {}", "{}: not a valid auth file {}";
+    FileNotAuthSkipped => "{}: 不是有效的认证文件，已跳过", "{}: not a valid auth file, skipped";
+    FileRemoteChanged => "{}: 远程文件在同步期间被其他设备修改，请重新同步", "{}: the remote file was changed by another device during sync, sync again";
+    FileDeleteRemoteFailed => "{}: 删除远程文件失败 {}", "{}: failed to delete the remote file {}";
+    FileDeleteFailed => "{}: 删除失败 {}", "{}: delete failed {}";
+    FileReadFailed => "{}: 读取失败 {}", "{}: read failed {}";
+    FileWriteFailed => "{}: 写入失败 {}", "{}: write failed {}";
+    FileMigrateConflict => "{}: 迁移为加密失败，远程文件在同步期间被修改", "{}: could not encrypt, the remote file changed during sync";
+    FileMigrateFailed => "{}: 迁移为加密失败 {}", "{}: could not encrypt {}";
+    FileTooLarge => "超过大小限制 ({} > {})", "exceeds the size limit ({} > {})";
+    ListDirFailed => "列目录失败: {}", "Listing failed: {}";
+
+    // 命令行
+    CliError => "错误: {}", "Error: {}";
+    CliSyncPassphraseRequired => "需要 --sync-passphrase（或先在桌面端保存同步口令）", "--sync-passphrase is required (or save a sync passphrase in the desktop app first)";
+    CliFlagRequired => "当前同步后端需要 --{}", "This sync backend requires --{}";
+    CliEnterPassphrase => "请输入加密口令: ", "Enter the vault passphrase: ";
+    CliReadPassphraseFailed => "读取口令失败: {}", "Failed to read the passphrase: {}";
+    CliVaultNeedsPassphrase => "账号库已加密，请通过 {} 提供口令", "The account vault is encrypted, provide the passphrase via {}";
+    CliSwitched => "已切换到 {} ({})", "Switched to {} ({})";
+    CliCodexBothUnsupported => "Codex 配置暂不支持双向同步，请分别使用 upload / download", "Codex config cannot be synced in both directions yet, use upload / download separately";
+    CliVaultStatus => "加密存储: {}", "Encrypted storage: {}";
+    CliEnabled => "已启用", "enabled";
+    CliDisabled => "未启用", "disabled";
+    CliDiagnosticsExported => "已导出诊断包: {}", "Diagnostics exported to {}";
+    CliNeverExhausts => "重置前不会用满", "Not before reset";
+    CliNotEnoughSamples => "记录不足", "Not enough data";
+    CliSuccess => "成功", "OK";
+    CliFailed => "失败", "Failed";
+    CliColName => "名称", "Name";
+    CliColEmail => "邮箱", "Email";
+    CliColPlan => "套餐", "Plan";
+    CliColTokenExpires => "Token 过期", "Token expires";
+    CliColPrimary => "5小时", "5h";
+    CliColWeekly => "每周", "Weekly";
+    CliColReset => "重置", "Resets";
+    CliColRate => "速度", "Rate";
+    CliColExhausts => "预计用满", "Runs out";
+    CliColError => "错误", "Error";
+    CliColResult => "结果", "Result";
+    CliColMessage => "信息", "Message";
+    CliColAction => "操作", "Action";
+    CliColFile => "文件", "File";
+    CliUploaded => "上传", "Uploaded";
+    CliDownloaded => "下载", "Downloaded";
+    CliMigrated => "迁移", "Migrated";
+    CliConflict => "冲突", "Conflict";
+    CliDeleted => "删除", "Deleted";
+    CliSkipped => "跳过", "Skipped";
+    CliEncrypted => "已加密", "Encrypted";
+    CliAlreadyEncrypted => "已跳过", "Already encrypted";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_in_order() {
+        assert_eq!(fill("{}: 读取失败 {}", &[&"a.json", &"boom"]), "a.json: 读取失败 boom");
+        assert_eq!(fill("{} 与 {}", &[&1]), "1 与 ");
+        assert_eq!(fill("无参数", &[&1]), "无参数");
+    }
+
+    #[test]
+    fn every_message_has_matching_placeholders() {
+        // 逐条检查两种语言的参数个数一致，漏写 `{}` 会让参数被静默丢弃
+        let count = |s: &str| s.matches("{}").count();
+        for &msg in Msg::ALL {
+            assert_eq!(count(msg.template(Locale::Zh)), count(msg.template(Locale::En)), "{:?}", msg);
+        }
+    }
+}
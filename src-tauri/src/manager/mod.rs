@@ -0,0 +1,31 @@
+// 账号、用量、Token 刷新与云端同步的核心逻辑，不依赖 Tauri，
+// 由桌面端命令 (gui::commands) 和命令行工具 (codex-manager-cli) 共用。
+
+pub mod accounts;
+pub mod atomic;
+pub mod auth;
+pub mod config;
+pub mod credentials;
+pub mod crypto;
+pub mod diagnostics;
+pub mod error;
+pub mod folder;
+pub mod git_repo;
+pub mod http;
+pub mod i18n;
+pub mod logging;
+pub mod propfind;
+pub mod s3;
+pub mod strategy;
+pub mod sync;
+pub mod sync_backend;
+pub mod sync_manifest;
+#[cfg(test)]
+mod test_support;
+pub mod token;
+pub mod tombstone;
+pub mod usage;
+pub mod usage_history;
+pub mod vault;
+pub mod watch;
+pub mod webdav;
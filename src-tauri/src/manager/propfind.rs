@@ -0,0 +1,302 @@
+use quick_xml::events::Event;
+use quick_xml::name::{Namespace, ResolveResult};
+use quick_xml::NsReader;
+
+use super::error::AppError;
+
+use crate::t;
+
+// ========== WebDAV PROPFIND 响应解析 ==========
+//
+// 按命名空间而不是前缀匹配元素（`d:`、`D:`、`lp1:`、`ns0:` 或默认命名空间都能识别），
+// 每个 <response> 解析为一条 DavEntry；只采用状态为 200 的 <propstat> 中的属性。
+
+const DAV_NS: &[u8] = b"DAV:";
+
+/// multistatus 中的一项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DavEntry {
+    /// 原始 href（可能是绝对 URL，仍为 URL 编码）
+    pub href: String,
+    pub is_collection: bool,
+    pub content_length: Option<u64>,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl DavEntry {
+    /// 解码后的路径部分，去掉协议和主机
+    pub fn path(&self) -> String {
+        href_path(&self.href)
+    }
+
+    /// 若是 `dir`（解码后的路径）的直接子项则返回其名称，目录本身或更深层的项返回 `None`
+    pub fn child_name(&self, dir: &str) -> Option<String> {
+        let dir = format!("{}/", dir.trim_end_matches('/'));
+        let path = self.path();
+        let rest = path.strip_prefix(&dir)?.trim_end_matches('/');
+        if rest.is_empty() || rest.contains('/') {
+            None
+        } else {
+            Some(rest.to_string())
+        }
+    }
+}
+
+/// 取 href 或 URL 的路径部分并做 URL 解码
+pub fn href_path(href: &str) -> String {
+    let path = match href.find("://") {
+        Some(scheme_end) => {
+            let after_host = &href[scheme_end + 3..];
+            after_host.find('/').map_or("/", |i| &after_host[i..])
+        }
+        None => href,
+    };
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    urlencoding::decode(path)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// 正在解析的 DAV 属性
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Href,
+    Status,
+    ContentLength,
+    Etag,
+    LastModified,
+}
+
+/// 单个 <propstat> 中收集到的属性，状态确认为 200 后再合并到条目
+#[derive(Default)]
+struct PropStat {
+    status_ok: bool,
+    is_collection: bool,
+    content_length: Option<u64>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// 解析 207 Multi-Status 响应体
+pub fn parse_multistatus(xml: &str) -> Result<Vec<DavEntry>, AppError> {
+    let mut reader = NsReader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut entries = Vec::new();
+    let mut entry: Option<DavEntry> = None;
+    let mut propstat: Option<PropStat> = None;
+    let mut field: Option<Field> = None;
+    let mut text = String::new();
+
+    loop {
+        let (ns, event) = reader
+            .read_resolved_event()
+            .map_err(|e| t!(ParseResponseFailed, "PROPFIND", e))?;
+        let is_dav = matches!(ns, ResolveResult::Bound(Namespace(DAV_NS)));
+
+        match event {
+            Event::Start(e) if is_dav => match e.local_name().as_ref() {
+                b"response" => entry = Some(DavEntry::default()),
+                b"propstat" => propstat = Some(PropStat::default()),
+                b"collection" => {
+                    if let Some(p) = propstat.as_mut() {
+                        p.is_collection = true;
+                    }
+                }
+                name => {
+                    field = match name {
+                        b"href" if propstat.is_none() => Some(Field::Href),
+                        b"status" => Some(Field::Status),
+                        b"getcontentlength" => Some(Field::ContentLength),
+                        b"getetag" => Some(Field::Etag),
+                        b"getlastmodified" => Some(Field::LastModified),
+                        _ => None,
+                    };
+                    text.clear();
+                }
+            },
+            Event::Empty(e) if is_dav && e.local_name().as_ref() == b"collection" => {
+                if let Some(p) = propstat.as_mut() {
+                    p.is_collection = true;
+                }
+            }
+            Event::Text(e) if field.is_some() => {
+                text.push_str(&e.decode().map_err(|e| t!(ParseResponseFailed, "PROPFIND", e))?);
+            }
+            Event::CData(e) if field.is_some() => {
+                text.push_str(&e.decode().map_err(|e| t!(ParseResponseFailed, "PROPFIND", e))?);
+            }
+            Event::GeneralRef(e) if field.is_some() => {
+                if let Ok(Some(c)) = e.resolve_char_ref() {
+                    text.push(c);
+                } else {
+                    let name = e.decode().map_err(|e| t!(ParseResponseFailed, "PROPFIND", e))?;
+                    let resolved = quick_xml::escape::resolve_predefined_entity(&name)
+                        .ok_or_else(|| t!(ParseResponseFailed, "PROPFIND", format!("&{};", name)))?;
+                    text.push_str(resolved);
+                }
+            }
+            Event::End(e) if is_dav => match e.local_name().as_ref() {
+                b"response" => {
+                    if let Some(entry) = entry.take() {
+                        if !entry.href.is_empty() {
+                            entries.push(entry);
+                        }
+                    }
+                }
+                b"propstat" => {
+                    if let (Some(p), Some(entry)) = (propstat.take(), entry.as_mut()) {
+                        if p.status_ok {
+                            entry.is_collection |= p.is_collection;
+                            entry.content_length = entry.content_length.or(p.content_length);
+                            entry.etag = entry.etag.take().or(p.etag);
+                            entry.last_modified = entry.last_modified.take().or(p.last_modified);
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(current) = field.take() {
+                        let value = text.trim().to_string();
+                        match (current, entry.as_mut(), propstat.as_mut()) {
+                            (Field::Href, Some(entry), _) => entry.href = value,
+                            (Field::Status, _, Some(p)) => p.status_ok = value.split_whitespace().nth(1) == Some("200"),
+                            (Field::ContentLength, _, Some(p)) => p.content_length = value.parse().ok(),
+                            (Field::Etag, _, Some(p)) if !value.is_empty() => p.etag = Some(value),
+                            (Field::LastModified, _, Some(p)) if !value.is_empty() => p.last_modified = Some(value),
+                            _ => {}
+                        }
+                    }
+                }
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(entries: &'a [DavEntry], name: &str, dir: &str) -> &'a DavEntry {
+        entries
+            .iter()
+            .find(|e| e.child_name(dir).as_deref() == Some(name))
+            .unwrap_or_else(|| panic!("未找到 {}", name))
+    }
+
+    #[test]
+    fn parses_nextcloud_response() {
+        let entries = parse_multistatus(include_str!("testdata/propfind_nextcloud.xml")).unwrap();
+        let dir = "/remote.php/dav/files/alice/codex-manager/accounts/";
+        assert_eq!(entries.len(), 4);
+
+        // 目录本身不算子项
+        assert_eq!(entries[0].child_name(dir), None);
+        assert!(entries[0].is_collection);
+
+        let alice = find(&entries, "alice.json", dir);
+        assert!(!alice.is_collection);
+        assert_eq!(alice.content_length, Some(4242));
+        assert_eq!(alice.etag.as_deref(), Some("\"8f3b9a1c2d4e5f60\""));
+        assert_eq!(alice.last_modified.as_deref(), Some("Tue, 07 Jan 2025 09:15:02 GMT"));
+
+        // 404 propstat 中的空属性不应覆盖有效值
+        let work = find(&entries, "工作 账号.json", dir);
+        assert_eq!(work.content_length, Some(3981));
+        assert_eq!(work.etag.as_deref(), Some("\"a1b2c3d4e5f60718\""));
+
+        let sub = find(&entries, "archive", dir);
+        assert!(sub.is_collection);
+        assert_eq!(sub.content_length, None);
+    }
+
+    #[test]
+    fn parses_jianguoyun_response() {
+        let entries = parse_multistatus(include_str!("testdata/propfind_jianguoyun.xml")).unwrap();
+        let dir = "/dav/codex-manager/codex/skills/";
+        assert_eq!(entries.len(), 3);
+
+        let skill = find(&entries, "pdf-tools", dir);
+        assert!(skill.is_collection);
+
+        let readme = find(&entries, "说明.md", dir);
+        assert!(!readme.is_collection);
+        assert_eq!(readme.content_length, Some(1536));
+        // 坚果云不返回 ETag
+        assert_eq!(readme.etag, None);
+        assert_eq!(readme.last_modified.as_deref(), Some("Mon, 06 Jan 2025 08:00:00 GMT"));
+    }
+
+    #[test]
+    fn parses_apache_mod_dav_response() {
+        let entries = parse_multistatus(include_str!("testdata/propfind_apache.xml")).unwrap();
+        let dir = "/webdav/codex-manager/";
+        assert_eq!(entries.len(), 4);
+
+        // Apache 用 lp1: 前缀表示 DAV: 命名空间
+        let accounts = find(&entries, "accounts", dir);
+        assert!(accounts.is_collection);
+
+        let agents = find(&entries, "AGENTS.MD", dir);
+        assert!(!agents.is_collection);
+        assert_eq!(agents.content_length, Some(812));
+        assert_eq!(agents.etag.as_deref(), Some("\"32c-62b8c4a1f0e40\""));
+
+        let logo = find(&entries, "logo image.png", dir);
+        assert_eq!(logo.content_length, Some(20480));
+    }
+
+    #[test]
+    fn handles_other_prefixes_and_absolute_urls() {
+        let xml = r#"<?xml version="1.0"?>
+<ns0:multistatus xmlns:ns0="DAV:">
+  <ns0:response>
+    <ns0:href>https://dav.example.com:8443/files/codex/</ns0:href>
+    <ns0:propstat>
+      <ns0:prop><ns0:resourcetype><ns0:collection/></ns0:resourcetype></ns0:prop>
+      <ns0:status>HTTP/1.1 200 OK</ns0:status>
+    </ns0:propstat>
+  </ns0:response>
+  <response xmlns="DAV:">
+    <href>https://dav.example.com:8443/files/codex/a%26b.json</href>
+    <propstat>
+      <prop><resourcetype/><getcontentlength>10</getcontentlength><getetag>W/&quot;abc&quot;</getetag></prop>
+      <status>HTTP/1.1 200 OK</status>
+    </propstat>
+  </response>
+</ns0:multistatus>"#;
+        let entries = parse_multistatus(xml).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path(), "/files/codex/");
+        assert_eq!(entries[0].child_name("/files/codex"), None);
+        assert_eq!(entries[1].child_name("/files/codex").as_deref(), Some("a&b.json"));
+        assert_eq!(entries[1].etag.as_deref(), Some("W/\"abc\""));
+        assert!(!entries[1].is_collection);
+    }
+
+    #[test]
+    fn ignores_elements_from_other_namespaces() {
+        let xml = r#"<d:multistatus xmlns:d="DAV:" xmlns:x="urn:example">
+  <d:response>
+    <d:href>/dav/a.json</d:href>
+    <d:propstat>
+      <d:prop><x:getetag>"fake"</x:getetag><x:collection/><d:getcontentlength>5</d:getcontentlength></d:prop>
+      <d:status>HTTP/1.1 200 OK</d:status>
+    </d:propstat>
+  </d:response>
+</d:multistatus>"#;
+        let entries = parse_multistatus(xml).unwrap();
+        assert_eq!(entries[0].etag, None);
+        assert!(!entries[0].is_collection);
+        assert_eq!(entries[0].content_length, Some(5));
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        assert!(parse_multistatus("<d:multistatus xmlns:d=\"DAV:\"><d:response></d:multistatus>").is_err());
+    }
+}
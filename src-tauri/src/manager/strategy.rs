@@ -0,0 +1,480 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use super::accounts::AccountInfo;
+use super::config::AutoSwitchConfig;
+use super::usage::{RateLimitWindow, UsageInfo, UsageUpdate};
+
+use crate::t;
+
+// ========== 账号选择策略 ==========
+
+/// 内置的备用账号选择策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SwitchStrategyKind {
+    /// 5 小时窗口剩余额度最多
+    MostRemaining,
+    /// 周窗口重置时间最早（默认，尽量先用掉快要重置的额度）
+    #[default]
+    EarliestReset,
+    /// 按账号名称轮询，保证团队共享席位的轮换顺序可预测
+    RoundRobin,
+    /// 按用户设定的优先级顺序
+    Priority,
+}
+
+/// 参与选择的账号及其最新用量
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate<'a> {
+    pub account: &'a AccountInfo,
+    pub usage: Option<&'a UsageInfo>,
+    pub token_expired: bool,
+}
+
+impl Candidate<'_> {
+    fn primary(&self) -> Option<&RateLimitWindow> {
+        self.usage.and_then(|u| u.primary_window.as_ref())
+    }
+
+    fn secondary(&self) -> Option<&RateLimitWindow> {
+        self.usage.and_then(|u| u.secondary_window.as_ref())
+    }
+
+    fn primary_remaining(&self) -> f64 {
+        100.0 - self.primary().map_or(0.0, |w| w.used_percent)
+    }
+
+    /// 优先使用接口返回的套餐，其次使用 id_token 中的套餐
+    fn plan_type(&self) -> &str {
+        self.usage
+            .and_then(|u| u.plan_type.as_deref())
+            .unwrap_or(&self.account.plan_type)
+    }
+}
+
+/// 选择结果，附带选择理由
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchDecision {
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub email: String,
+    pub strategy: SwitchStrategyKind,
+    pub reason: String,
+}
+
+/// 备用账号选择策略
+///
+/// `candidates` 已经过滤掉 Token 失效或额度不足的账号且不为空，
+/// 返回选中账号在 `candidates` 中的下标以及选择理由。
+pub trait SwitchStrategy {
+    fn kind(&self) -> SwitchStrategyKind;
+
+    fn select(&self, current: Option<&AccountInfo>, candidates: &[Candidate]) -> Option<(usize, String)>;
+}
+
+pub struct MostRemaining;
+pub struct EarliestReset;
+pub struct RoundRobin;
+pub struct Priority {
+    pub order: Vec<String>,
+}
+
+/// 套餐权重，其他条件相同时优先使用额度更大的套餐
+fn plan_weight(plan_type: &str) -> u8 {
+    match plan_type.to_lowercase().as_str() {
+        "pro" => 4,
+        "enterprise" | "business" | "team" | "edu" => 3,
+        "plus" => 2,
+        "free" => 1,
+        _ => 0,
+    }
+}
+
+fn by_plan_desc(a: &Candidate, b: &Candidate) -> Ordering {
+    plan_weight(b.plan_type()).cmp(&plan_weight(a.plan_type()))
+}
+
+fn by_name(a: &Candidate, b: &Candidate) -> Ordering {
+    a.account.name.to_lowercase().cmp(&b.account.name.to_lowercase())
+}
+
+/// 按比较函数选出最小的候选下标
+fn min_index_by(candidates: &[Candidate], compare: impl Fn(&Candidate, &Candidate) -> Ordering) -> Option<usize> {
+    (0..candidates.len()).min_by(|&a, &b| compare(&candidates[a], &candidates[b]))
+}
+
+fn format_reset(resets_at: i64) -> String {
+    chrono::DateTime::from_timestamp(resets_at, 0)
+        .map(|t| t.with_timezone(&chrono::Local).format("%m-%d %H:%M").to_string())
+        .unwrap_or_else(|| resets_at.to_string())
+}
+
+impl SwitchStrategy for MostRemaining {
+    fn kind(&self) -> SwitchStrategyKind {
+        SwitchStrategyKind::MostRemaining
+    }
+
+    fn select(&self, _current: Option<&AccountInfo>, candidates: &[Candidate]) -> Option<(usize, String)> {
+        let index = min_index_by(candidates, |a, b| {
+            b.primary_remaining()
+                .total_cmp(&a.primary_remaining())
+                .then_with(|| by_plan_desc(a, b))
+                .then_with(|| by_name(a, b))
+        })?;
+        let chosen = &candidates[index];
+        let reason = t!(
+            ReasonMostRemaining,
+            format!("{:.0}", chosen.primary_remaining()),
+            chosen.plan_type()
+        );
+        Some((index, reason))
+    }
+}
+
+impl SwitchStrategy for EarliestReset {
+    fn kind(&self) -> SwitchStrategyKind {
+        SwitchStrategyKind::EarliestReset
+    }
+
+    fn select(&self, _current: Option<&AccountInfo>, candidates: &[Candidate]) -> Option<(usize, String)> {
+        // 周窗口优先，没有周窗口数据时退回 5 小时窗口
+        let resets_at = |c: &Candidate| {
+            c.secondary()
+                .and_then(|w| w.resets_at)
+                .or_else(|| c.primary().and_then(|w| w.resets_at))
+        };
+        let index = min_index_by(candidates, |a, b| {
+            resets_at(a)
+                .unwrap_or(i64::MAX)
+                .cmp(&resets_at(b).unwrap_or(i64::MAX))
+                .then_with(|| b.primary_remaining().total_cmp(&a.primary_remaining()))
+                .then_with(|| by_plan_desc(a, b))
+        })?;
+        let chosen = &candidates[index];
+        let reason = match resets_at(chosen) {
+            Some(ts) => t!(
+                ReasonEarliestReset,
+                format_reset(ts),
+                format!("{:.0}", chosen.primary_remaining())
+            ),
+            None => t!(ReasonNoResetTime, format!("{:.0}", chosen.primary_remaining())),
+        };
+        Some((index, reason))
+    }
+}
+
+impl SwitchStrategy for RoundRobin {
+    fn kind(&self) -> SwitchStrategyKind {
+        SwitchStrategyKind::RoundRobin
+    }
+
+    fn select(&self, current: Option<&AccountInfo>, candidates: &[Candidate]) -> Option<(usize, String)> {
+        let mut order: Vec<usize> = (0..candidates.len()).collect();
+        order.sort_by(|&a, &b| by_name(&candidates[a], &candidates[b]));
+
+        // 按名称顺序取当前账号之后的第一个，没有则回到开头
+        let next = current.and_then(|cur| {
+            let cur_name = cur.name.to_lowercase();
+            order
+                .iter()
+                .copied()
+                .find(|&i| candidates[i].account.name.to_lowercase() > cur_name)
+        });
+        let index = next.or_else(|| order.first().copied())?;
+
+        let reason = match (current, next) {
+            (Some(cur), Some(_)) => t!(ReasonRoundRobinNext, cur.name),
+            (Some(_), None) => t!(ReasonRoundRobinWrap),
+            (None, _) => t!(ReasonRoundRobinFirst),
+        };
+        Some((index, reason))
+    }
+}
+
+impl Priority {
+    /// 在优先级列表中的位置，可按账号名称、邮箱或 account_id 匹配
+    fn rank(&self, account: &AccountInfo) -> Option<usize> {
+        self.order.iter().position(|entry| {
+            let entry = entry.trim();
+            entry.eq_ignore_ascii_case(&account.name)
+                || (!account.email.is_empty() && entry.eq_ignore_ascii_case(&account.email))
+                || entry == account.id
+        })
+    }
+}
+
+impl SwitchStrategy for Priority {
+    fn kind(&self) -> SwitchStrategyKind {
+        SwitchStrategyKind::Priority
+    }
+
+    fn select(&self, _current: Option<&AccountInfo>, candidates: &[Candidate]) -> Option<(usize, String)> {
+        let index = min_index_by(candidates, |a, b| {
+            self.rank(a.account)
+                .unwrap_or(usize::MAX)
+                .cmp(&self.rank(b.account).unwrap_or(usize::MAX))
+                .then_with(|| by_plan_desc(a, b))
+                .then_with(|| by_name(a, b))
+        })?;
+        let chosen = &candidates[index];
+        let reason = match self.rank(chosen.account) {
+            Some(rank) => t!(ReasonPriorityRank, rank + 1),
+            None => t!(ReasonPriorityFallback, chosen.plan_type()),
+        };
+        Some((index, reason))
+    }
+}
+
+/// 根据配置构建策略
+pub fn strategy_for(config: &AutoSwitchConfig) -> Box<dyn SwitchStrategy + Send + Sync> {
+    match config.strategy {
+        SwitchStrategyKind::MostRemaining => Box::new(MostRemaining),
+        SwitchStrategyKind::EarliestReset => Box::new(EarliestReset),
+        SwitchStrategyKind::RoundRobin => Box::new(RoundRobin),
+        SwitchStrategyKind::Priority => Box::new(Priority { order: config.priority.clone() }),
+    }
+}
+
+/// 过滤掉当前账号、Token 失效以及额度低于阈值的账号后，按配置的策略选出备用账号
+pub fn choose_backup(config: &AutoSwitchConfig, accounts: &[Candidate]) -> Option<SwitchDecision> {
+    let used_limit = 100.0 - config.auto_switch_threshold.clamp(1, 50) as f64;
+    let current = accounts.iter().find(|c| c.account.is_active).map(|c| c.account);
+
+    let candidates: Vec<Candidate> = accounts
+        .iter()
+        .filter(|c| !c.account.is_active && !c.token_expired && c.usage.is_some())
+        .filter(|c| {
+            c.primary().map_or(0.0, |w| w.used_percent) < used_limit
+                && c.secondary().map_or(0.0, |w| w.used_percent) < used_limit
+        })
+        .copied()
+        .collect();
+
+    let strategy = strategy_for(config);
+    let (index, reason) = strategy.select(current, &candidates)?;
+    let chosen = candidates[index].account;
+
+    Some(SwitchDecision {
+        account_id: chosen.id.clone(),
+        file_path: chosen.file_path.clone(),
+        email: chosen.email.clone(),
+        strategy: strategy.kind(),
+        reason,
+    })
+}
+
+/// 将账号与用量查询结果配对，供选择策略使用
+pub fn candidates<'a>(accounts: &'a [AccountInfo], updates: &'a [UsageUpdate]) -> Vec<Candidate<'a>> {
+    accounts
+        .iter()
+        .map(|account| {
+            let update = updates.iter().find(|u| u.file_path == account.file_path);
+            Candidate {
+                account,
+                usage: update.and_then(|u| u.usage.as_ref()),
+                // Refresh Token 已永久失效的账号同样不可用
+                token_expired: update.is_some_and(|u| u.is_token_expired) || account.refresh_failure.is_some(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::token::{RefreshFailure, RefreshFailureKind};
+
+    fn account(name: &str, id: &str, email: &str, plan: &str, active: bool) -> AccountInfo {
+        AccountInfo {
+            id: id.to_string(),
+            name: name.to_string(),
+            email: email.to_string(),
+            plan_type: plan.to_string(),
+            subscription_end: None,
+            is_active: active,
+            file_path: format!("/accounts/{}.json", name),
+            expires_at: None,
+            last_refresh: String::new(),
+            refresh_failure: None,
+        }
+    }
+
+    fn usage(primary_used: f64, weekly_reset: Option<i64>) -> UsageInfo {
+        UsageInfo {
+            primary_window: Some(RateLimitWindow { used_percent: primary_used, window_minutes: Some(300), resets_at: None }),
+            secondary_window: weekly_reset.map(|reset| RateLimitWindow {
+                used_percent: 10.0,
+                window_minutes: Some(10080),
+                resets_at: Some(reset),
+            }),
+            plan_type: None,
+        }
+    }
+
+    fn config(strategy: SwitchStrategyKind, priority: &[&str]) -> AutoSwitchConfig {
+        AutoSwitchConfig {
+            strategy,
+            priority: priority.iter().map(|s| s.to_string()).collect(),
+            ..AutoSwitchConfig::default()
+        }
+    }
+
+    fn chosen(strategy: SwitchStrategyKind, priority: &[&str], candidates: &[Candidate]) -> Option<String> {
+        choose_backup(&config(strategy, priority), candidates).map(|d| d.email)
+    }
+
+    #[test]
+    fn most_remaining_prefers_quota_then_plan() {
+        let accounts = [
+            account("a", "1", "a@x.com", "plus", true),
+            account("b", "2", "b@x.com", "plus", false),
+            account("c", "3", "c@x.com", "plus", false),
+            account("d", "4", "d@x.com", "pro", false),
+        ];
+        let usages = [usage(99.0, None), usage(60.0, None), usage(20.0, None), usage(20.0, None)];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .zip(&usages)
+            .map(|(account, usage)| Candidate { account, usage: Some(usage), token_expired: false })
+            .collect();
+        // c 与 d 剩余相同，d 套餐更大
+        assert_eq!(chosen(SwitchStrategyKind::MostRemaining, &[], &candidates).as_deref(), Some("d@x.com"));
+    }
+
+    #[test]
+    fn earliest_reset_prefers_weekly_reset_then_remaining() {
+        let accounts = [
+            account("a", "1", "a@x.com", "plus", true),
+            account("b", "2", "b@x.com", "plus", false),
+            account("c", "3", "c@x.com", "plus", false),
+            account("d", "4", "d@x.com", "plus", false),
+        ];
+        let usages = [usage(99.0, Some(100)), usage(10.0, Some(3000)), usage(50.0, Some(2000)), usage(0.0, None)];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .zip(&usages)
+            .map(|(account, usage)| Candidate { account, usage: Some(usage), token_expired: false })
+            .collect();
+        assert_eq!(chosen(SwitchStrategyKind::EarliestReset, &[], &candidates).as_deref(), Some("c@x.com"));
+
+        // 都没有重置时间时按剩余额度
+        let usages = [usage(99.0, None), usage(30.0, None), usage(50.0, None), usage(40.0, None)];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .zip(&usages)
+            .map(|(account, usage)| Candidate { account, usage: Some(usage), token_expired: false })
+            .collect();
+        assert_eq!(chosen(SwitchStrategyKind::EarliestReset, &[], &candidates).as_deref(), Some("b@x.com"));
+    }
+
+    #[test]
+    fn round_robin_takes_next_name_and_wraps() {
+        let usage = usage(10.0, None);
+        let accounts = [
+            account("alpha", "1", "alpha@x.com", "plus", false),
+            account("bravo", "2", "bravo@x.com", "plus", true),
+            account("charlie", "3", "charlie@x.com", "plus", false),
+        ];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .map(|account| Candidate { account, usage: Some(&usage), token_expired: false })
+            .collect();
+        assert_eq!(chosen(SwitchStrategyKind::RoundRobin, &[], &candidates).as_deref(), Some("charlie@x.com"));
+
+        let accounts = [
+            account("alpha", "1", "alpha@x.com", "plus", false),
+            account("bravo", "2", "bravo@x.com", "plus", false),
+            account("charlie", "3", "charlie@x.com", "plus", true),
+        ];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .map(|account| Candidate { account, usage: Some(&usage), token_expired: false })
+            .collect();
+        assert_eq!(chosen(SwitchStrategyKind::RoundRobin, &[], &candidates).as_deref(), Some("alpha@x.com"));
+    }
+
+    #[test]
+    fn priority_follows_list_then_plan() {
+        let usage = usage(10.0, None);
+        let accounts = [
+            account("main", "1", "main@x.com", "pro", true),
+            account("spare", "2", "spare@x.com", "plus", false),
+            account("team", "3", "team@x.com", "team", false),
+            account("free", "4", "free@x.com", "free", false),
+        ];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .map(|account| Candidate { account, usage: Some(&usage), token_expired: false })
+            .collect();
+        // 按名称、邮箱或 account_id 匹配
+        assert_eq!(chosen(SwitchStrategyKind::Priority, &["main", "SPARE@x.com", "3"], &candidates).as_deref(), Some("spare@x.com"));
+        assert_eq!(chosen(SwitchStrategyKind::Priority, &["4"], &candidates).as_deref(), Some("free@x.com"));
+        // 列表中都不可用时按套餐
+        assert_eq!(chosen(SwitchStrategyKind::Priority, &["nobody"], &candidates).as_deref(), Some("team@x.com"));
+    }
+
+    #[test]
+    fn rotates_between_team_seats_sharing_account_id() {
+        let usage = usage(10.0, None);
+        let accounts = [
+            account("seat-a", "team", "a@corp.com", "team", true),
+            account("seat-b", "team", "b@corp.com", "team", false),
+            account("seat-c", "team", "c@corp.com", "team", false),
+        ];
+        let candidates: Vec<Candidate> = accounts
+            .iter()
+            .map(|account| Candidate { account, usage: Some(&usage), token_expired: false })
+            .collect();
+        for kind in [
+            SwitchStrategyKind::MostRemaining,
+            SwitchStrategyKind::EarliestReset,
+            SwitchStrategyKind::RoundRobin,
+            SwitchStrategyKind::Priority,
+        ] {
+            let decision = chosen(kind, &[], &candidates);
+            assert!(decision.as_deref().is_some_and(|email| email != "a@corp.com"), "{:?}: {:?}", kind, decision);
+        }
+        assert_eq!(chosen(SwitchStrategyKind::RoundRobin, &[], &candidates).as_deref(), Some("b@corp.com"));
+    }
+
+    #[test]
+    fn skips_expired_unqueried_and_exhausted_accounts() {
+        let fresh = usage(0.0, None);
+        let exhausted = usage(97.0, None);
+        let fine = usage(50.0, None);
+        let accounts = [
+            account("active", "1", "active@x.com", "plus", true),
+            account("expired", "2", "expired@x.com", "pro", false),
+            account("unqueried", "3", "unqueried@x.com", "pro", false),
+            account("exhausted", "4", "exhausted@x.com", "pro", false),
+            account("ok", "5", "ok@x.com", "free", false),
+        ];
+        let mut candidates = vec![
+            Candidate { account: &accounts[0], usage: Some(&fresh), token_expired: false },
+            Candidate { account: &accounts[1], usage: Some(&fresh), token_expired: true },
+            Candidate { account: &accounts[2], usage: None, token_expired: false },
+            Candidate { account: &accounts[3], usage: Some(&exhausted), token_expired: false },
+            Candidate { account: &accounts[4], usage: Some(&fine), token_expired: false },
+        ];
+        assert_eq!(chosen(SwitchStrategyKind::MostRemaining, &[], &candidates).as_deref(), Some("ok@x.com"));
+
+        candidates.pop();
+        assert_eq!(chosen(SwitchStrategyKind::MostRemaining, &[], &candidates), None);
+    }
+
+    #[test]
+    fn permanent_refresh_failure_counts_as_expired() {
+        let mut failed = account("failed", "1", "failed@x.com", "plus", false);
+        failed.refresh_failure = Some(RefreshFailure {
+            kind: RefreshFailureKind::Reused,
+            message: String::new(),
+            failed_at: String::new(),
+        });
+        let accounts = [failed];
+        let candidates = candidates(&accounts, &[]);
+        assert!(candidates[0].token_expired);
+    }
+}
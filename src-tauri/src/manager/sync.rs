@@ -0,0 +1,1160 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::atomic;
+use super::auth::CodexAuthFile;
+use super::config::{get_accounts_dir, get_codex_dir, get_prompts_dir, get_skills_dir};
+use super::crypto::{Envelope, KeyCheck, SecretKey};
+use super::error::AppError;
+use super::sync_backend::{BackendConfig, PutCondition, PutOutcome, RemoteEntry, SyncBackend, SyncTarget};
+use super::sync_manifest::{compare_freshness, content_hash, FileState, SyncManifest};
+use super::tombstone::{self, PendingDeletion, Tombstone, Tombstones};
+use super::vault;
+use super::webdav;
+
+use crate::t;
+
+// ========== 账号与 Codex 配置同步 ==========
+//
+// 与具体存储无关，远程读写全部经过 SyncBackend。
+
+/// 远程 accounts/ 目录中的同步口令校验文件（不以 .json 结尾，不会被当作账号下载）
+const SYNC_KEY_FILE: &str = ".sync-key";
+
+/// 用于验证同步口令的已知明文
+const SYNC_KEY_CHECK: &[u8] = b"codex-manager-sync";
+
+/// 同一进程内的同步依次进行，避免手动同步与后台自动同步同时读写同步清单
+static SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncResult {
+    pub uploaded: Vec<String>,
+    pub downloaded: Vec<String>,
+    pub errors: Vec<String>,
+    /// 旧版本遗留的远程文件（明文账号、旧目录结构），本次已迁移
+    #[serde(default)]
+    pub migrated: Vec<String>,
+    /// 本地与远程都修改过且无法自动判断的文件，两端均保持不变
+    #[serde(default)]
+    pub conflicts: Vec<String>,
+    /// 因超过大小限制等原因未传输的文件及原因
+    #[serde(default)]
+    pub skipped: Vec<String>,
+    /// 因删除或重命名而移除的文件，`a → b` 表示重命名
+    #[serde(default)]
+    pub deleted: Vec<String>,
+}
+
+impl SyncResult {
+    /// 合并另一次同步的结果
+    pub fn merge(&mut self, other: SyncResult) {
+        self.uploaded.extend(other.uploaded);
+        self.downloaded.extend(other.downloaded);
+        self.errors.extend(other.errors);
+        self.migrated.extend(other.migrated);
+        self.conflicts.extend(other.conflicts);
+        self.skipped.extend(other.skipped);
+        self.deleted.extend(other.deleted);
+    }
+}
+
+// ========== 远程目录结构 ==========
+
+/// Codex 配置中的单个文件名
+pub const AGENTS_MD: &str = "AGENTS.MD";
+pub const CONFIG_TOML: &str = "config.toml";
+
+/// 远程目录结构，路径相对于后端根目录，上传和下载都通过它拼路径：
+///
+/// ```text
+/// accounts/*.json        账号文件（加密）
+/// codex/AGENTS.MD
+/// codex/config.toml
+/// codex/prompts/
+/// codex/skills/<skill>/
+/// manifest.json          删除记录（墓碑）
+/// trash/<时间>/          已删除的文件，保留期后清理
+/// ```
+pub struct RemoteLayout;
+
+impl RemoteLayout {
+    pub const ACCOUNTS: &'static str = "accounts/";
+    pub const CODEX: &'static str = "codex/";
+    pub const PROMPTS: &'static str = "codex/prompts/";
+    pub const SKILLS: &'static str = "codex/skills/";
+    pub const MANIFEST: &'static str = "manifest.json";
+    pub const TRASH: &'static str = "trash/";
+
+    /// 需要预先创建的目录，父目录在前
+    pub fn dirs() -> [&'static str; 6] {
+        ["", Self::ACCOUNTS, Self::CODEX, Self::PROMPTS, Self::SKILLS, Self::TRASH]
+    }
+}
+
+/// 准备远程目录：交给后端做准备工作（拉取、迁移旧结构），再确保各子目录存在
+async fn prepare_layout(backend: &dyn SyncBackend, result: &mut SyncResult) -> Result<(), AppError> {
+    backend.prepare(result).await?;
+    for dir in RemoteLayout::dirs() {
+        if let Err(e) = backend.mkdir(dir).await {
+            tracing::warn!(dir, error = %e, "创建远程目录失败");
+        }
+    }
+    Ok(())
+}
+
+/// 测试后端连接：WebDAV 检查远程目录，其他后端做一次准备并列出根目录
+pub async fn test_backend(config: &BackendConfig) -> Result<String, AppError> {
+    if let BackendConfig::Webdav(config) = config {
+        return webdav::webdav_test_connection(config).await;
+    }
+    let backend = config.open()?;
+    backend.prepare(&mut SyncResult::default()).await?;
+    backend.list("").await?;
+    Ok(t!(ConnectionOk))
+}
+
+// ========== 账号同步 ==========
+
+/// 账号同步方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncMode {
+    /// 只把本地较新的文件推送到远程
+    Upload,
+    /// 只把远程较新的文件拉取到本地
+    Download,
+    /// 双向同步，各取较新的一方
+    Both,
+}
+
+impl SyncMode {
+    fn can_upload(self) -> bool {
+        self != SyncMode::Download
+    }
+
+    fn can_download(self) -> bool {
+        self != SyncMode::Upload
+    }
+}
+
+/// 一次账号同步过程中共享的上下文
+struct AccountSync<'a> {
+    backend: &'a dyn SyncBackend,
+    key: SecretKey,
+    accounts_dir: PathBuf,
+    mode: SyncMode,
+    tombstones: Tombstones,
+    /// 本次删除的远程文件移入的回收站目录
+    trash_dir: String,
+    /// 尚未同步过的本地文件：明文哈希 -> 文件名，用于识别重命名
+    new_files: HashMap<String, String>,
+    result: &'a mut SyncResult,
+}
+
+/// 解密并校验后的远程账号文件
+struct RemoteAccount {
+    plaintext: String,
+    auth: CodexAuthFile,
+    etag: Option<String>,
+    /// 旧版本上传的明文
+    legacy: bool,
+}
+
+/// 同步账号文件
+///
+/// 以同步清单为基准做三方比较：
+/// 只有一端修改时同步到另一端，两端都修改时取 Token 较新的一方，无法判断时记为冲突。
+/// 本地删除（包括重命名）的文件在远程移入回收站并留下墓碑，其他设备据此删除本地副本。
+pub async fn sync_accounts(target: &SyncTarget, mode: SyncMode) -> Result<SyncResult, AppError> {
+    let _guard = SYNC_LOCK.lock().await;
+    let backend = target.backend.open()?;
+    let backend = backend.as_ref();
+    let accounts_dir = get_accounts_dir();
+    let mut result = SyncResult::default();
+
+    // 确保本地目录存在
+    if !accounts_dir.exists() {
+        fs::create_dir_all(&accounts_dir).map_err(|e| AppError::io(&accounts_dir, e))?;
+    }
+
+    prepare_layout(backend, &mut result).await?;
+
+    let key = load_or_create_sync_key(backend, &target.sync_passphrase).await?;
+    let tombstones = Tombstones::load(backend, &key).await?;
+    let remote_entries: Vec<RemoteEntry> = backend
+        .list(RemoteLayout::ACCOUNTS)
+        .await?
+        .into_iter()
+        .filter(|e| !e.is_dir && e.name.ends_with(".json"))
+        .collect();
+
+    let remote_id = format!("{}{}", backend.id(), RemoteLayout::ACCOUNTS);
+    let mut manifest = SyncManifest::load();
+    let base_files = manifest.files(&remote_id);
+    let mut next_files = HashMap::new();
+
+    let mut names: BTreeSet<String> = remote_entries.iter().map(|e| e.name.clone()).collect();
+    let mut new_files = HashMap::new();
+    if let Ok(entries) = fs::read_dir(&accounts_dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("json") {
+                if let Some(filename) = path.file_name().and_then(|s| s.to_str()) {
+                    names.insert(filename.to_string());
+                    if !base_files.contains_key(filename) {
+                        if let Ok(content) = vault::read_plaintext(&path) {
+                            new_files.insert(content_hash(&content), filename.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut ctx = AccountSync {
+        backend,
+        key,
+        accounts_dir,
+        mode,
+        tombstones,
+        trash_dir: tombstone::trash_dir(),
+        new_files,
+        result: &mut result,
+    };
+    for name in names {
+        let base = base_files.get(&name);
+        let remote = remote_entries.iter().find(|e| e.name == name);
+        // 本次没有完成同步的文件保留旧基准，下次继续比较
+        if let Some(state) = ctx.sync_file(&name, base, remote).await.or_else(|| base.cloned()) {
+            next_files.insert(name, state);
+        }
+    }
+
+    let tombstones = ctx.tombstones;
+    if mode.can_upload() {
+        if let Err(e) = tombstones.save(backend, target.trash_retention_days).await {
+            result.errors.push(e.to_string());
+        }
+    }
+
+    // 远程写入没有最终生效（如推送失败）时保留旧基准，下次重新比较
+    if let Err(e) = backend.finish(&mut result).await {
+        result.errors.push(e.to_string());
+        return Ok(result);
+    }
+    manifest.set_files(&remote_id, next_files);
+    manifest.save()?;
+    Ok(result)
+}
+
+impl AccountSync<'_> {
+    fn remote_path(name: &str) -> String {
+        format!("{}{}", RemoteLayout::ACCOUNTS, name)
+    }
+
+    /// 同步单个账号文件，返回新的基准状态
+    async fn sync_file(&mut self, name: &str, base: Option<&FileState>, remote: Option<&RemoteEntry>) -> Option<FileState> {
+        let local_path = self.accounts_dir.join(name);
+        let local = if local_path.exists() {
+            match vault::read_plaintext(&local_path) {
+                Ok(content) => Some(content),
+                Err(e) => {
+                    self.result.errors.push(format!("{}: {}", name, e));
+                    return None;
+                }
+            }
+        } else {
+            None
+        };
+
+        let local_changed = local.as_deref().map(content_hash) != base.map(|b| b.hash.clone());
+        // 后端不提供 ETag 时无法判断，按已修改处理（下载后再比较哈希）
+        let remote_changed = match (remote, base) {
+            (Some(remote), Some(base)) => remote.etag.is_none() || remote.etag != base.etag,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        match (local, remote) {
+            (None, None) => None,
+
+            // 仅本地存在
+            (Some(local), None) => {
+                // 其他设备已删除，且本地仍是删除前的版本：随之删除，而不是重新上传
+                let tombstone = self.tombstones.covering(&Self::remote_path(name)).map(|(_, t)| t.clone());
+                if let Some(tombstone) = tombstone {
+                    let unchanged = !local_changed && base.is_some();
+                    if unchanged || tombstone.hash.as_deref() == Some(content_hash(&local).as_str()) {
+                        if !self.mode.can_download() {
+                            return base.cloned();
+                        }
+                        return self.delete_local(name, &local_path, &tombstone);
+                    }
+                }
+                if !self.mode.can_upload() {
+                    return None;
+                }
+                self.upload(name, &local, PutCondition::IfAbsent).await
+            }
+
+            // 仅远程存在
+            (None, Some(_)) => {
+                // 本地已删除（或重命名）且远程没有再修改：删除远程
+                if let Some(base) = base.filter(|_| !remote_changed) {
+                    return self.delete_remote(name, base).await;
+                }
+                if base.is_none() && !self.mode.can_download() {
+                    return None;
+                }
+                let remote = self.fetch(name).await?;
+                // 后端不提供 ETag 时按内容判断远程是否修改过
+                if let Some(base) = base.filter(|base| base.hash == content_hash(&remote.plaintext)) {
+                    return self.delete_remote(name, base).await;
+                }
+                // 远程修改过的文件优先于本地删除
+                if !self.mode.can_download() {
+                    return None;
+                }
+                self.write_local(name, &local_path, remote).await
+            }
+
+            (Some(local), Some(entry)) => {
+                if !local_changed && !remote_changed {
+                    return base.cloned();
+                }
+                if local_changed && !remote_changed {
+                    if !self.mode.can_upload() {
+                        return None;
+                    }
+                    let condition = entry.etag.as_deref().map_or(PutCondition::Always, PutCondition::IfMatch);
+                    return self.upload(name, &local, condition).await;
+                }
+
+                let remote = self.fetch(name).await?;
+                if content_hash(&remote.plaintext) == content_hash(&local) {
+                    // 内容一致，只需更新基准（旧版明文顺便迁移）
+                    return self.migrate_legacy(name, &remote).await;
+                }
+                if !local_changed {
+                    if !self.mode.can_download() {
+                        return None;
+                    }
+                    return self.write_local(name, &local_path, remote).await;
+                }
+
+                // 两端都修改过，比较 Token 新旧
+                let local_auth = match serde_json::from_str::<CodexAuthFile>(&local) {
+                    Ok(auth) => auth,
+                    Err(e) => {
+                        self.result.errors.push(t!(FileLocalNotAuth, name, e));
+                        return None;
+                    }
+                };
+                match compare_freshness(&local_auth, &remote.auth) {
+                    Some(Ordering::Greater) => {
+                        if !self.mode.can_upload() {
+                            return None;
+                        }
+                        let condition = remote.etag.as_deref().map_or(PutCondition::Always, PutCondition::IfMatch);
+                        self.upload(name, &local, condition).await
+                    }
+                    Some(_) => {
+                        if !self.mode.can_download() {
+                            return None;
+                        }
+                        self.write_local(name, &local_path, remote).await
+                    }
+                    None => {
+                        self.result.conflicts.push(t!(
+                            FileBothModified,
+                            name,
+                            local_auth.last_refresh,
+                            remote.auth.last_refresh
+                        ));
+                        None
+                    }
+                }
+            }
+        }
+    }
+
+    /// 下载、解密并校验远程账号文件
+    async fn fetch(&mut self, name: &str) -> Option<RemoteAccount> {
+        let (content, etag) = match self.backend.get(&Self::remote_path(name)).await {
+            Ok(Some(file)) => (file.content, file.etag),
+            Ok(None) => {
+                self.result.errors.push(t!(FileRemoteGone, name));
+                return None;
+            }
+            Err(e) => {
+                self.result.errors.push(format!("{}: {}", name, e));
+                return None;
+            }
+        };
+
+        let (plaintext, legacy) = match Envelope::parse(&content) {
+            Some(envelope) => match envelope.open(&self.key).and_then(|data| {
+                String::from_utf8(data).map_err(|e| AppError::Corrupt(t!(DecryptedNotText, e)))
+            }) {
+                Ok(plaintext) => (plaintext, false),
+                Err(e) => {
+                    self.result.errors.push(format!("{}: {}", name, e));
+                    return None;
+                }
+            },
+            // 旧版本直接上传的明文
+            None => match String::from_utf8(content) {
+                Ok(plaintext) => (plaintext, true),
+                Err(_) => {
+                    self.result.errors.push(t!(FileNotAuth, name));
+                    return None;
+                }
+            },
+        };
+
+        match serde_json::from_str::<CodexAuthFile>(&plaintext) {
+            Ok(auth) => Some(RemoteAccount { plaintext, auth, etag, legacy }),
+            Err(e) => {
+                self.result.errors.push(t!(FileNotAuthDetail, name, e));
+                None
+            }
+        }
+    }
+
+    /// 加密后按条件上传，返回新 ETag；远程在此期间被改动时返回 `Ok(None)`
+    async fn put_sealed(&self, name: &str, plaintext: &str, condition: PutCondition<'_>) -> Result<Option<Option<String>>, AppError> {
+        let sealed = Envelope::seal(&self.key, plaintext.as_bytes()).and_then(|envelope| envelope.to_json())?;
+        match self.backend.put(&Self::remote_path(name), sealed.as_bytes(), condition).await? {
+            PutOutcome::Written(etag) => Ok(Some(etag)),
+            PutOutcome::Conflict => Ok(None),
+        }
+    }
+
+    /// 加密上传，远程在此期间被改动时记为冲突
+    async fn upload(&mut self, name: &str, plaintext: &str, condition: PutCondition<'_>) -> Option<FileState> {
+        match self.put_sealed(name, plaintext, condition).await {
+            Ok(Some(etag)) => {
+                self.tombstones.clear(&Self::remote_path(name));
+                self.result.uploaded.push(name.to_string());
+                Some(FileState::new(plaintext, etag))
+            }
+            Ok(None) => {
+                self.result.conflicts.push(t!(FileRemoteChanged, name));
+                None
+            }
+            Err(e) => {
+                self.result.errors.push(format!("{}: {}", name, e));
+                None
+            }
+        }
+    }
+
+    /// 把远程文件移入回收站并留下墓碑；只下载时保留基准，下次上传时再删除
+    async fn delete_remote(&mut self, name: &str, base: &FileState) -> Option<FileState> {
+        if !self.mode.can_upload() {
+            return Some(base.clone());
+        }
+        let path = Self::remote_path(name);
+        match tombstone::move_to_trash(self.backend, &path, &self.trash_dir).await {
+            Ok(trash) => {
+                let tombstone = Tombstone {
+                    deleted_at: chrono::Utc::now().to_rfc3339(),
+                    hash: Some(base.hash.clone()),
+                    renamed_to: self.new_files.get(&base.hash).map(|new_name| Self::remote_path(new_name)),
+                    trash,
+                };
+                self.result.deleted.push(tombstone.describe(name));
+                self.tombstones.add(&path, tombstone);
+                None
+            }
+            Err(e) => {
+                self.result.errors.push(t!(FileDeleteRemoteFailed, name, e));
+                Some(base.clone())
+            }
+        }
+    }
+
+    /// 其他设备删除或重命名了该账号，删除本地副本
+    fn delete_local(&mut self, name: &str, local_path: &Path, tombstone: &Tombstone) -> Option<FileState> {
+        match fs::remove_file(local_path) {
+            Ok(()) => self.result.deleted.push(tombstone.describe(name)),
+            Err(e) => self.result.errors.push(t!(FileDeleteFailed, name, e)),
+        }
+        None
+    }
+
+    /// 用远程内容覆盖本地
+    async fn write_local(&mut self, name: &str, local_path: &Path, remote: RemoteAccount) -> Option<FileState> {
+        if let Err(e) = vault::write_plaintext(local_path, &remote.plaintext) {
+            self.result.errors.push(format!("{}: {}", name, e));
+            return None;
+        }
+        self.result.downloaded.push(name.to_string());
+        self.migrate_legacy(name, &remote).await
+    }
+
+    /// 远程是旧版明文时重新加密上传，返回新的基准状态
+    async fn migrate_legacy(&mut self, name: &str, remote: &RemoteAccount) -> Option<FileState> {
+        if !remote.legacy {
+            return Some(FileState::new(&remote.plaintext, remote.etag.clone()));
+        }
+        let condition = remote.etag.as_deref().map_or(PutCondition::Always, PutCondition::IfMatch);
+        match self.put_sealed(name, &remote.plaintext, condition).await {
+            Ok(Some(etag)) => {
+                self.result.migrated.push(name.to_string());
+                Some(FileState::new(&remote.plaintext, etag))
+            }
+            Ok(None) => {
+                self.result.errors.push(t!(FileMigrateConflict, name));
+                Some(FileState::new(&remote.plaintext, remote.etag.clone()))
+            }
+            Err(e) => {
+                self.result.errors.push(t!(FileMigrateFailed, name, e));
+                Some(FileState::new(&remote.plaintext, remote.etag.clone()))
+            }
+        }
+    }
+}
+
+/// 读取远程的同步口令校验文件并解出密钥；远程尚无该文件时用当前口令创建
+async fn load_or_create_sync_key(backend: &dyn SyncBackend, passphrase: &str) -> Result<SecretKey, AppError> {
+    if passphrase.is_empty() {
+        return Err(AppError::InvalidInput(t!(SyncPassphraseRequired)));
+    }
+
+    let path = format!("{}{}", RemoteLayout::ACCOUNTS, SYNC_KEY_FILE);
+    match backend.get(&path).await? {
+        Some(file) => {
+            let meta: KeyCheck = serde_json::from_slice(&file.content)
+                .map_err(|e| t!(RemoteKeyFileCorrupt, e))?;
+            meta.unlock(passphrase).map_err(|_| AppError::WrongPassphrase)
+        }
+        None => {
+            let (meta, key) = KeyCheck::create(passphrase, SYNC_KEY_CHECK)?;
+            let content = serde_json::to_string_pretty(&meta).map_err(|e| t!(SerializeFailed, e))?;
+            match backend.put(&path, content.as_bytes(), PutCondition::IfAbsent).await? {
+                PutOutcome::Written(_) => Ok(key),
+                PutOutcome::Conflict => Err(AppError::Conflict(t!(SyncPassphraseRace))),
+            }
+        }
+    }
+}
+
+// ========== Codex 配置同步 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodexSyncConfig {
+    #[serde(rename = "syncPrompts")]
+    pub sync_prompts: bool,
+    #[serde(rename = "syncSkills")]
+    pub sync_skills: bool,
+    #[serde(rename = "syncAgentsMd")]
+    pub sync_agents_md: bool,
+    #[serde(rename = "syncConfigToml")]
+    pub sync_config_toml: bool,
+    /// 单个文件的大小上限（MB），超过的文件跳过并记入 `SyncResult.skipped`，0 表示不限制
+    #[serde(rename = "maxFileSizeMb", default = "default_max_file_size_mb")]
+    pub max_file_size_mb: u64,
+}
+
+fn default_max_file_size_mb() -> u64 {
+    20
+}
+
+impl Default for CodexSyncConfig {
+    fn default() -> Self {
+        Self {
+            sync_prompts: true,
+            sync_skills: true,
+            sync_agents_md: true,
+            sync_config_toml: false,
+            max_file_size_mb: default_max_file_size_mb(),
+        }
+    }
+}
+
+impl CodexSyncConfig {
+    /// 单个文件的字节上限，`None` 表示不限制
+    fn size_limit(&self) -> Option<u64> {
+        (self.max_file_size_mb > 0).then(|| self.max_file_size_mb * 1024 * 1024)
+    }
+}
+
+/// 文件大小超过上限时返回跳过原因
+fn oversize_reason(size: u64, limit: Option<u64>) -> Option<String> {
+    let limit = limit?;
+    (size > limit).then(|| t!(FileTooLarge, format_size(size), format_size(limit)))
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB {
+        format!("{:.1} MB", bytes as f64 / MB)
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+/// 上传 Codex 配置 (prompts, skills, AGENTS.MD)
+pub async fn sync_codex_upload(target: &SyncTarget, sync_config: &CodexSyncConfig) -> Result<SyncResult, AppError> {
+    let _guard = SYNC_LOCK.lock().await;
+    let backend = target.backend.open()?;
+    let backend = backend.as_ref();
+    let codex_dir = get_codex_dir();
+    let limit = sync_config.size_limit();
+
+    let mut result = SyncResult::default();
+    prepare_layout(backend, &mut result).await?;
+    let key = load_or_create_sync_key(backend, &target.sync_passphrase).await?;
+    let mut tombstones = Tombstones::load(backend, &key).await?;
+
+    // 本地删除的 prompts 和 skills 先移入远程回收站
+    let deleted = upload_deletions(backend, sync_config, &mut tombstones, &mut result).await;
+
+    // 同步 AGENTS.MD
+    if sync_config.sync_agents_md {
+        let agents_md = codex_dir.join(AGENTS_MD);
+        if agents_md.exists() {
+            let remote = format!("{}{}", RemoteLayout::CODEX, AGENTS_MD);
+            upload_file(backend, &agents_md, &remote, limit, &mut tombstones, &mut result).await;
+        }
+    }
+
+    // 同步 config.toml
+    if sync_config.sync_config_toml {
+        let config_toml = codex_dir.join(CONFIG_TOML);
+        if config_toml.exists() {
+            let remote = format!("{}{}", RemoteLayout::CODEX, CONFIG_TOML);
+            upload_file(backend, &config_toml, &remote, limit, &mut tombstones, &mut result).await;
+        }
+    }
+
+    // 同步 prompts
+    if sync_config.sync_prompts {
+        let prompts_dir = get_prompts_dir();
+        if prompts_dir.exists() {
+            upload_dir_recursive(backend, RemoteLayout::PROMPTS, &prompts_dir, limit, &mut tombstones, &mut result).await;
+        }
+    }
+
+    // 同步 skills
+    if sync_config.sync_skills {
+        let skills_dir = get_skills_dir();
+        if skills_dir.exists() {
+            // 遍历 skills 目录
+            if let Ok(entries) = fs::read_dir(&skills_dir) {
+                for entry in entries.flatten() {
+                    let path = entry.path();
+                    if !path.is_dir() {
+                        continue;
+                    }
+                    let dir_name = path.file_name()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or("");
+                    // 跳过 .system 和 dist
+                    if dir_name.starts_with('.') || dir_name == "dist" {
+                        continue;
+                    }
+
+                    // 其他设备已删除、本地也没有再修改的 skill 不再上传
+                    let skill_remote = format!("{}{}/", RemoteLayout::SKILLS, dir_name);
+                    if tombstones.covering(&skill_remote).is_some_and(|(_, t)| t.matches_local(&path)) {
+                        continue;
+                    }
+
+                    // 为每个 skill 创建远程目录并上传
+                    let _ = backend.mkdir(&skill_remote).await;
+                    upload_dir_recursive(backend, &skill_remote, &path, limit, &mut tombstones, &mut result).await;
+                }
+            }
+        }
+    }
+
+    // 墓碑写入远程清单后才移除本地删除记录，写入失败时下次同步再写
+    match tombstones.save(backend, target.trash_retention_days).await {
+        Ok(()) => {
+            if let Err(e) = tombstone::complete_pending_deletions(deleted).await {
+                result.errors.push(e.to_string());
+            }
+        }
+        Err(e) => result.errors.push(e.to_string()),
+    }
+    if let Err(e) = backend.finish(&mut result).await {
+        result.errors.push(e.to_string());
+    }
+    Ok(result)
+}
+
+/// 远程路径是否属于本次同步的内容
+fn in_scope(path: &str, sync_config: &CodexSyncConfig) -> bool {
+    (sync_config.sync_prompts && path.starts_with(RemoteLayout::PROMPTS))
+        || (sync_config.sync_skills && path.starts_with(RemoteLayout::SKILLS))
+}
+
+/// 把本地删除记录同步到远程：远程文件移入回收站并写下墓碑，返回已处理的记录
+///
+/// 记录在墓碑写回远程后才能移除（见 `tombstone::complete_pending_deletions`）。
+async fn upload_deletions(backend: &dyn SyncBackend, sync_config: &CodexSyncConfig, tombstones: &mut Tombstones, result: &mut SyncResult) -> Vec<PendingDeletion> {
+    let pending = tombstone::load_pending_deletions();
+    let trash_dir = tombstone::trash_dir();
+    let mut done = Vec::new();
+    for deletion in pending.into_iter().filter(|deletion| in_scope(&deletion.path, sync_config)) {
+        match tombstone::move_to_trash(backend, &deletion.path, &trash_dir).await {
+            Ok(trash) => {
+                result.deleted.push(deletion.path.clone());
+                tombstones.add(&deletion.path, deletion.tombstone(trash));
+                done.push(deletion);
+            }
+            Err(e) => result.errors.push(t!(FileDeleteRemoteFailed, deletion.path, e)),
+        }
+    }
+    done
+}
+
+/// 按原始字节上传单个文件，超过大小限制的记为跳过
+///
+/// 其他设备已删除、本地仍是删除前版本的文件不上传；上传成功后移除该路径的墓碑。
+async fn upload_file(backend: &dyn SyncBackend, path: &Path, remote: &str, limit: Option<u64>, tombstones: &mut Tombstones, result: &mut SyncResult) {
+    if tombstones.covering(remote).is_some_and(|(_, t)| t.matches_local(path)) {
+        return;
+    }
+    let size = match fs::metadata(path) {
+        Ok(meta) => meta.len(),
+        Err(e) => {
+            result.errors.push(t!(FileReadFailed, remote, e));
+            return;
+        }
+    };
+    if let Some(reason) = oversize_reason(size, limit) {
+        result.skipped.push(format!("{}: {}", remote, reason));
+        return;
+    }
+    match fs::read(path) {
+        Ok(content) => match backend.put(remote, &content, PutCondition::Always).await {
+            Ok(_) => {
+                tombstones.clear(remote);
+                result.uploaded.push(remote.to_string());
+            }
+            Err(e) => result.errors.push(format!("{}: {}", remote, e)),
+        },
+        Err(e) => result.errors.push(t!(FileReadFailed, remote, e)),
+    }
+}
+
+/// 递归上传目录
+async fn upload_dir_recursive(backend: &dyn SyncBackend, remote_dir: &str, dir: &Path, limit: Option<u64>, tombstones: &mut Tombstones, result: &mut SyncResult) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = path.file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or("");
+
+            // 跳过 __pycache__ 等
+            if name.starts_with("__") || name.starts_with('.') {
+                continue;
+            }
+
+            if path.is_dir() {
+                // 创建子目录并递归
+                let sub_remote = format!("{}{}/", remote_dir, name);
+                let _ = backend.mkdir(&sub_remote).await;
+                Box::pin(upload_dir_recursive(backend, &sub_remote, &path, limit, tombstones, result)).await;
+            } else {
+                let remote = format!("{}{}", remote_dir, name);
+                upload_file(backend, &path, &remote, limit, tombstones, result).await;
+            }
+        }
+    }
+}
+
+/// 下载 Codex 配置
+pub async fn sync_codex_download(target: &SyncTarget, sync_config: &CodexSyncConfig) -> Result<SyncResult, AppError> {
+    let _guard = SYNC_LOCK.lock().await;
+    let backend = target.backend.open()?;
+    let backend = backend.as_ref();
+    let codex_dir = get_codex_dir();
+    let limit = sync_config.size_limit();
+
+    let mut result = SyncResult::default();
+    prepare_layout(backend, &mut result).await?;
+
+    // 其他设备删除的 prompts 和 skills，本地没有再修改的随之删除
+    let key = load_or_create_sync_key(backend, &target.sync_passphrase).await?;
+    let tombstones = Tombstones::load(backend, &key).await?;
+    apply_deletions(&tombstones, &codex_dir, sync_config, &mut result);
+
+    // 下载 AGENTS.MD
+    if sync_config.sync_agents_md {
+        let remote = format!("{}{}", RemoteLayout::CODEX, AGENTS_MD);
+        download_file(backend, &remote, &codex_dir.join(AGENTS_MD), limit, &mut result).await;
+    }
+
+    // 下载 prompts
+    if sync_config.sync_prompts {
+        let prompts_dir = get_prompts_dir();
+        if !prompts_dir.exists() {
+            let _ = fs::create_dir_all(&prompts_dir);
+        }
+        download_dir_recursive(backend, RemoteLayout::PROMPTS, &prompts_dir, limit, &mut result).await;
+    }
+
+    // 下载 skills
+    if sync_config.sync_skills {
+        let skills_dir = get_skills_dir();
+        if !skills_dir.exists() {
+            let _ = fs::create_dir_all(&skills_dir);
+        }
+        download_dir_recursive(backend, RemoteLayout::SKILLS, &skills_dir, limit, &mut result).await;
+    }
+
+    // 下载 config.toml
+    if sync_config.sync_config_toml {
+        let remote = format!("{}{}", RemoteLayout::CODEX, CONFIG_TOML);
+        download_file(backend, &remote, &codex_dir.join(CONFIG_TOML), limit, &mut result).await;
+    }
+
+    if let Err(e) = backend.finish(&mut result).await {
+        result.errors.push(e.to_string());
+    }
+    Ok(result)
+}
+
+/// 删除本地仍是删除前版本的 prompts 和 skills
+fn apply_deletions(tombstones: &Tombstones, codex_dir: &Path, sync_config: &CodexSyncConfig, result: &mut SyncResult) {
+    for (path, tombstone) in tombstones.under(RemoteLayout::CODEX) {
+        if !in_scope(path, sync_config) {
+            continue;
+        }
+        let Some(local) = tombstone::local_codex_path(path, codex_dir) else {
+            tracing::warn!(%path, "忽略指向 prompts / skills 目录之外的删除记录");
+            result.errors.push(t!(InvalidPath, path));
+            continue;
+        };
+        if !local.exists() || !tombstone.matches_local(&local) {
+            continue;
+        }
+        let removed = if local.is_dir() {
+            fs::remove_dir_all(&local)
+        } else {
+            fs::remove_file(&local)
+        };
+        match removed {
+            Ok(()) => result.deleted.push(path.to_string()),
+            Err(e) => result.errors.push(t!(FileDeleteFailed, path, e)),
+        }
+    }
+}
+
+/// 按原始字节下载单个文件，远程不存在时忽略，超过大小限制的记为跳过
+async fn download_file(backend: &dyn SyncBackend, remote: &str, local_path: &Path, limit: Option<u64>, result: &mut SyncResult) {
+    match backend.get(remote).await {
+        Ok(Some(file)) => {
+            // 列目录时没有返回大小的后端，下载后再检查一次
+            if let Some(reason) = oversize_reason(file.content.len() as u64, limit) {
+                result.skipped.push(format!("{}: {}", remote, reason));
+                return;
+            }
+            match atomic::write_file(local_path, &file.content) {
+                Ok(_) => result.downloaded.push(remote.to_string()),
+                Err(e) => result.errors.push(t!(FileWriteFailed, remote, e)),
+            }
+        }
+        Ok(None) => {}
+        Err(e) => result.errors.push(format!("{}: {}", remote, e)),
+    }
+}
+
+/// 递归下载目录
+async fn download_dir_recursive(backend: &dyn SyncBackend, remote_dir: &str, local_dir: &Path, limit: Option<u64>, result: &mut SyncResult) {
+    // 列出远程文件
+    let items = match backend.list(remote_dir).await {
+        Ok(items) => items,
+        Err(e) => {
+            result.errors.push(t!(ListDirFailed, e));
+            return;
+        }
+    };
+    for item in items {
+        // 跳过 .DS_Store 等隐藏文件
+        if item.name.starts_with('.') {
+            continue;
+        }
+        if !tombstone::is_plain_name(&item.name) {
+            result.errors.push(t!(InvalidPath, format!("{}{}", remote_dir, item.name)));
+            continue;
+        }
+        if item.is_dir {
+            // 是目录，递归下载
+            let sub_remote = format!("{}{}/", remote_dir, item.name);
+            let sub_local = local_dir.join(&item.name);
+            if !sub_local.exists() {
+                let _ = fs::create_dir_all(&sub_local);
+            }
+            Box::pin(download_dir_recursive(backend, &sub_remote, &sub_local, limit, result)).await;
+        } else {
+            let remote = format!("{}{}", remote_dir, item.name);
+            // 列表已给出大小时，超限的文件不必下载
+            if let Some(reason) = item.size.and_then(|size| oversize_reason(size, limit)) {
+                result.skipped.push(format!("{}: {}", remote, reason));
+                continue;
+            }
+            download_file(backend, &remote, &local_dir.join(&item.name), limit, result).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::folder::{FolderBackend, FolderConfig};
+    use crate::manager::config::get_sync_manifest_file;
+    use crate::manager::test_support;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    fn target(remote: &Path) -> SyncTarget {
+        SyncTarget {
+            backend: BackendConfig::Folder(FolderConfig { path: remote.to_string_lossy().into_owned() }),
+            sync_passphrase: PASSPHRASE.to_string(),
+            trash_retention_days: 0,
+        }
+    }
+
+    fn auth_json(account_id: &str, refresh_token: &str, last_refresh: &str) -> String {
+        serde_json::json!({
+            "OPENAI_API_KEY": null,
+            "last_refresh": last_refresh,
+            "tokens": {
+                "access_token": "access",
+                "account_id": account_id,
+                "id_token": "id",
+                "refresh_token": refresh_token,
+            }
+        })
+        .to_string()
+    }
+
+    fn codex_sync_config() -> CodexSyncConfig {
+        CodexSyncConfig {
+            sync_prompts: true,
+            sync_skills: true,
+            sync_agents_md: false,
+            sync_config_toml: false,
+            max_file_size_mb: 0,
+        }
+    }
+
+    fn tombstone(hash: Option<&str>, deleted_at: chrono::DateTime<chrono::Utc>) -> Tombstone {
+        Tombstone {
+            deleted_at: deleted_at.to_rfc3339(),
+            hash: hash.map(str::to_string),
+            renamed_to: None,
+            trash: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn applies_tombstones_inside_codex_dirs_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let codex = dir.path().join(".codex");
+        fs::create_dir_all(codex.join("prompts")).unwrap();
+        fs::create_dir_all(codex.join("skills/pdf")).unwrap();
+        fs::create_dir_all(dir.path().join("victim")).unwrap();
+        fs::write(codex.join("prompts/review.md"), "review").unwrap();
+        fs::write(codex.join("prompts/edited.md"), "edited after deletion").unwrap();
+        fs::write(codex.join("skills/pdf/SKILL.md"), "pdf").unwrap();
+        fs::write(dir.path().join("victim/data"), "keep me").unwrap();
+
+        let backend = FolderBackend::at(dir.path().join("remote"));
+        let mut tombstones = Tombstones::load(&backend, &SecretKey::generate()).await.unwrap();
+        let later = chrono::Utc::now() + chrono::Duration::hours(1);
+        tombstones.add("codex/prompts/review.md", tombstone(Some(&content_hash("review")), later));
+        tombstones.add("codex/prompts/edited.md", tombstone(Some(&content_hash("original")), later));
+        tombstones.add("codex/skills/pdf/", tombstone(None, later));
+        tombstones.add("codex/skills/../../victim/", tombstone(None, later));
+        tombstones.add("codex/prompts/../../victim/data", tombstone(Some(&content_hash("keep me")), later));
+
+        let mut result = SyncResult::default();
+        apply_deletions(&tombstones, &codex, &codex_sync_config(), &mut result);
+
+        assert!(!codex.join("prompts/review.md").exists());
+        assert!(!codex.join("skills/pdf").exists());
+        // 删除后又修改过的文件保留
+        assert!(codex.join("prompts/edited.md").exists());
+        // 越出 prompts / skills 目录的记录被拒绝
+        assert_eq!(fs::read_to_string(dir.path().join("victim/data")).unwrap(), "keep me");
+        assert_eq!(result.deleted, ["codex/prompts/review.md", "codex/skills/pdf/"]);
+        assert_eq!(result.errors.len(), 2, "{:?}", result.errors);
+    }
+
+    /// 一个账号文件的三方同步场景：上次同步的基准、本地和远程的当前内容，以及期望的结果
+    struct Case {
+        name: &'static str,
+        base: Option<String>,
+        local: Option<String>,
+        remote: Option<String>,
+        uploaded: bool,
+        downloaded: bool,
+        conflict: bool,
+        /// 同步后两端的内容
+        expect_local: Option<String>,
+        expect_remote: Option<String>,
+    }
+
+    /// 以同步密钥加密后直接写入远程目录，模拟其他设备的上传
+    fn put_remote(remote: &Path, key: &SecretKey, content: &str) {
+        let sealed = Envelope::seal(key, content.as_bytes()).unwrap().to_json().unwrap();
+        fs::write(remote.join("accounts/a.json"), sealed).unwrap();
+    }
+
+    fn read_remote(remote: &Path, key: &SecretKey) -> Option<String> {
+        let content = fs::read(remote.join("accounts/a.json")).ok()?;
+        Some(String::from_utf8(Envelope::parse(&content).unwrap().open(key).unwrap()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn three_way_merge_of_account_files() {
+        let v1 = auth_json("acct", "rt-1", "2026-01-01T00:00:00Z");
+        let v2 = auth_json("acct", "rt-2", "2026-01-02T00:00:00Z");
+        let v3 = auth_json("acct", "rt-3", "2026-01-03T00:00:00Z");
+        let tie_a = auth_json("acct", "rt-a", "2026-01-02T00:00:00Z");
+        let tie_b = auth_json("acct", "rt-b", "2026-01-02T00:00:00Z");
+        let some = |s: &String| Some(s.clone());
+        let cases = [
+            Case { name: "新文件上传", base: None, local: some(&v1), remote: None, uploaded: true, downloaded: false, conflict: false, expect_local: some(&v1), expect_remote: some(&v1) },
+            Case { name: "远程新文件下载", base: None, local: None, remote: some(&v1), uploaded: false, downloaded: true, conflict: false, expect_local: some(&v1), expect_remote: some(&v1) },
+            Case { name: "仅本地修改", base: some(&v1), local: some(&v2), remote: some(&v1), uploaded: true, downloaded: false, conflict: false, expect_local: some(&v2), expect_remote: some(&v2) },
+            Case { name: "仅远程修改", base: some(&v1), local: some(&v1), remote: some(&v2), uploaded: false, downloaded: true, conflict: false, expect_local: some(&v2), expect_remote: some(&v2) },
+            Case { name: "都修改，本地较新", base: some(&v1), local: some(&v3), remote: some(&v2), uploaded: true, downloaded: false, conflict: false, expect_local: some(&v3), expect_remote: some(&v3) },
+            Case { name: "都修改，远程较新", base: some(&v1), local: some(&v2), remote: some(&v3), uploaded: false, downloaded: true, conflict: false, expect_local: some(&v3), expect_remote: some(&v3) },
+            Case { name: "无基准，远程较新", base: None, local: some(&v2), remote: some(&v3), uploaded: false, downloaded: true, conflict: false, expect_local: some(&v3), expect_remote: some(&v3) },
+            Case { name: "都修改，无法判断", base: some(&v1), local: some(&tie_a), remote: some(&tie_b), uploaded: false, downloaded: false, conflict: true, expect_local: some(&tie_a), expect_remote: some(&tie_b) },
+            Case { name: "本地删除", base: some(&v1), local: None, remote: some(&v1), uploaded: false, downloaded: false, conflict: false, expect_local: None, expect_remote: None },
+        ];
+
+        for case in cases {
+            let home = test_support::home();
+            let remote = home.path.join("remote");
+            fs::create_dir_all(remote.join("accounts")).unwrap();
+            let local_path = home.path.join(".myswitch/accounts/a.json");
+            let target = target(&remote);
+            let key = load_or_create_sync_key(&FolderBackend::at(remote.clone()), PASSPHRASE).await.unwrap();
+
+            if let Some(base) = &case.base {
+                fs::create_dir_all(local_path.parent().unwrap()).unwrap();
+                fs::write(&local_path, base).unwrap();
+                sync_accounts(&target, SyncMode::Both).await.unwrap();
+            }
+            match &case.local {
+                Some(local) => {
+                    fs::create_dir_all(local_path.parent().unwrap()).unwrap();
+                    fs::write(&local_path, local).unwrap();
+                }
+                None => {
+                    let _ = fs::remove_file(&local_path);
+                }
+            }
+            if let Some(content) = case.remote.as_ref().filter(|content| case.base.as_ref() != Some(*content)) {
+                put_remote(&remote, &key, content);
+            }
+
+            let result = sync_accounts(&target, SyncMode::Both).await.unwrap();
+            assert!(result.errors.is_empty(), "{}: {:?}", case.name, result.errors);
+            assert_eq!(!result.uploaded.is_empty(), case.uploaded, "{}: {:?}", case.name, result);
+            assert_eq!(!result.downloaded.is_empty(), case.downloaded, "{}: {:?}", case.name, result);
+            assert_eq!(!result.conflicts.is_empty(), case.conflict, "{}: {:?}", case.name, result);
+            assert_eq!(fs::read_to_string(&local_path).ok(), case.expect_local, "{}", case.name);
+            assert_eq!(read_remote(&remote, &key), case.expect_remote, "{}", case.name);
+        }
+    }
+
+    #[tokio::test]
+    async fn accounts_are_encrypted_end_to_end() {
+        let home = test_support::home();
+        let remote = home.path.join("remote");
+        fs::create_dir_all(&remote).unwrap();
+        let accounts = home.path.join(".myswitch/accounts");
+        fs::create_dir_all(&accounts).unwrap();
+        let content = auth_json("acct", "rt-secret", "2026-01-01T00:00:00Z");
+        fs::write(accounts.join("a.json"), &content).unwrap();
+
+        sync_accounts(&target(&remote), SyncMode::Both).await.unwrap();
+        let uploaded = fs::read(remote.join("accounts/a.json")).unwrap();
+        assert!(Envelope::parse(&uploaded).is_some());
+        assert!(!String::from_utf8_lossy(&uploaded).contains("rt-secret"));
+
+        // 另一台设备用错误的同步口令：直接失败，不下载也不上传
+        fs::remove_file(accounts.join("a.json")).unwrap();
+        fs::remove_file(get_sync_manifest_file()).unwrap();
+        let mut wrong = target(&remote);
+        wrong.sync_passphrase = "wrong passphrase".to_string();
+        assert!(matches!(sync_accounts(&wrong, SyncMode::Both).await, Err(AppError::WrongPassphrase)));
+        assert!(!accounts.join("a.json").exists());
+
+        let result = sync_accounts(&target(&remote), SyncMode::Both).await.unwrap();
+        assert_eq!(result.downloaded, ["a.json"]);
+        assert_eq!(fs::read_to_string(accounts.join("a.json")).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn detects_renames_and_replays_them_on_other_devices() {
+        let home = test_support::home();
+        let remote = home.path.join("remote");
+        fs::create_dir_all(&remote).unwrap();
+        let accounts = home.path.join(".myswitch/accounts");
+        let target = target(&remote);
+        let content = auth_json("acct", "rt-1", "2026-01-01T00:00:00Z");
+
+        fs::create_dir_all(&accounts).unwrap();
+        fs::write(accounts.join("a.json"), &content).unwrap();
+        let result = sync_accounts(&target, SyncMode::Both).await.unwrap();
+        assert_eq!(result.uploaded, ["a.json"]);
+
+        // 本机重命名：远程旧文件移入回收站，墓碑记下新名称
+        fs::rename(accounts.join("a.json"), accounts.join("b.json")).unwrap();
+        let result = sync_accounts(&target, SyncMode::Both).await.unwrap();
+        assert_eq!(result.uploaded, ["b.json"]);
+        assert_eq!(result.deleted, ["a.json → b.json"]);
+        assert!(!remote.join("accounts/a.json").exists());
+
+        // 另一台设备仍是重命名前的状态：删除 a.json，下载 b.json
+        fs::remove_file(accounts.join("b.json")).unwrap();
+        fs::write(accounts.join("a.json"), &content).unwrap();
+        fs::remove_file(get_sync_manifest_file()).unwrap();
+        let result = sync_accounts(&target, SyncMode::Both).await.unwrap();
+        assert_eq!(result.deleted, ["a.json → b.json"]);
+        assert_eq!(result.downloaded, ["b.json"]);
+        assert!(!accounts.join("a.json").exists());
+        assert_eq!(fs::read_to_string(accounts.join("b.json")).unwrap(), content);
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+    }
+
+    #[tokio::test]
+    async fn clears_local_deletions_once_tombstones_are_saved() {
+        let home = test_support::home();
+        let remote = home.path.join("remote");
+        fs::create_dir_all(&remote).unwrap();
+        let prompts = get_prompts_dir();
+        fs::create_dir_all(&prompts).unwrap();
+        fs::write(prompts.join("review.md"), "review").unwrap();
+        let target = target(&remote);
+        sync_codex_upload(&target, &codex_sync_config()).await.unwrap();
+        assert!(remote.join("codex/prompts/review.md").exists());
+
+        tombstone::delete_codex_path(&prompts.join("review.md")).unwrap();
+        let result = sync_codex_upload(&target, &codex_sync_config()).await.unwrap();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        assert_eq!(result.deleted, ["codex/prompts/review.md"]);
+        assert!(!remote.join("codex/prompts/review.md").exists());
+        assert!(tombstone::load_pending_deletions().is_empty());
+
+        let key = load_or_create_sync_key(&FolderBackend::at(remote.clone()), PASSPHRASE).await.unwrap();
+        let tombstones = Tombstones::load(&FolderBackend::at(remote), &key).await.unwrap();
+        assert!(tombstones.covering("codex/prompts/review.md").is_some());
+    }
+}
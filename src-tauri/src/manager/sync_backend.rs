@@ -0,0 +1,128 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::error::AppError;
+use super::folder::{FolderBackend, FolderConfig};
+use super::git_repo::{GitBackend, GitRepoConfig};
+use super::s3::{S3Backend, S3Config};
+use super::sync::SyncResult;
+use super::webdav::{WebDavBackend, WebDavConfig};
+
+// ========== 同步后端 ==========
+//
+// 账号和 Codex 配置的同步逻辑只通过 SyncBackend 读写远程，
+// 路径均相对于后端的根目录，用 `/` 分隔，目录以 `/` 结尾（根目录为空串）。
+
+/// 上传前的条件，用于避免覆盖其他设备刚写入的文件
+#[derive(Debug, Clone, Copy)]
+pub enum PutCondition<'a> {
+    /// 无条件覆盖
+    Always,
+    /// 仅当远程 ETag 未变化时写入
+    IfMatch(&'a str),
+    /// 仅当远程文件不存在时写入
+    IfAbsent,
+}
+
+/// 条件上传的结果
+#[derive(Debug, Clone)]
+pub enum PutOutcome {
+    /// 已写入，附带新的 ETag（后端不提供时为空）
+    Written(Option<String>),
+    /// 条件不满足，远程已被其他设备修改
+    Conflict,
+}
+
+/// 远程目录中的一个直接子项
+#[derive(Debug, Clone)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub etag: Option<String>,
+}
+
+/// 下载到的远程文件
+#[derive(Debug, Clone)]
+pub struct RemoteFile {
+    pub content: Vec<u8>,
+    pub etag: Option<String>,
+}
+
+#[async_trait]
+pub trait SyncBackend: Send + Sync {
+    /// 远程位置的唯一标识，用作同步清单的键
+    fn id(&self) -> String;
+
+    /// 同步开始前调用（拉取仓库、迁移旧目录结构等），迁移的条目记入 `result`
+    async fn prepare(&self, _result: &mut SyncResult) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// 同步结束后调用（提交并推送等），失败时本次同步不更新同步清单
+    async fn finish(&self, _result: &mut SyncResult) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    /// 列出目录的直接子项，目录不存在时返回空列表
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>, AppError>;
+
+    /// 下载文件，不存在时返回 `None`
+    async fn get(&self, path: &str) -> Result<Option<RemoteFile>, AppError>;
+
+    /// 上传文件
+    async fn put(&self, path: &str, content: &[u8], condition: PutCondition<'_>) -> Result<PutOutcome, AppError>;
+
+    /// 删除文件或目录，不存在时视为成功
+    async fn delete(&self, path: &str) -> Result<(), AppError>;
+
+    /// 创建目录（父目录需已存在），已存在时视为成功
+    async fn mkdir(&self, dir: &str) -> Result<(), AppError>;
+}
+
+/// 按扩展名推断 Content-Type，文本类型附带 UTF-8 编码
+pub fn content_type_for(path: &str) -> String {
+    let mime = mime_guess::from_path(path).first_or_octet_stream();
+    if mime.type_() == mime_guess::mime::TEXT || mime.subtype() == mime_guess::mime::JSON {
+        format!("{}; charset=utf-8", mime.essence_str())
+    } else {
+        mime.essence_str().to_string()
+    }
+}
+
+/// 同步后端的连接参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum BackendConfig {
+    Webdav(WebDavConfig),
+    S3(S3Config),
+    Folder(FolderConfig),
+    Git(GitRepoConfig),
+}
+
+/// 一次同步的目标：后端连接参数加同步口令
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncTarget {
+    pub backend: BackendConfig,
+    /// 同步口令，账号文件上传前用它派生的密钥加密，各设备需一致
+    #[serde(rename = "syncPassphrase", default)]
+    pub sync_passphrase: String,
+    /// 远程回收站保留已删除文件的天数，0 表示一直保留
+    #[serde(rename = "trashRetentionDays", default = "default_trash_retention_days")]
+    pub trash_retention_days: u32,
+}
+
+fn default_trash_retention_days() -> u32 {
+    30
+}
+
+impl BackendConfig {
+    pub fn open(&self) -> Result<Box<dyn SyncBackend>, AppError> {
+        Ok(match self {
+            BackendConfig::Webdav(config) => Box::new(WebDavBackend::new(config)?),
+            BackendConfig::S3(config) => Box::new(S3Backend::new(config)?),
+            BackendConfig::Folder(config) => Box::new(FolderBackend::new(config)),
+            BackendConfig::Git(config) => Box::new(GitBackend::new(config)),
+        })
+    }
+}
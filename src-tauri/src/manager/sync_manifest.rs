@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs;
+
+use super::atomic;
+use super::auth::CodexAuthFile;
+use super::config::get_sync_manifest_file;
+use super::error::AppError;
+
+use crate::t;
+
+// ========== 同步清单（三方合并的基准） ==========
+//
+// 每个远程目录单独记录上次同步成功时各文件的状态：
+// 明文哈希用于判断本地是否修改，ETag 用于判断远程是否修改，
+// last_refresh 用于两端都修改时挑选较新的 Token。
+
+/// 单个文件上次同步时的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileState {
+    /// 明文内容的 SHA-256
+    pub hash: String,
+    /// 远程文件的 ETag，服务器不提供时为空
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(rename = "lastRefresh", default)]
+    pub last_refresh: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncManifest {
+    /// 远程目录 URL -> 文件名 -> 状态
+    #[serde(default)]
+    pub remotes: HashMap<String, HashMap<String, FileState>>,
+}
+
+impl SyncManifest {
+    /// 读取清单，不存在或损坏时视为从未同步
+    pub fn load() -> Self {
+        let Ok(content) = fs::read_to_string(get_sync_manifest_file()) else {
+            return Self::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "同步清单已损坏，按从未同步处理");
+            Self::default()
+        })
+    }
+
+    /// 原子写入清单，写到一半中断时保留上一份完整的清单
+    pub fn save(&self) -> Result<(), AppError> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| t!(SerializeFailed, e))?;
+        atomic::write_file(&get_sync_manifest_file(), content.as_bytes())
+    }
+
+    pub fn files(&self, remote: &str) -> HashMap<String, FileState> {
+        self.remotes.get(remote).cloned().unwrap_or_default()
+    }
+
+    pub fn set_files(&mut self, remote: &str, files: HashMap<String, FileState>) {
+        self.remotes.insert(remote.to_string(), files);
+    }
+}
+
+impl FileState {
+    pub fn new(plaintext: &str, etag: Option<String>) -> Self {
+        Self {
+            hash: content_hash(plaintext),
+            etag,
+            last_refresh: serde_json::from_str::<CodexAuthFile>(plaintext)
+                .ok()
+                .map(|auth| auth.last_refresh),
+        }
+    }
+}
+
+/// 内容的 SHA-256（十六进制）
+pub fn content_hash(content: impl AsRef<[u8]>) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// 比较两份认证文件哪份 Token 更新
+///
+/// 返回 `None` 表示无法判断（不同账号，或刷新时间相同/无法解析），属于真正的冲突。
+pub fn compare_freshness(local: &CodexAuthFile, remote: &CodexAuthFile) -> Option<Ordering> {
+    if local.tokens.account_id != remote.tokens.account_id {
+        return None;
+    }
+    let parse = |s: &str| chrono::DateTime::parse_from_rfc3339(s).ok();
+    match (parse(&local.last_refresh), parse(&remote.last_refresh)) {
+        (Some(l), Some(r)) if l != r => Some(l.cmp(&r)),
+        _ => None,
+    }
+}
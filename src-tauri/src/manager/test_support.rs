@@ -0,0 +1,129 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{LazyLock, Mutex, MutexGuard};
+
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use base64::Engine;
+
+use super::auth::CodexAuthFile;
+use super::vault;
+
+// ========== 测试辅助 ==========
+//
+// 各路径都取自用户目录，测试进程启动后把 HOME 指向一个临时目录。
+// 读写用户目录的测试通过 `home()` 串行执行，每次拿到的都是清空后的目录。
+
+static HOME: LazyLock<tempfile::TempDir> = LazyLock::new(|| {
+    let dir = tempfile::tempdir().expect("创建临时目录失败");
+    std::env::set_var("HOME", dir.path());
+    dir
+});
+
+static HOME_LOCK: Mutex<()> = Mutex::new(());
+
+/// 独占测试用的用户目录，持有返回值期间其他测试不会读写它
+pub struct TestHome {
+    pub path: PathBuf,
+    _guard: MutexGuard<'static, ()>,
+}
+
+pub fn home() -> TestHome {
+    // 前一个测试断言失败时锁会中毒，目录随后会被清空，可以继续使用
+    let guard = HOME_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let path = HOME.path().to_path_buf();
+    for entry in fs::read_dir(&path).expect("读取临时目录失败").flatten() {
+        let _ = fs::remove_dir_all(entry.path()).or_else(|_| fs::remove_file(entry.path()));
+    }
+    vault::lock();
+    TestHome { path, _guard: guard }
+}
+
+// ========== 认证文件 ==========
+
+/// 不带签名的 JWT，载荷为给定的声明
+pub fn id_token(claims: serde_json::Value) -> String {
+    format!("eyJhbGciOiJub25lIn0.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+}
+
+/// id_token 只含邮箱的认证文件，access_token 由 refresh_token 派生
+pub fn auth(account_id: &str, email: &str, refresh_token: &str, last_refresh: &str) -> CodexAuthFile {
+    serde_json::from_value(serde_json::json!({
+        "OPENAI_API_KEY": null,
+        "last_refresh": last_refresh,
+        "tokens": {
+            "access_token": format!("access-{}", refresh_token),
+            "account_id": account_id,
+            "id_token": id_token(serde_json::json!({ "email": email })),
+            "refresh_token": refresh_token,
+        }
+    }))
+    .unwrap()
+}
+
+/// 以明文写入认证文件，按需创建目录
+pub fn write_auth(path: &Path, auth: &CodexAuthFile) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, serde_json::to_string_pretty(auth).unwrap()).unwrap();
+}
+
+/// 修改 base64 字段（密文、nonce 等）解码后的第一个字节，模拟篡改
+pub fn flip_first_byte(encoded: &str) -> String {
+    let mut bytes = BASE64.decode(encoded).unwrap();
+    bytes[0] ^= 1;
+    BASE64.encode(bytes)
+}
+
+// ========== 本地模拟 HTTP 服务器 ==========
+
+/// 模拟服务器收到的请求
+pub struct FakeRequest {
+    /// 请求行和请求头
+    pub head: String,
+}
+
+/// 在本地端口上模拟 HTTP 服务器，依次处理 `requests` 个请求
+///
+/// 每个请求读完后交给 `respond` 取得状态行（如 `200 OK`）和 JSON 内容，回复后通过通道传回请求。
+/// 返回 `http://127.0.0.1:<端口>` 和接收请求的通道。
+pub fn fake_server<F>(requests: usize, mut respond: F) -> (String, mpsc::Receiver<FakeRequest>)
+where
+    F: FnMut(&FakeRequest) -> (&'static str, String) + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().take(requests) {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut head = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                    break;
+                }
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                head.push_str(&line);
+            }
+            reader.read_exact(&mut vec![0; length]).unwrap();
+
+            let request = FakeRequest { head };
+            let (status, body) = respond(&request);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            let _ = tx.send(request);
+        }
+    });
+    (addr, rx)
+}
@@ -0,0 +1,381 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use super::accounts;
+use super::atomic;
+use super::auth::{extract_info_from_auth, CodexAuthFile};
+use super::config::{get_codex_auth_file, get_token_failures_file, load_config, AutoRefreshConfig};
+use super::error::AppError;
+use super::http;
+use super::logging;
+use super::vault;
+
+use crate::t;
+
+// ========== Token 刷新 ==========
+
+/// Token 刷新请求结构
+#[derive(Debug, Serialize)]
+struct TokenRefreshRequest {
+    client_id: String,
+    grant_type: &'static str,
+    refresh_token: String,
+    scope: &'static str,
+}
+
+/// Token 刷新响应结构
+#[derive(Debug, Deserialize)]
+struct TokenRefreshResponse {
+    id_token: Option<String>,
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+}
+
+/// 失败记录保留的天数，过期后允许再次尝试
+const FAILURE_RETENTION_DAYS: i64 = 90;
+
+/// Refresh Token 永久失效的原因，重试无用，只能重新登录 Codex CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefreshFailureKind {
+    Expired,
+    Reused,
+    Invalidated,
+}
+
+impl RefreshFailureKind {
+    fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "refresh_token_expired" => Some(Self::Expired),
+            "refresh_token_reused" => Some(Self::Reused),
+            "refresh_token_invalidated" => Some(Self::Invalidated),
+            _ => None,
+        }
+    }
+}
+
+impl From<RefreshFailureKind> for AppError {
+    fn from(kind: RefreshFailureKind) -> Self {
+        match kind {
+            RefreshFailureKind::Expired => AppError::TokenExpired,
+            RefreshFailureKind::Reused => AppError::RefreshTokenReused,
+            RefreshFailureKind::Invalidated => AppError::RefreshTokenInvalidated,
+        }
+    }
+}
+
+/// 记录在账号上的永久刷新失败。以失败时的 refresh_token 为键，
+/// 账号文件换成新 Token（重新登录、写回、同步）后自然不再匹配
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshFailure {
+    pub kind: RefreshFailureKind,
+    pub message: String,
+    /// 失败时间（RFC 3339）
+    pub failed_at: String,
+}
+
+fn token_hash(auth: &CodexAuthFile) -> String {
+    hex::encode(Sha256::digest(auth.tokens.refresh_token.as_bytes()))
+}
+
+fn load_failures() -> BTreeMap<String, RefreshFailure> {
+    fs::read(get_token_failures_file())
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 账号当前 Token 的永久失败记录
+pub fn failure_for(auth: &CodexAuthFile) -> Option<RefreshFailure> {
+    load_failures().remove(&token_hash(auth))
+}
+
+async fn record_failure(auth: &CodexAuthFile, kind: RefreshFailureKind) -> Result<(), AppError> {
+    let hash = token_hash(auth);
+    // 读取、修改、写回都在锁内，避免并发刷新的账号互相覆盖记录
+    atomic::with_lock(move || {
+        let now = chrono::Utc::now();
+        let mut failures = load_failures();
+        failures.retain(|_, failure| {
+            chrono::DateTime::parse_from_rfc3339(&failure.failed_at)
+                .is_ok_and(|t| (now - t.with_timezone(&chrono::Utc)).num_days() < FAILURE_RETENTION_DAYS)
+        });
+        failures.insert(
+            hash,
+            RefreshFailure {
+                kind,
+                message: AppError::from(kind).to_string(),
+                failed_at: now.to_rfc3339(),
+            },
+        );
+        let content = serde_json::to_string_pretty(&failures).map_err(|e| t!(SerializeFailed, e))?;
+        atomic::write_atomic(&get_token_failures_file(), content.as_bytes())
+    })
+    .await
+}
+
+/// 按提前量和抖动计算应开始刷新的时间（秒级时间戳），Token 中没有过期时间时返回 `None`
+///
+/// 抖动由 refresh_token 派生，同一 Token 每次计算结果相同，不同账号错开。
+pub fn refresh_due_at(auth: &CodexAuthFile, config: &AutoRefreshConfig) -> Option<i64> {
+    let (_, _, _, expires_at) = extract_info_from_auth(auth);
+    let jitter_secs = config.jitter_minutes * 60;
+    let jitter = if jitter_secs == 0 {
+        0
+    } else {
+        let digest = Sha256::digest(auth.tokens.refresh_token.as_bytes());
+        u64::from_be_bytes(digest[..8].try_into().ok()?) % (jitter_secs + 1)
+    };
+    Some(expires_at? - (config.lead_minutes * 60 + jitter) as i64)
+}
+
+/// 刷新指定账号的 Token
+///
+/// 遇到永久失败时记录在账号上（见 `failure_for`）。
+pub async fn refresh_account_token(file_path: &str) -> Result<String, AppError> {
+    // Codex CLI 可能已经轮换过这个账号的 refresh_token，先把它写回账号文件
+    if let Err(e) = accounts::write_back_live_auth() {
+        tracing::warn!(error = %e, "写回 Codex CLI 刷新的 Token 失败");
+    }
+
+    // 读取认证文件
+    let path = Path::new(file_path);
+    let auth = vault::read_auth(path)?;
+    
+    let refresh_token = &auth.tokens.refresh_token;
+    logging::register_secret(refresh_token);
+    let network = load_config().network;
+    
+    // 构建刷新请求
+    let refresh_request = TokenRefreshRequest {
+        client_id: network.client_id.clone(),
+        grant_type: "refresh_token",
+        refresh_token: refresh_token.clone(),
+        scope: "openid profile email",
+    };
+    
+    tracing::info!(file = file_path, url = %network.oauth_token_url, "开始刷新 Token");
+    
+    // 发送刷新请求
+    let client = http::client_for(&network)?;
+    let response = client
+        .post(&network.oauth_token_url)
+        .header("Content-Type", "application/json")
+        .json(&refresh_request)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+    
+    let status = response.status();
+    tracing::debug!(%status, "Token 刷新响应");
+    
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        tracing::warn!(%status, body = %body, "Token 刷新失败");
+        
+        // 解析错误信息
+        if let Ok(error_json) = serde_json::from_str::<serde_json::Value>(&body) {
+            if let Some(error) = error_json.get("error") {
+                let error_code = error.get("code")
+                    .or_else(|| error_json.get("code"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("unknown");
+                
+                return match RefreshFailureKind::from_code(error_code) {
+                    Some(kind) => {
+                        if let Err(e) = record_failure(&auth, kind).await {
+                            tracing::warn!(error = %e, "记录 Token 失败状态失败");
+                        }
+                        Err(kind.into())
+                    }
+                    None => Err(AppError::HttpStatus(status.as_u16())),
+                };
+            }
+        }
+        
+        return Err(AppError::HttpStatus(status.as_u16()));
+    }
+    
+    // 解析响应
+    let refresh_response: TokenRefreshResponse = response.json().await
+        .map_err(|e| t!(ParseResponseFailed, "OAuth", e))?;
+    
+    // 只记录返回了哪些 Token，不记录内容
+    tracing::info!(
+        file = file_path,
+        access_token = refresh_response.access_token.is_some(),
+        id_token = refresh_response.id_token.is_some(),
+        refresh_token = refresh_response.refresh_token.is_some(),
+        "Token 刷新成功"
+    );
+    if let Some(token) = &refresh_response.refresh_token {
+        logging::register_secret(token);
+    }
+    
+    // 刷新期间账号文件可能已被写回、同步或另一个进程刷新，持锁重新读取后再写入
+    let path = path.to_path_buf();
+    atomic::with_lock(move || save_refreshed(&path, &auth.tokens.refresh_token, refresh_response)).await?;
+    Ok(t!(TokenRefreshed))
+}
+
+/// 把刷新结果写入账号文件，账号仍是刷新前的 Token 时才写入；调用方需已持有 `atomic::lock()`
+fn save_refreshed(path: &Path, old_refresh_token: &str, refresh_response: TokenRefreshResponse) -> Result<(), AppError> {
+    let current = vault::read_auth(path)?;
+    if current.tokens.refresh_token != old_refresh_token {
+        tracing::warn!(file = %path.display(), "刷新期间账号 Token 已被更新，放弃本次结果");
+        return Err(AppError::Conflict(t!(TokenChangedDuringRefresh)));
+    }
+
+    // 更新认证文件
+    let mut updated_auth = current;
+    
+    if let Some(new_access_token) = refresh_response.access_token {
+        updated_auth.tokens.access_token = new_access_token;
+    }
+    if let Some(new_id_token) = refresh_response.id_token {
+        updated_auth.tokens.id_token = new_id_token;
+    }
+    if let Some(new_refresh_token) = refresh_response.refresh_token {
+        updated_auth.tokens.refresh_token = new_refresh_token;
+    }
+    
+    // 更新 last_refresh 时间
+    updated_auth.last_refresh = chrono::Utc::now().to_rfc3339();
+    
+    // 写回文件
+    let content = serde_json::to_string_pretty(&updated_auth).map_err(|e| t!(SerializeFailed, e))?;
+    atomic::write_atomic(path, vault::encode_plaintext(&content)?.as_bytes())?;
+    tracing::debug!(file = %path.display(), "已更新认证文件");
+
+    // 当前使用中的账号：旧 refresh_token 已作废，auth.json 同步换成新 Token
+    let live_path = get_codex_auth_file();
+    let live = fs::read_to_string(&live_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<CodexAuthFile>(&content).ok());
+    if live.is_some_and(|live| live.tokens.refresh_token == old_refresh_token) {
+        atomic::write_atomic(&live_path, content.as_bytes())?;
+        tracing::info!(path = %live_path.display(), "已同步更新 auth.json");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::config::{save_config, AppConfig};
+    use crate::manager::test_support::{self, write_auth as write};
+
+    /// 带过期时间的认证文件
+    fn auth(refresh_token: &str, expires_at: i64) -> CodexAuthFile {
+        let mut auth = test_support::auth("account-1", "a@example.com", refresh_token, "2026-01-01T00:00:00Z");
+        auth.tokens.id_token = test_support::id_token(serde_json::json!({ "email": "a@example.com", "exp": expires_at }));
+        auth
+    }
+
+    /// 模拟 OAuth 服务器并写入配置：处理一个请求，读完请求体后先执行 `before_reply`，再返回给定的状态和内容
+    fn fake_oauth(status: &'static str, body: String, before_reply: impl FnOnce() + Send + 'static) {
+        let mut before_reply = Some(before_reply);
+        let (addr, _) = test_support::fake_server(1, move |_| {
+            if let Some(before_reply) = before_reply.take() {
+                before_reply();
+            }
+            (status, body.clone())
+        });
+        let mut config = AppConfig::default();
+        config.network.oauth_token_url = format!("{}/oauth/token", addr);
+        save_config(&config).unwrap();
+    }
+
+    #[test]
+    fn due_time_applies_lead_and_stable_jitter() {
+        let expires_at = 2_000_000_000;
+        let no_jitter = AutoRefreshConfig { enabled: true, lead_minutes: 30, jitter_minutes: 0 };
+        assert_eq!(refresh_due_at(&auth("rt", expires_at), &no_jitter), Some(expires_at - 30 * 60));
+
+        let config = AutoRefreshConfig { enabled: true, lead_minutes: 30, jitter_minutes: 10 };
+        let dues: Vec<i64> = (0..20)
+            .map(|i| refresh_due_at(&auth(&format!("rt-{}", i), expires_at), &config).unwrap())
+            .collect();
+        for due in &dues {
+            assert!((expires_at - 40 * 60..=expires_at - 30 * 60).contains(due), "{due}");
+        }
+        // 同一 Token 每次结果相同，不同账号错开
+        assert_eq!(refresh_due_at(&auth("rt-0", expires_at), &config), Some(dues[0]));
+        assert!(dues.iter().any(|due| *due != dues[0]));
+
+        // 没有过期时间时不安排刷新
+        let mut no_exp = auth("rt", expires_at);
+        no_exp.tokens.id_token = "not-a-jwt".to_string();
+        assert_eq!(refresh_due_at(&no_exp, &config), None);
+    }
+
+    #[tokio::test]
+    async fn failures_are_tracked_per_token_and_expire() {
+        let _home = test_support::home();
+        let old = auth("rt-old", 0);
+        let other = auth("rt-other", 0);
+        assert!(failure_for(&old).is_none());
+
+        record_failure(&old, RefreshFailureKind::Reused).await.unwrap();
+        let failure = failure_for(&old).unwrap();
+        assert_eq!(failure.kind, RefreshFailureKind::Reused);
+        assert_eq!(failure.message, AppError::RefreshTokenReused.to_string());
+        // 换成新 Token 后不再匹配
+        assert!(failure_for(&other).is_none());
+
+        // 超过保留期的记录在下次写入时清理
+        let mut failures = load_failures();
+        failures.get_mut(&token_hash(&old)).unwrap().failed_at =
+            (chrono::Utc::now() - chrono::Duration::days(FAILURE_RETENTION_DAYS + 1)).to_rfc3339();
+        fs::write(get_token_failures_file(), serde_json::to_vec(&failures).unwrap()).unwrap();
+        record_failure(&other, RefreshFailureKind::Expired).await.unwrap();
+        assert!(failure_for(&old).is_none());
+        assert_eq!(failure_for(&other).unwrap().kind, RefreshFailureKind::Expired);
+    }
+
+    #[tokio::test]
+    async fn refresh_writes_rotated_tokens() {
+        let home = test_support::home();
+        let path = home.path.join(".myswitch/accounts/a.json");
+        write(&path, &auth("rt-1", 0));
+        fake_oauth("200 OK", r#"{"access_token":"access-2","refresh_token":"rt-2"}"#.to_string(), || {});
+
+        refresh_account_token(path.to_str().unwrap()).await.unwrap();
+        let updated = vault::read_auth(&path).unwrap();
+        assert_eq!(updated.tokens.refresh_token, "rt-2");
+        assert_eq!(updated.tokens.access_token, "access-2");
+    }
+
+    #[tokio::test]
+    async fn refresh_records_permanent_failure() {
+        let home = test_support::home();
+        let path = home.path.join(".myswitch/accounts/a.json");
+        write(&path, &auth("rt-1", 0));
+        fake_oauth("401 Unauthorized", r#"{"error":{"code":"refresh_token_reused"}}"#.to_string(), || {});
+
+        let err = refresh_account_token(path.to_str().unwrap()).await.unwrap_err();
+        assert_eq!(err, AppError::RefreshTokenReused);
+        assert_eq!(failure_for(&auth("rt-1", 0)).unwrap().kind, RefreshFailureKind::Reused);
+        assert_eq!(vault::read_auth(&path).unwrap().tokens.refresh_token, "rt-1");
+    }
+
+    #[tokio::test]
+    async fn refresh_keeps_tokens_written_during_request() {
+        let home = test_support::home();
+        let path = home.path.join(".myswitch/accounts/a.json");
+        write(&path, &auth("rt-1", 0));
+        // 请求进行中另一个写入者（写回、同步、命令行）换上了新 Token
+        let concurrent = path.clone();
+        fake_oauth("200 OK", r#"{"refresh_token":"rt-mine"}"#.to_string(), move || {
+            write(&concurrent, &auth("rt-theirs", 0))
+        });
+
+        let err = refresh_account_token(path.to_str().unwrap()).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)));
+        assert_eq!(vault::read_auth(&path).unwrap().tokens.refresh_token, "rt-theirs");
+    }
+}
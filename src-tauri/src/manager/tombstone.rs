@@ -0,0 +1,573 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use super::atomic;
+use super::config::{get_codex_dir, get_pending_deletions_file};
+use super::crypto::{Envelope, SecretKey};
+use super::error::AppError;
+use super::sync::RemoteLayout;
+use super::sync_backend::{PutCondition, PutOutcome, SyncBackend};
+use super::sync_manifest::content_hash;
+
+use crate::t;
+
+// ========== 删除与重命名的同步 ==========
+//
+// 远程根目录下的 manifest.json 为每个删除过的路径保留一条墓碑，其他设备据此删除本地副本，
+// 而不是把它重新上传。被删除的文件先移到远程 trash/<时间>/ 下，超过保留期后清理。
+//
+// 同一路径上的文件比墓碑更新时（删除后又被重新创建或修改），文件优先，墓碑随之移除。
+//
+// 墓碑决定本地删除哪些文件，清单与账号文件一样用同步密钥加密，远程被篡改时无法通过校验。
+
+/// 回收站目录名中的时间格式，按字典序排列即按时间排列
+const TRASH_STAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// 写回远程清单时与其他设备冲突的最大重试次数
+const MANIFEST_RETRIES: usize = 3;
+
+/// 单个被删除路径的墓碑，路径相对于后端根目录，目录以 `/` 结尾
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tombstone {
+    /// 删除时间（RFC 3339）
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: String,
+    /// 删除前内容的 SHA-256，目录为空
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// 重命名时的新路径
+    #[serde(rename = "renamedTo", default)]
+    pub renamed_to: Option<String>,
+    /// 回收站中的位置，远程文件不存在时为空
+    #[serde(default)]
+    pub trash: Option<String>,
+}
+
+impl Tombstone {
+    fn deleted_at(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.deleted_at).ok().map(|t| t.with_timezone(&Utc))
+    }
+
+    /// 本地副本是否就是被删除的那一份（删除后没有再修改），是则应随之删除
+    pub fn matches_local(&self, local: &Path) -> bool {
+        if local.is_dir() {
+            return match (self.deleted_at(), latest_modified(local)) {
+                (Some(deleted_at), Some(modified)) => DateTime::<Utc>::from(modified) <= deleted_at,
+                _ => false,
+            };
+        }
+        match (&self.hash, fs::read(local)) {
+            (Some(hash), Ok(content)) => &content_hash(&content) == hash,
+            _ => false,
+        }
+    }
+
+    /// 用于同步结果的描述，重命名时为 `a.json → b.json`
+    pub fn describe(&self, name: &str) -> String {
+        match self.renamed_to.as_deref().and_then(|path| path.rsplit('/').next()) {
+            Some(new_name) => format!("{} → {}", name, new_name),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// 目录中最近一次修改的时间
+fn latest_modified(dir: &Path) -> Option<SystemTime> {
+    let mut latest = fs::metadata(dir).and_then(|m| m.modified()).ok();
+    for entry in fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        let modified = if path.is_dir() {
+            latest_modified(&path)
+        } else {
+            entry.metadata().and_then(|m| m.modified()).ok()
+        };
+        latest = latest.max(modified);
+    }
+    latest
+}
+
+/// 远程同步清单
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteManifest {
+    #[serde(default)]
+    pub tombstones: BTreeMap<String, Tombstone>,
+}
+
+/// 一次同步中读取的远程清单，以及本次对它的修改
+pub struct Tombstones {
+    key: SecretKey,
+    manifest: RemoteManifest,
+    etag: Option<String>,
+    exists: bool,
+    added: BTreeMap<String, Tombstone>,
+    removed: BTreeSet<String>,
+}
+
+impl Tombstones {
+    /// 读取并解密远程清单，不存在时视为没有任何删除记录
+    pub async fn load(backend: &dyn SyncBackend, key: &SecretKey) -> Result<Self, AppError> {
+        let (manifest, etag, exists) = read_manifest(backend, key).await?;
+        Ok(Self {
+            key: key.clone(),
+            manifest,
+            etag,
+            exists,
+            added: BTreeMap::new(),
+            removed: BTreeSet::new(),
+        })
+    }
+
+    /// 覆盖该路径的墓碑：路径本身或其所在目录被删除
+    pub fn covering(&self, path: &str) -> Option<(&str, &Tombstone)> {
+        self.manifest
+            .tombstones
+            .iter()
+            .find(|(deleted, _)| *deleted == path || (deleted.ends_with('/') && path.starts_with(deleted.as_str())))
+            .map(|(deleted, tombstone)| (deleted.as_str(), tombstone))
+    }
+
+    /// 指定目录下的全部墓碑
+    pub fn under<'a>(&'a self, dir: &'a str) -> impl Iterator<Item = (&'a str, &'a Tombstone)> + 'a {
+        self.manifest
+            .tombstones
+            .iter()
+            .filter(move |(path, _)| path.starts_with(dir))
+            .map(|(path, tombstone)| (path.as_str(), tombstone))
+    }
+
+    pub fn add(&mut self, path: &str, tombstone: Tombstone) {
+        self.removed.remove(path);
+        self.manifest.tombstones.insert(path.to_string(), tombstone.clone());
+        self.added.insert(path.to_string(), tombstone);
+    }
+
+    /// 路径被重新上传，移除它及其所在目录的墓碑
+    pub fn clear(&mut self, path: &str) {
+        let stale: Vec<String> = self
+            .manifest
+            .tombstones
+            .keys()
+            .filter(|deleted| *deleted == path || (deleted.ends_with('/') && path.starts_with(deleted.as_str())))
+            .cloned()
+            .collect();
+        for deleted in stale {
+            self.manifest.tombstones.remove(&deleted);
+            self.added.remove(&deleted);
+            self.removed.insert(deleted);
+        }
+    }
+
+    /// 清理超过保留期的回收站和墓碑，再把本次修改写回远程
+    ///
+    /// `retention_days` 为 0 时不清理。写回冲突时在最新的远程清单上重放本次修改。
+    pub async fn save(mut self, backend: &dyn SyncBackend, retention_days: u32) -> Result<(), AppError> {
+        let cutoff = (retention_days > 0).then(|| Utc::now() - chrono::Duration::days(retention_days.into()));
+        if let Some(cutoff) = cutoff {
+            purge_trash(backend, cutoff).await;
+        }
+
+        for _ in 0..MANIFEST_RETRIES {
+            let before = self.manifest.tombstones.len();
+            if let Some(cutoff) = cutoff {
+                self.manifest
+                    .tombstones
+                    .retain(|_, tombstone| tombstone.deleted_at().is_none_or(|t| t > cutoff));
+            }
+            let pruned = self.manifest.tombstones.len() != before;
+            if self.added.is_empty() && self.removed.is_empty() && !pruned {
+                return Ok(());
+            }
+
+            let content = serde_json::to_vec(&self.manifest).map_err(|e| t!(SerializeFailed, e))?;
+            let content = Envelope::seal(&self.key, &content)?.to_json()?;
+            let condition = match (&self.etag, self.exists) {
+                (Some(etag), true) => PutCondition::IfMatch(etag),
+                (None, true) => PutCondition::Always,
+                (_, false) => PutCondition::IfAbsent,
+            };
+            if let PutOutcome::Written(_) = backend.put(RemoteLayout::MANIFEST, content.as_bytes(), condition).await? {
+                return Ok(());
+            }
+
+            // 其他设备刚刚写过清单，在最新版本上重放本次修改
+            let (mut manifest, etag, exists) = read_manifest(backend, &self.key).await?;
+            for path in &self.removed {
+                manifest.tombstones.remove(path);
+            }
+            manifest.tombstones.extend(self.added.clone());
+            self.manifest = manifest;
+            self.etag = etag;
+            self.exists = exists;
+        }
+        Err(AppError::Conflict(t!(ManifestContention)))
+    }
+}
+
+async fn read_manifest(backend: &dyn SyncBackend, key: &SecretKey) -> Result<(RemoteManifest, Option<String>, bool), AppError> {
+    let Some(file) = backend.get(RemoteLayout::MANIFEST).await? else {
+        return Ok((RemoteManifest::default(), None, false));
+    };
+    let Some(envelope) = Envelope::parse(&file.content) else {
+        // 旧版本写下的明文清单无法确认来源，其中的墓碑不再采用，下次写回时换成加密清单
+        tracing::warn!("远程同步清单未加密，忽略其中的删除记录");
+        return Ok((RemoteManifest::default(), file.etag, true));
+    };
+    // 同步口令已经校验过，解密失败说明清单被篡改或损坏
+    let content = envelope.open(key).map_err(|_| AppError::Corrupt(t!(RemoteManifestTampered)))?;
+    let manifest = serde_json::from_slice(&content).map_err(|e| AppError::Corrupt(t!(RemoteManifestCorrupt, e)))?;
+    Ok((manifest, file.etag, true))
+}
+
+/// 墓碑路径对应的本地 prompts / skills 路径
+///
+/// 清单来自远程，只接受 `codex/prompts/` 和 `codex/skills/` 下由普通名称组成的路径，
+/// 并确认解析符号链接后仍位于对应目录之内，否则返回 `None`。
+pub fn local_codex_path(path: &str, codex_dir: &Path) -> Option<PathBuf> {
+    let relative = path.strip_prefix(RemoteLayout::CODEX)?;
+    let relative = relative.strip_suffix('/').unwrap_or(relative);
+    let mut segments = relative.split('/');
+    let root = match segments.next()? {
+        "prompts" => codex_dir.join("prompts"),
+        "skills" => codex_dir.join("skills"),
+        _ => return None,
+    };
+    let mut local = root.clone();
+    let mut depth = 0;
+    for segment in segments {
+        if !is_plain_name(segment) {
+            return None;
+        }
+        local.push(segment);
+        depth += 1;
+    }
+    if depth == 0 {
+        return None;
+    }
+    // 不存在的路径无需删除；存在时按真实位置再确认一次
+    if let (Ok(root), Ok(real)) = (root.canonicalize(), local.canonicalize()) {
+        if real == root || !real.starts_with(&root) {
+            return None;
+        }
+    }
+    Some(local)
+}
+
+/// 远程给出的单个路径片段是否为普通文件名（不含分隔符，不是 `.`、`..` 或盘符）
+pub fn is_plain_name(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment != "."
+        && segment != ".."
+        && !segment.contains(['/', '\\', ':', '\0'])
+}
+
+// ========== 远程回收站 ==========
+
+/// 本次同步使用的回收站目录，如 `trash/20260101T080000Z/`
+pub fn trash_dir() -> String {
+    format!("{}{}/", RemoteLayout::TRASH, Utc::now().format(TRASH_STAMP_FORMAT))
+}
+
+/// 把远程文件或目录移到回收站，返回回收站中的路径；远程不存在时返回 `None`
+pub async fn move_to_trash(backend: &dyn SyncBackend, path: &str, trash_dir: &str) -> Result<Option<String>, AppError> {
+    let target = format!("{}{}", trash_dir, path);
+    let copied = if path.ends_with('/') {
+        copy_dir(backend, path, &target).await?
+    } else {
+        match backend.get(path).await? {
+            Some(file) => {
+                create_parents(backend, &target).await;
+                backend.put(&target, &file.content, PutCondition::Always).await?;
+                true
+            }
+            None => false,
+        }
+    };
+    if !copied {
+        return Ok(None);
+    }
+    backend.delete(path).await?;
+    Ok(Some(target))
+}
+
+/// 递归复制远程目录，源目录为空或不存在时返回 `false`
+async fn copy_dir(backend: &dyn SyncBackend, dir: &str, target: &str) -> Result<bool, AppError> {
+    let items = backend.list(dir).await?;
+    if items.is_empty() {
+        return Ok(false);
+    }
+    create_parents(backend, target).await;
+    let _ = backend.mkdir(target).await;
+    for item in items {
+        if item.is_dir {
+            let sub_dir = format!("{}{}/", dir, item.name);
+            let sub_target = format!("{}{}/", target, item.name);
+            Box::pin(copy_dir(backend, &sub_dir, &sub_target)).await?;
+        } else if let Some(file) = backend.get(&format!("{}{}", dir, item.name)).await? {
+            backend.put(&format!("{}{}", target, item.name), &file.content, PutCondition::Always).await?;
+        }
+    }
+    Ok(true)
+}
+
+/// 逐级创建路径的父目录（WebDAV 等后端不会自动创建）
+async fn create_parents(backend: &dyn SyncBackend, path: &str) {
+    let trimmed = path.trim_end_matches('/');
+    let mut end = 0;
+    while let Some(pos) = trimmed[end..].find('/') {
+        end += pos + 1;
+        let _ = backend.mkdir(&trimmed[..end]).await;
+    }
+}
+
+/// 删除早于 `cutoff` 的回收站目录，失败只记录日志，下次同步再试
+async fn purge_trash(backend: &dyn SyncBackend, cutoff: DateTime<Utc>) {
+    let items = match backend.list(RemoteLayout::TRASH).await {
+        Ok(items) => items,
+        Err(e) => {
+            tracing::warn!(error = %e, "列出回收站失败");
+            return;
+        }
+    };
+    for item in items.into_iter().filter(|item| item.is_dir) {
+        let Ok(stamp) = NaiveDateTime::parse_from_str(&item.name, TRASH_STAMP_FORMAT) else {
+            continue;
+        };
+        if stamp.and_utc() < cutoff {
+            let dir = format!("{}{}/", RemoteLayout::TRASH, item.name);
+            if let Err(e) = backend.delete(&dir).await {
+                tracing::warn!(%dir, error = %e, "清理回收站失败");
+            }
+        }
+    }
+}
+
+// ========== 本地删除记录 ==========
+//
+// prompts 和 skills 没有同步基准，本地删除时先记下来，下次上传 Codex 配置时写成墓碑。
+// 记录文件可能同时被界面、命令行和后台同步修改，读取、修改、写回都在 `atomic::lock()` 内进行。
+
+/// 等待同步到远程的本地删除
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDeletion {
+    /// 远程路径，如 codex/prompts/review.md、codex/skills/pdf/
+    pub path: String,
+    #[serde(rename = "deletedAt")]
+    pub deleted_at: String,
+    #[serde(default)]
+    pub hash: Option<String>,
+}
+
+impl PendingDeletion {
+    pub fn tombstone(&self, trash: Option<String>) -> Tombstone {
+        Tombstone {
+            deleted_at: self.deleted_at.clone(),
+            hash: self.hash.clone(),
+            renamed_to: None,
+            trash,
+        }
+    }
+}
+
+pub fn load_pending_deletions() -> Vec<PendingDeletion> {
+    fs::read_to_string(get_pending_deletions_file())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// 调用方需已持有 `atomic::lock()`
+fn save_pending_deletions(pending: &[PendingDeletion]) -> Result<(), AppError> {
+    let path = get_pending_deletions_file();
+    if pending.is_empty() {
+        let _ = fs::remove_file(&path);
+        return Ok(());
+    }
+    let content = serde_json::to_string_pretty(pending).map_err(|e| t!(SerializeFailed, e))?;
+    atomic::write_atomic(&path, content.as_bytes())
+}
+
+/// 移除已写成远程墓碑的删除记录
+///
+/// 重新读取记录文件再移除，同步期间新增的删除保留；同一路径在同步期间被再次删除时也保留。
+pub async fn complete_pending_deletions(done: Vec<PendingDeletion>) -> Result<(), AppError> {
+    if done.is_empty() {
+        return Ok(());
+    }
+    atomic::with_lock(move || {
+        let mut pending = load_pending_deletions();
+        pending.retain(|p| !done.contains(p));
+        save_pending_deletions(&pending)
+    })
+    .await
+}
+
+/// 删除 Codex 目录下的文件或目录，并记录下来供下次上传时同步到远程
+///
+/// 不在 Codex 目录下的路径只删除、不记录。
+pub fn delete_codex_path(local: &Path) -> Result<(), AppError> {
+    let is_dir = local.is_dir();
+    let hash = if is_dir {
+        None
+    } else {
+        fs::read(local).ok().map(|content| content_hash(&content))
+    };
+
+    if is_dir {
+        fs::remove_dir_all(local).map_err(|e| AppError::io(local, e))?;
+    } else {
+        fs::remove_file(local).map_err(|e| AppError::io(local, e))?;
+    }
+
+    let Ok(relative) = local.strip_prefix(get_codex_dir()) else {
+        return Ok(());
+    };
+    let mut path = RemoteLayout::CODEX.to_string();
+    path.push_str(&relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/"));
+    if is_dir {
+        path.push('/');
+    }
+
+    let _lock = atomic::lock()?;
+    let mut pending = load_pending_deletions();
+    pending.retain(|p| p.path != path);
+    pending.push(PendingDeletion {
+        path,
+        deleted_at: Utc::now().to_rfc3339(),
+        hash,
+    });
+    save_pending_deletions(&pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::folder::FolderBackend;
+    use crate::manager::test_support::{self, flip_first_byte};
+
+    fn tombstone(hash: Option<&str>) -> Tombstone {
+        Tombstone {
+            deleted_at: Utc::now().to_rfc3339(),
+            hash: hash.map(str::to_string),
+            renamed_to: None,
+            trash: None,
+        }
+    }
+
+    #[test]
+    fn rejects_paths_outside_codex_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let codex = dir.path().join(".codex");
+        fs::create_dir_all(codex.join("skills/pdf")).unwrap();
+        fs::create_dir_all(dir.path().join("victim")).unwrap();
+
+        assert_eq!(local_codex_path("codex/prompts/review.md", &codex), Some(codex.join("prompts/review.md")));
+        assert_eq!(local_codex_path("codex/skills/pdf/", &codex), Some(codex.join("skills/pdf")));
+        for path in [
+            "codex/skills/../../victim/",
+            "codex/prompts/../../../etc/passwd",
+            "codex/skills/./",
+            "codex/skills/",
+            "codex/skills//",
+            "codex/skills/a//b",
+            "codex/prompts/..\\..\\victim",
+            "codex/prompts/C:/Windows",
+            "codex/AGENTS.MD",
+            "codex/../victim/",
+            "accounts/a.json",
+        ] {
+            assert_eq!(local_codex_path(path, &codex), None, "{}", path);
+        }
+
+        // 指向目录之外的符号链接按真实位置判断
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path().join("victim"), codex.join("skills/link")).unwrap();
+            assert_eq!(local_codex_path("codex/skills/link/", &codex), None);
+        }
+    }
+
+    #[test]
+    fn plain_names() {
+        assert!(is_plain_name("review.md"));
+        assert!(is_plain_name("..hidden"));
+        for name in ["", ".", "..", "a/b", "a\\b", "C:", "a\0b"] {
+            assert!(!is_plain_name(name), "{:?}", name);
+        }
+    }
+
+    #[tokio::test]
+    async fn manifest_is_sealed_with_sync_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FolderBackend::at(dir.path().to_path_buf());
+        let key = SecretKey::generate();
+
+        let mut tombstones = Tombstones::load(&backend, &key).await.unwrap();
+        tombstones.add("codex/prompts/review.md", tombstone(Some("abc")));
+        tombstones.save(&backend, 0).await.unwrap();
+
+        let content = fs::read(dir.path().join(RemoteLayout::MANIFEST)).unwrap();
+        assert!(Envelope::parse(&content).is_some());
+        assert!(!String::from_utf8_lossy(&content).contains("review.md"));
+        let loaded = Tombstones::load(&backend, &key).await.unwrap();
+        assert!(loaded.covering("codex/prompts/review.md").is_some());
+
+        // 其他密钥（即其他口令）或被改动的密文无法通过校验
+        assert!(Tombstones::load(&backend, &SecretKey::generate()).await.is_err());
+        let mut envelope = Envelope::parse(&content).unwrap();
+        envelope.ciphertext = flip_first_byte(&envelope.ciphertext);
+        fs::write(dir.path().join(RemoteLayout::MANIFEST), envelope.to_json().unwrap()).unwrap();
+        assert!(matches!(Tombstones::load(&backend, &key).await, Err(AppError::Corrupt(_))));
+    }
+
+    #[tokio::test]
+    async fn completing_keeps_deletions_recorded_meanwhile() {
+        let home = test_support::home();
+        let prompts = home.path.join(".codex/prompts");
+        fs::create_dir_all(&prompts).unwrap();
+        for name in ["a.md", "b.md"] {
+            fs::write(prompts.join(name), name).unwrap();
+        }
+        delete_codex_path(&prompts.join("a.md")).unwrap();
+        let synced = load_pending_deletions();
+
+        // 同步进行中又删除了一个文件
+        delete_codex_path(&prompts.join("b.md")).unwrap();
+        complete_pending_deletions(synced).await.unwrap();
+        let pending: Vec<String> = load_pending_deletions().into_iter().map(|p| p.path).collect();
+        assert_eq!(pending, ["codex/prompts/b.md"]);
+
+        // 同一路径在同步期间被重新创建并再次删除，新的记录保留
+        let synced = load_pending_deletions();
+        fs::write(prompts.join("b.md"), "again").unwrap();
+        delete_codex_path(&prompts.join("b.md")).unwrap();
+        complete_pending_deletions(synced).await.unwrap();
+        assert_eq!(load_pending_deletions()[0].hash, Some(content_hash(b"again")));
+
+        complete_pending_deletions(load_pending_deletions()).await.unwrap();
+        assert!(!get_pending_deletions_file().exists());
+    }
+
+    #[tokio::test]
+    async fn ignores_unsealed_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FolderBackend::at(dir.path().to_path_buf());
+        let mut manifest = RemoteManifest::default();
+        manifest.tombstones.insert("codex/skills/../../".to_string(), tombstone(None));
+        fs::write(dir.path().join(RemoteLayout::MANIFEST), serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let key = SecretKey::generate();
+        let tombstones = Tombstones::load(&backend, &key).await.unwrap();
+        assert_eq!(tombstones.under(RemoteLayout::CODEX).count(), 0);
+
+        // 写回时换成加密清单
+        let mut tombstones = tombstones;
+        tombstones.add("codex/prompts/a.md", tombstone(Some("abc")));
+        tombstones.save(&backend, 0).await.unwrap();
+        assert!(Envelope::parse(&fs::read(dir.path().join(RemoteLayout::MANIFEST)).unwrap()).is_some());
+    }
+}
@@ -0,0 +1,458 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use super::auth::extract_info_from_auth;
+use super::config::{load_config, UsageFetchConfig};
+use super::error::AppError;
+use super::http;
+use super::usage_history;
+use super::vault;
+
+use crate::t;
+
+// ========== 用量查询 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitWindow {
+    #[serde(rename = "usedPercent")]
+    pub used_percent: f64,
+    #[serde(rename = "windowMinutes")]
+    pub window_minutes: Option<i64>,
+    #[serde(rename = "resetsAt")]
+    pub resets_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageInfo {
+    #[serde(rename = "primaryWindow")]
+    pub primary_window: Option<RateLimitWindow>,
+    #[serde(rename = "secondaryWindow")]
+    pub secondary_window: Option<RateLimitWindow>,
+    #[serde(rename = "planType")]
+    pub plan_type: Option<String>,
+}
+
+// API 响应结构
+#[derive(Debug, Deserialize)]
+struct ApiRateLimitWindow {
+    used_percent: f64,
+    limit_window_seconds: Option<i32>,
+    reset_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiRateLimitDetails {
+    primary_window: Option<ApiRateLimitWindow>,
+    secondary_window: Option<ApiRateLimitWindow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiUsageResponse {
+    rate_limit: Option<ApiRateLimitDetails>,
+    plan_type: Option<String>,
+}
+
+/// 上次成功的用量接口保持有效的时间，过期后按配置顺序重新探测（首选接口可能已恢复）
+const ENDPOINT_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// 记住上次成功的用量接口，之后优先请求它，不必每次依次尝试所有地址
+///
+/// 各账号使用同一组接口，按全局记录；接口失败时回退到按配置顺序逐个尝试。
+#[derive(Default)]
+pub struct EndpointCache {
+    working: Mutex<Option<(String, Instant)>>,
+}
+
+impl EndpointCache {
+    /// 仍在有效期内、且仍在配置列表中的接口
+    fn get(&self, urls: &[String]) -> Option<String> {
+        let working = self.working.lock().ok()?;
+        let (url, at) = working.as_ref()?;
+        (at.elapsed() < ENDPOINT_TTL && urls.contains(url)).then(|| url.clone())
+    }
+
+    fn set(&self, url: &str) {
+        if let Ok(mut working) = self.working.lock() {
+            *working = Some((url.to_string(), Instant::now()));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut working) = self.working.lock() {
+            *working = None;
+        }
+    }
+}
+
+static ENDPOINTS: LazyLock<EndpointCache> = LazyLock::new(EndpointCache::default);
+
+/// 获取账号的用量信息
+pub async fn fetch_usage(file_path: &str) -> Result<UsageInfo, AppError> {
+    // 读取认证文件
+    let auth = vault::read_auth(Path::new(file_path))?;
+    let network = load_config().network;
+    let client = http::client_for(&network)?;
+    let usage = query_usage(&client, &ENDPOINTS, &network.usage_urls, &auth.tokens.access_token, &auth.tokens.account_id).await?;
+
+    let (email, ..) = extract_info_from_auth(&auth);
+    // 记录时持有写入锁，放到阻塞线程执行
+    let (account_id, sample) = (auth.tokens.account_id.clone(), usage.clone());
+    let recorded = tokio::task::spawn_blocking(move || {
+        usage_history::record(&account_id, &email.unwrap_or_default(), &sample)
+    })
+    .await;
+    if let Ok(Err(e)) = recorded {
+        tracing::warn!(error = %e, "记录用量历史失败");
+    }
+    Ok(usage)
+}
+
+/// 请求用量接口：先试上次成功的接口，失败再按配置顺序逐个尝试，使用第一个成功的响应
+async fn query_usage(
+    client: &reqwest::Client,
+    cache: &EndpointCache,
+    urls: &[String],
+    access_token: &str,
+    account_id: &str,
+) -> Result<UsageInfo, AppError> {
+    let cached = cache.get(urls);
+    let mut candidates: Vec<&String> = Vec::with_capacity(urls.len());
+    candidates.extend(urls.iter().filter(|url| cached.as_ref() == Some(*url)));
+    candidates.extend(urls.iter().filter(|url| cached.as_ref() != Some(*url)));
+
+    let mut final_response = None;
+    let mut last_error = AppError::InvalidInput(t!(NoUsageUrls));
+
+    for url in candidates {
+        let mut request = client
+            .get(url)
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+            .header("Origin", "https://chatgpt.com");
+            
+        // 如果有 account_id，必须带上
+        if !account_id.is_empty() {
+             request = request.header("ChatGPT-Account-Id", account_id);
+        }
+            
+        match request.send().await {
+            Ok(resp) => {
+                let status = resp.status();
+                if status.is_success() {
+                    tracing::debug!(%url, "用量接口可用");
+                    cache.set(url);
+                    final_response = Some(resp);
+                    break;
+                } else {
+                    tracing::debug!(%url, %status, "用量接口返回错误");
+                    last_error = AppError::HttpStatus(status.as_u16());
+                    // 已知可用的接口返回 401/403，是这个账号的 Token 失效，换接口也无济于事
+                    if cached.as_ref() == Some(url)
+                        && matches!(status, reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN)
+                    {
+                        break;
+                    }
+                    cache.clear();
+                }
+            },
+            Err(e) => {
+                tracing::debug!(%url, error = %e, "用量接口请求失败");
+                last_error = AppError::Network(e.to_string());
+                cache.clear();
+            }
+        }
+    }
+
+    // 所有接口都失败时返回最后一个错误
+    let response = final_response.ok_or(last_error)?;
+    
+    // 尝试解析，兼容不同的响应格式
+    let api_response: ApiUsageResponse = response.json().await
+        .map_err(|e| AppError::Other(t!(ParseResponseFailed, "API", e)))?;
+    
+    // 转换响应格式
+    let map_window = |w: Option<ApiRateLimitWindow>| -> Option<RateLimitWindow> {
+        w.map(|window| RateLimitWindow {
+            used_percent: window.used_percent,
+            window_minutes: window.limit_window_seconds.map(|s| (s as i64 + 59) / 60),
+            resets_at: window.reset_at,
+        })
+    };
+    
+    let (primary, secondary) = match api_response.rate_limit {
+        Some(details) => (
+            map_window(details.primary_window),
+            map_window(details.secondary_window),
+        ),
+        None => (None, None),
+    };
+    
+    Ok(UsageInfo {
+        primary_window: primary,
+        secondary_window: secondary,
+        plan_type: api_response.plan_type,
+    })
+}
+
+/// 单个账号的用量查询结果（后台任务推送给前端，也作为切换策略的输入）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageUpdate {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub usage: Option<UsageInfo>,
+    pub error: Option<AppError>,
+    #[serde(rename = "isTokenExpired")]
+    pub is_token_expired: bool,
+}
+
+impl UsageUpdate {
+    /// 查询用量，失败时记录错误；接口返回 401/403 视为 Token 失效
+    pub async fn fetch(file_path: &str) -> Self {
+        match fetch_usage(file_path).await {
+            Ok(usage) => Self {
+                file_path: file_path.to_string(),
+                usage: Some(usage),
+                error: None,
+                is_token_expired: false,
+            },
+            Err(e) => Self::failed(file_path, e),
+        }
+    }
+
+    fn failed(file_path: &str, error: AppError) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            usage: None,
+            is_token_expired: error.is_auth_expired(),
+            error: Some(error),
+        }
+    }
+}
+
+// ========== 批量查询 ==========
+
+/// 批量查询多个账号的用量，每查完一个账号调用一次 `on_update`（按完成顺序），
+/// 返回的结果与 `file_paths` 顺序一致
+pub async fn fetch_all(
+    file_paths: &[String],
+    config: &UsageFetchConfig,
+    on_update: impl FnMut(&UsageUpdate),
+) -> Vec<UsageUpdate> {
+    fetch_all_with(file_paths, config, |file_path| async move { UsageUpdate::fetch(&file_path).await }, on_update).await
+}
+
+/// `fetch_all` 的实现，查询单个账号的方式由 `fetch` 提供
+async fn fetch_all_with<F, Fut>(
+    file_paths: &[String],
+    config: &UsageFetchConfig,
+    fetch: F,
+    mut on_update: impl FnMut(&UsageUpdate),
+) -> Vec<UsageUpdate>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = UsageUpdate> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
+    let timeout = Duration::from_secs(config.timeout_seconds.max(1));
+
+    let mut tasks = JoinSet::new();
+    for (index, file_path) in file_paths.iter().enumerate() {
+        let semaphore = semaphore.clone();
+        let query = fetch(file_path.clone());
+        let file_path = file_path.clone();
+        tasks.spawn(async move {
+            // 信号量不会被关闭，获取失败时直接查询
+            let _permit = semaphore.acquire_owned().await;
+            let update = match tokio::time::timeout(timeout, query).await {
+                Ok(update) => update,
+                Err(_) => UsageUpdate::failed(&file_path, AppError::Network(t!(UsageTimeout, timeout.as_secs()))),
+            };
+            (index, update)
+        });
+    }
+
+    let mut results: Vec<Option<UsageUpdate>> = vec![None; file_paths.len()];
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok((index, update)) => {
+                on_update(&update);
+                results[index] = Some(update);
+            }
+            Err(e) => tracing::error!(error = %e, "用量查询任务异常退出"),
+        }
+    }
+
+    // 异常退出的任务也返回一条失败结果，调用方不必再比对账号列表
+    results
+        .into_iter()
+        .zip(file_paths)
+        .map(|(update, file_path)| update.unwrap_or_else(|| UsageUpdate::failed(file_path, AppError::Other(t!(UsageTaskAborted)))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::config::NetworkConfig;
+    use crate::manager::test_support::{self, FakeRequest};
+    use std::sync::mpsc;
+
+    const USAGE_BODY: &str = r#"{"plan_type":"team","rate_limit":{"primary_window":{"used_percent":42.5,"limit_window_seconds":18000,"reset_at":1767225600},"secondary_window":null}}"#;
+
+    /// 模拟用量接口：路径为 /usage 时返回用量，其余返回 404，Token 为 `expired` 时返回 401
+    fn usage_server(requests: usize) -> (String, mpsc::Receiver<FakeRequest>) {
+        test_support::fake_server(requests, |request: &FakeRequest| {
+            let first = request.head.lines().next().unwrap_or_default();
+            if request.head.to_ascii_lowercase().contains("bearer expired") {
+                ("401 Unauthorized", String::new())
+            } else if first.contains("/usage ") {
+                ("200 OK", USAGE_BODY.to_string())
+            } else {
+                ("404 Not Found", String::new())
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_next_usage_url() {
+        let (addr, requests) = usage_server(2);
+        let urls = vec![format!("{}/missing", addr), format!("{}/usage", addr)];
+        let client = http::builder_for(&NetworkConfig::default()).unwrap().no_proxy().build().unwrap();
+
+        let usage = query_usage(&client, &EndpointCache::default(), &urls, "access-token", "account-1").await.unwrap();
+        assert_eq!(usage.plan_type.as_deref(), Some("team"));
+        let primary = usage.primary_window.unwrap();
+        assert_eq!(primary.used_percent, 42.5);
+        assert_eq!(primary.window_minutes, Some(300));
+        assert!(usage.secondary_window.is_none());
+
+        assert!(requests.recv().unwrap().head.starts_with("GET /missing "));
+        let head = requests.recv().unwrap().head.to_ascii_lowercase();
+        assert!(head.contains("authorization: bearer access-token"));
+        assert!(head.contains("chatgpt-account-id: account-1"));
+    }
+
+    #[tokio::test]
+    async fn reports_last_error_when_all_urls_fail() {
+        let (addr, _requests) = usage_server(1);
+        let client = http::builder_for(&NetworkConfig::default()).unwrap().no_proxy().build().unwrap();
+
+        let err = query_usage(&client, &EndpointCache::default(), &[format!("{}/missing", addr)], "t", "")
+            .await
+            .unwrap_err();
+        assert_eq!(err, AppError::HttpStatus(404));
+    }
+
+    #[tokio::test]
+    async fn remembers_working_usage_url() {
+        let (addr, requests) = usage_server(4);
+        let urls = vec![format!("{}/missing", addr), format!("{}/usage", addr)];
+        let client = http::builder_for(&NetworkConfig::default()).unwrap().no_proxy().build().unwrap();
+        let cache = EndpointCache::default();
+
+        query_usage(&client, &cache, &urls, "t", "").await.unwrap();
+        assert!(requests.recv().unwrap().head.starts_with("GET /missing "));
+        assert!(requests.recv().unwrap().head.starts_with("GET /usage "));
+
+        // 之后直接请求上次成功的接口
+        query_usage(&client, &cache, &urls, "t", "").await.unwrap();
+        assert!(requests.recv().unwrap().head.starts_with("GET /usage "));
+
+        // 已知可用的接口返回 401 时不再尝试其他接口
+        let err = query_usage(&client, &cache, &urls, "expired", "").await.unwrap_err();
+        assert_eq!(err, AppError::HttpStatus(401));
+        assert!(requests.recv().unwrap().head.starts_with("GET /usage "));
+        assert!(requests.try_recv().is_err());
+        assert_eq!(cache.get(&urls), Some(urls[1].clone()));
+
+        // 配置中已移除的接口不再使用
+        assert_eq!(cache.get(&urls[..1]), None);
+    }
+
+    #[tokio::test]
+    async fn sends_requests_through_configured_proxy() {
+        let (addr, requests) = usage_server(1);
+        let network = NetworkConfig { proxy: Some(addr), ..NetworkConfig::default() };
+        let client = http::builder_for(&network).unwrap().build().unwrap();
+
+        // 目标主机无法解析，只有经过代理才能拿到响应
+        let urls = vec!["http://usage.invalid/usage".to_string()];
+        let usage = query_usage(&client, &EndpointCache::default(), &urls, "t", "").await.unwrap();
+        assert_eq!(usage.plan_type.as_deref(), Some("team"));
+        assert!(requests.recv().unwrap().head.starts_with("GET http://usage.invalid/usage "));
+    }
+
+    #[tokio::test]
+    async fn fetch_all_limits_concurrency_and_keeps_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let paths: Vec<String> = (0..10).map(|i| format!("account-{}.json", i)).collect();
+        let config = UsageFetchConfig { concurrency: 3, timeout_seconds: 5 };
+
+        let mut streamed = Vec::new();
+        let updates = fetch_all_with(
+            &paths,
+            &config,
+            |file_path| {
+                let running = running.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    // 序号越小越慢，完成顺序与输入顺序相反
+                    let index: u64 = file_path.trim_start_matches("account-").trim_end_matches(".json").parse().unwrap();
+                    tokio::time::sleep(Duration::from_millis(5 * (10 - index))).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    UsageUpdate::failed(&file_path, AppError::HttpStatus(401))
+                }
+            },
+            |update| streamed.push(update.file_path.clone()),
+        )
+        .await;
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(streamed.len(), 10);
+        assert_ne!(streamed, paths);
+        assert_eq!(updates.iter().map(|u| u.file_path.clone()).collect::<Vec<_>>(), paths);
+        assert!(updates.iter().all(|u| u.is_token_expired));
+    }
+
+    #[tokio::test]
+    async fn fetch_all_times_out_slow_accounts() {
+        let paths = vec!["slow.json".to_string(), "fast.json".to_string()];
+        let config = UsageFetchConfig { concurrency: 2, timeout_seconds: 1 };
+
+        let updates = fetch_all_with(
+            &paths,
+            &config,
+            |file_path| async move {
+                if file_path == "slow.json" {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                }
+                UsageUpdate { file_path, usage: None, error: None, is_token_expired: false }
+            },
+            |_| {},
+        )
+        .await;
+
+        assert_eq!(updates[0].error, Some(AppError::Network(t!(UsageTimeout, 1))));
+        assert!(!updates[0].is_token_expired);
+        assert_eq!(updates[1].error, None);
+    }
+
+    #[test]
+    fn rejects_missing_ca_bundle() {
+        let network = NetworkConfig {
+            ca_bundle: Some("/nonexistent/ca.pem".to_string()),
+            ..NetworkConfig::default()
+        };
+        assert!(http::builder_for(&network).is_err());
+    }
+}
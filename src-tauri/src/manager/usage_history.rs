@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+
+use super::accounts::AccountInfo;
+use super::atomic;
+use super::config::get_usage_history_dir;
+use super::error::AppError;
+use super::usage::{RateLimitWindow, UsageInfo};
+
+use crate::t;
+
+// ========== 用量历史与预测 ==========
+//
+// 每次查询到的用量按账号追加到 ~/.myswitch/usage-history/<邮箱>_<account_id>.jsonl，
+// 每行一条带时间戳的记录。团队账号的多个席位共用 account_id，因此按邮箱和 account_id 一起区分，
+// 账号文件改名后历史仍然连续。
+
+/// 历史保留的天数
+const RETENTION_DAYS: i64 = 90;
+
+/// 与上一条记录间隔不足这么久时不再记录（秒），避免频繁手动刷新写入大量重复数据
+const MIN_INTERVAL: i64 = 60;
+
+/// 估算消耗速度至少需要的时间跨度（秒）
+const MIN_SPAN: i64 = 10 * 60;
+
+/// 同一窗口内各次查询返回的重置时间可能有几秒偏差
+const RESET_TOLERANCE: i64 = 120;
+
+/// 本进程内各历史文件最后一次写入的时间，用于去重
+static LAST_RECORDED: LazyLock<Mutex<HashMap<PathBuf, i64>>> = LazyLock::new(Default::default);
+
+/// 一次用量查询的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageSample {
+    /// 查询时间（秒级时间戳）
+    pub at: i64,
+    #[serde(flatten)]
+    pub usage: UsageInfo,
+}
+
+/// 单个限额窗口的预测
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowForecast {
+    pub used_percent: f64,
+    /// 当前窗口内每小时消耗的百分比，样本不足时为空
+    pub burn_rate_per_hour: Option<f64>,
+    /// 按当前速度用满 100% 的时间；在窗口重置前用不完时为空
+    pub exhausts_at: Option<i64>,
+    pub resets_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageForecast {
+    pub primary: Option<WindowForecast>,
+    pub secondary: Option<WindowForecast>,
+}
+
+/// 账号的用量历史与预测
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageHistory {
+    pub file_path: String,
+    pub name: String,
+    pub email: String,
+    pub samples: Vec<UsageSample>,
+    pub forecast: UsageForecast,
+}
+
+// ========== 读写 ==========
+
+/// 账号的历史文件，文件名只保留安全字符；Token 中没有邮箱（`email` 为空）时只用 account_id
+fn history_file(account_id: &str, email: &str) -> PathBuf {
+    let key = if email.is_empty() {
+        account_id.to_string()
+    } else {
+        format!("{}_{}", email, account_id)
+    };
+    let name: String = key
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "@.-_".contains(c) { c } else { '_' })
+        .collect();
+    get_usage_history_dir().join(format!("{}.jsonl", name))
+}
+
+/// 追加一条用量记录（持有写入锁，异步任务中应放到阻塞线程执行）
+pub fn record(account_id: &str, email: &str, usage: &UsageInfo) -> Result<(), AppError> {
+    let path = history_file(account_id, email);
+    let now = chrono::Utc::now().timestamp();
+    {
+        let mut last = LAST_RECORDED.lock().map_err(|_| t!(InternalState))?;
+        if last.get(&path).is_some_and(|&at| now - at < MIN_INTERVAL) {
+            return Ok(());
+        }
+        last.insert(path.clone(), now);
+    }
+
+    let sample = UsageSample { at: now, usage: usage.clone() };
+    let mut line = serde_json::to_string(&sample).map_err(|e| t!(SerializeFailed, e))?;
+    line.push('\n');
+
+    // 追加和清理共用写入锁：清理会用新文件替换旧文件，期间追加到旧文件的记录会丢失
+    let _lock = atomic::lock()?;
+    let dir = get_usage_history_dir();
+    fs::create_dir_all(&dir).map_err(|e| AppError::io(&dir, e))?;
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    // 整行一次写入，O_APPEND 保证多个进程同时追加时不会交错
+    options
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| AppError::io(&path, e))?;
+
+    prune_if_stale(&path, now)
+}
+
+/// 最早的记录超过保留期一天以上时重写文件，每个文件大约每天清理一次；调用方需已持有 `atomic::lock()`
+fn prune_if_stale(path: &PathBuf, now: i64) -> Result<(), AppError> {
+    let cutoff = now - RETENTION_DAYS * 86400;
+    let oldest = fs::File::open(path)
+        .ok()
+        .and_then(|file| BufReader::new(file).lines().next()?.ok())
+        .and_then(|line| serde_json::from_str::<UsageSample>(&line).ok());
+    if oldest.is_none_or(|sample| sample.at >= cutoff - 86400) {
+        return Ok(());
+    }
+
+    let content: String = fs::read_to_string(path)
+        .map_err(|e| AppError::io(path, e))?
+        .lines()
+        .filter(|line| serde_json::from_str::<UsageSample>(line).is_ok_and(|s| s.at >= cutoff))
+        .flat_map(|line| [line, "\n"])
+        .collect();
+    atomic::write_atomic(path, content.as_bytes())
+}
+
+/// 读取账号在 `since`（秒级时间戳）之后的记录，按时间排序；无法解析的行忽略
+pub fn samples(account_id: &str, email: &str, since: i64) -> Vec<UsageSample> {
+    let Ok(file) = fs::File::open(history_file(account_id, email)) else {
+        return Vec::new();
+    };
+    let mut samples: Vec<UsageSample> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<UsageSample>(&line).ok())
+        .filter(|sample| sample.at >= since)
+        .collect();
+    samples.sort_by_key(|sample| sample.at);
+    samples
+}
+
+/// 账号最近一次记录的用量，只读取文件末尾，供托盘菜单频繁调用
+pub fn latest(account_id: &str, email: &str) -> Option<UsageSample> {
+    const TAIL: u64 = 4096;
+    let mut file = fs::File::open(history_file(account_id, email)).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL))).ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    // 截断处的第一行不完整，解析失败会被跳过
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<UsageSample>(line).ok())
+}
+
+/// 账号最近 `days` 天的历史和预测
+pub fn history(account: &AccountInfo, days: u32) -> UsageHistory {
+    let now = chrono::Utc::now().timestamp();
+    // 每周窗口的预测需要整个窗口内的记录，至少读取 7 天
+    let all = samples(&account.id, &account.email, now - i64::from(days.max(7)) * 86400);
+    let forecast = forecast(&all);
+    let since = now - i64::from(days) * 86400;
+    UsageHistory {
+        file_path: account.file_path.clone(),
+        name: account.name.clone(),
+        email: account.email.clone(),
+        samples: all.into_iter().filter(|sample| sample.at >= since).collect(),
+        forecast,
+    }
+}
+
+// ========== 预测 ==========
+
+/// 按最新窗口内的消耗速度预测两个窗口何时用满
+pub fn forecast(samples: &[UsageSample]) -> UsageForecast {
+    UsageForecast {
+        primary: forecast_window(samples, |usage| usage.primary_window.as_ref()),
+        secondary: forecast_window(samples, |usage| usage.secondary_window.as_ref()),
+    }
+}
+
+fn forecast_window(
+    samples: &[UsageSample],
+    window: impl Fn(&UsageInfo) -> Option<&RateLimitWindow>,
+) -> Option<WindowForecast> {
+    let points: Vec<(i64, &RateLimitWindow)> = samples
+        .iter()
+        .filter_map(|sample| window(&sample.usage).map(|w| (sample.at, w)))
+        .collect();
+    let &(last_at, latest) = points.last()?;
+
+    // 只用当前窗口（重置时间相同）内的记录；接口没有返回重置时间时用窗口长度截取
+    let current: Vec<(i64, f64)> = points
+        .iter()
+        .filter(|(at, w)| match (latest.resets_at, w.resets_at) {
+            (Some(latest), Some(reset)) => (latest - reset).abs() <= RESET_TOLERANCE,
+            _ => latest.window_minutes.is_some_and(|minutes| last_at - at < minutes * 60),
+        })
+        .map(|&(at, w)| (at, w.used_percent))
+        .collect();
+
+    let burn_rate = burn_rate_per_second(&current);
+    let exhausts_at = if latest.used_percent >= 100.0 {
+        Some(last_at)
+    } else {
+        burn_rate
+            .filter(|&rate| rate > 0.0)
+            .map(|rate| last_at + ((100.0 - latest.used_percent) / rate).ceil() as i64)
+            // 在重置前用不完
+            .filter(|&at| latest.resets_at.is_none_or(|reset| at < reset))
+    };
+
+    Some(WindowForecast {
+        used_percent: latest.used_percent,
+        burn_rate_per_hour: burn_rate.map(|rate| rate * 3600.0),
+        exhausts_at,
+        resets_at: latest.resets_at,
+    })
+}
+
+/// 最小二乘拟合的消耗速度（百分比 / 秒），时间跨度不足时返回 None
+fn burn_rate_per_second(points: &[(i64, f64)]) -> Option<f64> {
+    let (first, last) = (points.first()?, points.last()?);
+    if points.len() < 2 || last.0 - first.0 < MIN_SPAN {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|&(t, _)| (t - first.0) as f64).sum::<f64>() / n;
+    let mean_u = points.iter().map(|&(_, u)| u).sum::<f64>() / n;
+    let (mut cov, mut var) = (0.0, 0.0);
+    for &(t, u) in points {
+        let dt = (t - first.0) as f64 - mean_t;
+        cov += dt * (u - mean_u);
+        var += dt * dt;
+    }
+    (var > 0.0).then(|| (cov / var).max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(at: i64, primary: f64, resets_at: i64) -> UsageSample {
+        UsageSample {
+            at,
+            usage: UsageInfo {
+                primary_window: Some(RateLimitWindow {
+                    used_percent: primary,
+                    window_minutes: Some(300),
+                    resets_at: Some(resets_at),
+                }),
+                secondary_window: None,
+                plan_type: Some("team".to_string()),
+            },
+        }
+    }
+
+    #[test]
+    fn forecasts_exhaustion_at_current_burn_rate() {
+        // 每小时消耗 20%，40% 时还剩 3 小时，窗口 5 小时后才重置
+        let samples = [sample(0, 20.0, 5 * 3600), sample(1800, 30.0, 5 * 3600), sample(3600, 40.0, 5 * 3600)];
+        let forecast = forecast(&samples).primary.unwrap();
+        assert_eq!(forecast.used_percent, 40.0);
+        assert!((forecast.burn_rate_per_hour.unwrap() - 20.0).abs() < 1e-9);
+        assert!(forecast.exhausts_at.is_some_and(|at| (at - 4 * 3600).abs() <= 1));
+        assert!(forecast_window(&samples, |u| u.secondary_window.as_ref()).is_none());
+    }
+
+    #[test]
+    fn no_exhaustion_when_window_resets_first() {
+        let samples = [sample(0, 20.0, 2 * 3600), sample(3600, 40.0, 2 * 3600)];
+        let forecast = forecast(&samples).primary.unwrap();
+        assert_eq!(forecast.exhausts_at, None);
+        assert_eq!(forecast.resets_at, Some(2 * 3600));
+    }
+
+    #[test]
+    fn ignores_samples_from_previous_window() {
+        // 上个窗口用到 90%，重置后才开始的新窗口只有一条记录，样本不足
+        let samples = [sample(0, 80.0, 3600), sample(1800, 90.0, 3600), sample(4000, 5.0, 3600 + 5 * 3600)];
+        let forecast = forecast(&samples).primary.unwrap();
+        assert_eq!(forecast.used_percent, 5.0);
+        assert_eq!(forecast.burn_rate_per_hour, None);
+        assert_eq!(forecast.exhausts_at, None);
+    }
+
+    #[test]
+    fn exhausted_window_reports_last_sample() {
+        let samples = [sample(100, 100.0, 3600)];
+        assert_eq!(forecast(&samples).primary.unwrap().exhausts_at, Some(100));
+    }
+
+    #[test]
+    fn record_prunes_expired_samples() {
+        let _home = crate::manager::test_support::home();
+        let now = chrono::Utc::now().timestamp();
+        let path = history_file("acct", "a@example.com");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let old = serde_json::to_string(&sample(now - (RETENTION_DAYS + 2) * 86400, 10.0, now)).unwrap();
+        let recent = serde_json::to_string(&sample(now - 3600, 20.0, now)).unwrap();
+        fs::write(&path, format!("{}\n{}\n", old, recent)).unwrap();
+
+        record("acct", "a@example.com", &sample(now, 30.0, now + 3600).usage).unwrap();
+        let kept: Vec<f64> = samples("acct", "a@example.com", 0)
+            .into_iter()
+            .filter_map(|s| s.usage.primary_window.map(|w| w.used_percent))
+            .collect();
+        assert_eq!(kept, [20.0, 30.0]);
+    }
+
+    #[test]
+    fn sample_serializes_flat() {
+        let line = serde_json::to_string(&sample(1, 2.5, 3)).unwrap();
+        assert!(line.starts_with(r#"{"at":1,"primaryWindow":{"usedPercent":2.5"#), "{}", line);
+        let parsed: UsageSample = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed.usage.primary_window.unwrap().resets_at, Some(3));
+    }
+}
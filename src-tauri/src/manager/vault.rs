@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::atomic;
+use super::auth::CodexAuthFile;
+use super::config::get_accounts_dir;
+use super::crypto::{Envelope, KeyCheck, SecretKey};
+use super::error::AppError;
+
+use crate::t;
+
+// ========== 账号加密存储 ==========
+//
+// 启用后账号目录中会多出一个 `.vault` 元数据文件（Argon2 参数 + 口令校验块），
+// 每个账号文件仍为 `<name>.json`，但内容是加密后的 Envelope。
+// 只有 ~/.codex/auth.json 会保存明文副本。
+
+/// 账号目录中的元数据文件名（不以 .json 结尾，避免被当作账号扫描）
+pub const VAULT_META_FILE: &str = ".vault";
+
+/// 用于验证口令的已知明文
+const VAULT_CHECK: &[u8] = b"codex-manager-vault";
+
+/// 解锁后缓存的密钥（仅保存在内存中）
+static UNLOCKED_KEY: Mutex<Option<SecretKey>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultStatus {
+    pub enabled: bool,
+    pub unlocked: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MigrationReport {
+    pub encrypted: Vec<String>,
+    pub skipped: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+fn meta_path(accounts_dir: &Path) -> PathBuf {
+    accounts_dir.join(VAULT_META_FILE)
+}
+
+fn load_meta(accounts_dir: &Path) -> Option<KeyCheck> {
+    let content = fs::read_to_string(meta_path(accounts_dir)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn cached_key() -> Option<SecretKey> {
+    UNLOCKED_KEY.lock().ok()?.clone()
+}
+
+fn set_cached_key(key: Option<SecretKey>) {
+    if let Ok(mut guard) = UNLOCKED_KEY.lock() {
+        *guard = key;
+    }
+}
+
+/// 当前账号目录是否启用了加密存储
+pub fn is_enabled() -> bool {
+    meta_path(&get_accounts_dir()).exists()
+}
+
+pub fn status() -> VaultStatus {
+    VaultStatus {
+        enabled: is_enabled(),
+        unlocked: cached_key().is_some(),
+    }
+}
+
+/// 使用口令解锁，口令错误时返回错误
+pub fn unlock(passphrase: &str) -> Result<(), AppError> {
+    let meta = load_meta(&get_accounts_dir()).ok_or(AppError::VaultNotEnabled)?;
+    set_cached_key(Some(meta.unlock(passphrase)?));
+    Ok(())
+}
+
+/// 清除内存中的密钥
+pub fn lock() {
+    set_cached_key(None);
+}
+
+fn unlocked_key() -> Result<SecretKey, AppError> {
+    cached_key().ok_or(AppError::VaultLocked)
+}
+
+/// 读取账号文件的明文 JSON，加密文件会透明解密
+pub fn read_plaintext(path: &Path) -> Result<String, AppError> {
+    let content = fs::read(path).map_err(|e| AppError::io(path, e))?;
+    match Envelope::parse(&content) {
+        Some(envelope) => {
+            let plaintext = envelope.open(&unlocked_key()?)?;
+            String::from_utf8(plaintext).map_err(|e| AppError::InvalidAuthJson(t!(DecryptedNotText, e)))
+        }
+        None => String::from_utf8(content).map_err(|e| AppError::InvalidAuthJson(e.to_string())),
+    }
+}
+
+/// 读取并解析账号文件
+pub fn read_auth(path: &Path) -> Result<CodexAuthFile, AppError> {
+    let content = read_plaintext(path)?;
+    serde_json::from_str(&content).map_err(|e| AppError::InvalidAuthJson(e.to_string()))
+}
+
+/// 账号文件落盘的内容，启用加密存储时为加密后的 Envelope
+pub fn encode_plaintext(content: &str) -> Result<String, AppError> {
+    if is_enabled() {
+        Envelope::seal(&unlocked_key()?, content.as_bytes())?.to_json()
+    } else {
+        Ok(content.to_string())
+    }
+}
+
+/// 写入账号文件的明文 JSON，启用加密存储时先加密
+pub fn write_plaintext(path: &Path, content: &str) -> Result<(), AppError> {
+    atomic::write_file(path, encode_plaintext(content)?.as_bytes())
+}
+
+/// 序列化并写入账号文件
+pub fn write_auth(path: &Path, auth: &CodexAuthFile) -> Result<(), AppError> {
+    let content = serde_json::to_string_pretty(auth).map_err(|e| t!(SerializeFailed, e))?;
+    write_plaintext(path, &content)
+}
+
+/// 将账号目录中的明文账号文件迁移为加密存储
+///
+/// 首次迁移时用口令创建 `.vault`；已启用时口令必须与现有口令一致。
+/// 迁移后保持解锁状态。
+pub fn migrate(passphrase: &str) -> Result<MigrationReport, AppError> {
+    if passphrase.chars().count() < 8 {
+        return Err(AppError::InvalidInput(t!(PassphraseTooShort)));
+    }
+
+    let accounts_dir = get_accounts_dir();
+    if !accounts_dir.exists() {
+        fs::create_dir_all(&accounts_dir).map_err(|e| AppError::io(&accounts_dir, e))?;
+    }
+
+    if load_meta(&accounts_dir).is_some() {
+        unlock(passphrase)?;
+    } else {
+        let (meta, key) = KeyCheck::create(passphrase, VAULT_CHECK)?;
+        let content = serde_json::to_string_pretty(&meta).map_err(|e| t!(SerializeFailed, e))?;
+        atomic::write_file(&meta_path(&accounts_dir), content.as_bytes())?;
+        set_cached_key(Some(key));
+    }
+
+    let key = unlocked_key()?;
+    let mut report = MigrationReport::default();
+    let entries = fs::read_dir(&accounts_dir).map_err(|e| AppError::io(&accounts_dir, e))?;
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("json") {
+            continue;
+        }
+        let name = path.file_name().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+
+        let content = match fs::read(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                report.errors.push(t!(FileReadFailed, name, e));
+                continue;
+            }
+        };
+        if Envelope::parse(&content).is_some() {
+            report.skipped.push(name);
+            continue;
+        }
+        if serde_json::from_slice::<CodexAuthFile>(&content).is_err() {
+            report.errors.push(t!(FileNotAuthSkipped, name));
+            continue;
+        }
+
+        // 原子替换，避免中途失败留下半个文件
+        let result = Envelope::seal(&key, &content)
+            .and_then(|envelope| envelope.to_json())
+            .and_then(|data| atomic::write_file(&path, data.as_bytes()));
+        match result {
+            Ok(_) => report.encrypted.push(name),
+            Err(e) => report.errors.push(format!("{}: {}", name, e)),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manager::test_support;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    fn account_json(refresh_token: &str) -> String {
+        serde_json::json!({
+            "OPENAI_API_KEY": null,
+            "last_refresh": "2026-01-01T00:00:00Z",
+            "tokens": {
+                "access_token": "access",
+                "account_id": "account-1",
+                "id_token": "id",
+                "refresh_token": refresh_token,
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn migrate_encrypts_plaintext_and_skips_encrypted_files() {
+        let home = test_support::home();
+        let accounts = home.path.join(".myswitch/accounts");
+        fs::create_dir_all(&accounts).unwrap();
+        fs::write(accounts.join("a.json"), account_json("rt-a")).unwrap();
+        fs::write(accounts.join("notes.json"), "{}").unwrap();
+
+        let report = migrate(PASSPHRASE).unwrap();
+        assert_eq!(report.encrypted, ["a.json"]);
+        assert_eq!(report.errors.len(), 1);
+        assert!(Envelope::parse(&fs::read(accounts.join("a.json")).unwrap()).is_some());
+
+        // 再次迁移：已加密的文件原样保留
+        let sealed = fs::read(accounts.join("a.json")).unwrap();
+        fs::write(accounts.join("b.json"), account_json("rt-b")).unwrap();
+        let report = migrate(PASSPHRASE).unwrap();
+        assert_eq!(report.encrypted, ["b.json"]);
+        assert_eq!(report.skipped, ["a.json"]);
+        assert_eq!(fs::read(accounts.join("a.json")).unwrap(), sealed);
+    }
+
+    #[test]
+    fn files_stay_readable_across_lock_and_unlock() {
+        let home = test_support::home();
+        let path = home.path.join(".myswitch/accounts/a.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, account_json("rt-a")).unwrap();
+        migrate(PASSPHRASE).unwrap();
+        assert!(status().enabled && status().unlocked);
+
+        lock();
+        assert!(!status().unlocked);
+        assert!(matches!(read_auth(&path), Err(AppError::VaultLocked)));
+        assert!(matches!(write_plaintext(&path, &account_json("rt-x")), Err(AppError::VaultLocked)));
+        assert!(matches!(unlock("wrong passphrase"), Err(AppError::WrongPassphrase)));
+        assert!(matches!(migrate("wrong passphrase"), Err(AppError::WrongPassphrase)));
+
+        unlock(PASSPHRASE).unwrap();
+        assert_eq!(read_auth(&path).unwrap().tokens.refresh_token, "rt-a");
+        write_plaintext(&path, &account_json("rt-b")).unwrap();
+        assert!(Envelope::parse(&fs::read(&path).unwrap()).is_some());
+        lock();
+        unlock(PASSPHRASE).unwrap();
+        assert_eq!(read_auth(&path).unwrap().tokens.refresh_token, "rt-b");
+    }
+
+    #[test]
+    fn plaintext_accounts_need_no_passphrase() {
+        let home = test_support::home();
+        let path = home.path.join(".myswitch/accounts/a.json");
+        write_plaintext(&path, &account_json("rt-a")).unwrap();
+        assert!(!status().enabled);
+        assert_eq!(fs::read_to_string(&path).unwrap(), account_json("rt-a"));
+        assert_eq!(read_auth(&path).unwrap().tokens.refresh_token, "rt-a");
+    }
+}
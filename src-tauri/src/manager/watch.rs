@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+// ========== 文件变化检测 ==========
+//
+// 定时比较文件的大小和修改时间，不依赖各平台的文件系统通知，网络共享上的目录同样适用。
+
+/// 两次检查之间没有新变化才认为写入已经结束
+const SETTLE_DELAY: Duration = Duration::from_secs(2);
+
+/// 某一时刻一组文件的大小和修改时间
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot(BTreeMap<PathBuf, (u64, Option<SystemTime>)>);
+
+impl Snapshot {
+    /// 目录下的文件（不含子目录），目录不存在时为空
+    pub fn of_dir(dir: &Path) -> Self {
+        let mut files = BTreeMap::new();
+        if let Ok(entries) = fs::read_dir(dir) {
+            for entry in entries.flatten() {
+                if let Ok(meta) = entry.metadata() {
+                    if meta.is_file() {
+                        files.insert(entry.path(), (meta.len(), meta.modified().ok()));
+                    }
+                }
+            }
+        }
+        Self(files)
+    }
+
+    /// 单个文件，不存在时为空
+    pub fn of_file(path: &Path) -> Self {
+        let mut files = BTreeMap::new();
+        if let Ok(meta) = fs::metadata(path) {
+            files.insert(path.to_path_buf(), (meta.len(), meta.modified().ok()));
+        }
+        Self(files)
+    }
+}
+
+/// 每隔 `poll` 检查一次，直到快照发生变化且稳定下来，随后更新 `snapshot`
+pub async fn wait_for_change(snapshot: &mut Snapshot, poll: Duration, take: impl Fn() -> Snapshot) {
+    loop {
+        tokio::time::sleep(poll).await;
+        let mut current = take();
+        if current == *snapshot {
+            continue;
+        }
+        // 等待连续写入结束，避免读到写了一半的文件
+        loop {
+            tokio::time::sleep(SETTLE_DELAY).await;
+            let next = take();
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        *snapshot = current;
+        return;
+    }
+}
@@ -0,0 +1,385 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::error::AppError;
+use super::http;
+use super::logging;
+use super::propfind::{href_path, parse_multistatus};
+use super::sync::{SyncResult, AGENTS_MD, CONFIG_TOML};
+use super::sync_backend::{content_type_for, PutCondition, PutOutcome, RemoteEntry, RemoteFile, SyncBackend};
+
+use crate::t;
+
+// ========== WebDAV 同步 ==========
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebDavConfig {
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    #[serde(rename = "remotePath")]
+    pub remote_path: String,
+}
+
+/// 规范化远程目录，确保前后斜杠存在
+fn normalize_remote_path(path: &str) -> String {
+    let mut p = path.trim().to_string();
+    if !p.starts_with('/') {
+        p.insert(0, '/');
+    }
+    if !p.ends_with('/') {
+        p.push('/');
+    }
+    p
+}
+
+/// 对路径的每一段做 URL 编码
+fn encode_remote_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| urlencoding::encode(segment).into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn response_etag(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+}
+
+/// WebDAV 后端，内部方法使用以 `/` 开头的服务器路径
+pub struct WebDavBackend {
+    client: reqwest::Client,
+    config: WebDavConfig,
+    /// 规范化后的远程根目录
+    root: String,
+}
+
+impl WebDavBackend {
+    pub fn new(config: &WebDavConfig) -> Result<Self, AppError> {
+        logging::register_secret(&config.password);
+        Ok(Self {
+            client: http::client()?,
+            root: normalize_remote_path(&config.remote_path),
+            config: config.clone(),
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), encode_remote_path(path))
+    }
+
+    fn request(&self, method: &str, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(reqwest::Method::from_bytes(method.as_bytes()).unwrap(), self.url(path))
+            .basic_auth(&self.config.username, Some(&self.config.password))
+    }
+
+    /// PROPFIND Depth: 1，列出目录中的直接子项（包括隐藏文件和子目录）
+    async fn list_dir(&self, dir: &str) -> Result<Vec<RemoteEntry>, AppError> {
+        let url = self.url(dir);
+        let response = self
+            .request("PROPFIND", dir)
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .header("Accept", "*/*")
+            .body(r#"<?xml version="1.0" encoding="utf-8"?><propfind xmlns="DAV:"><prop><resourcetype/><getcontentlength/><getetag/><getlastmodified/></prop></propfind>"#)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        let status = response.status();
+        tracing::debug!(%url, %status, "PROPFIND");
+
+        if status.as_u16() == 404 {
+            return Ok(Vec::new());
+        }
+        if !status.is_success() && status.as_u16() != 207 {
+            return Err(status_error("PROPFIND", status));
+        }
+
+        let body = response.text().await.map_err(|e| AppError::Network(e.to_string()))?;
+
+        // href 可能是绝对 URL，也可能只有路径，统一按解码后的路径比较
+        let dir = href_path(&url);
+        let entries = parse_multistatus(&body)?
+            .into_iter()
+            .filter_map(|entry| {
+                entry.child_name(&dir).map(|name| RemoteEntry {
+                    name,
+                    is_dir: entry.is_collection,
+                    size: entry.content_length,
+                    etag: entry.etag,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        tracing::debug!(%url, bytes = body.len(), entries = entries.len(), "列目录完成");
+        Ok(entries)
+    }
+
+    /// 下载文件的原始字节，同时返回 ETag
+    async fn get_file(&self, path: &str) -> Result<Option<RemoteFile>, AppError> {
+        let response = self
+            .request("GET", path)
+            .header("Accept", "*/*")
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        let status = response.status();
+        tracing::debug!(path, %status, "GET");
+
+        if status.as_u16() == 404 {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(status_error("GET", status));
+        }
+        let etag = response_etag(&response);
+        let content = response.bytes().await.map_err(|e| AppError::Network(e.to_string()))?.to_vec();
+        tracing::debug!(path, bytes = content.len(), "下载完成");
+        Ok(Some(RemoteFile { content, etag }))
+    }
+
+    /// 上传文件，成功时返回服务器给出的新 ETag
+    async fn put_file(&self, path: &str, content: &[u8], condition: PutCondition<'_>) -> Result<PutOutcome, AppError> {
+        let mut request = self
+            .request("PUT", path)
+            .header("Content-Type", content_type_for(path));
+        request = match condition {
+            PutCondition::Always => request,
+            PutCondition::IfMatch(etag) => request.header("If-Match", etag),
+            PutCondition::IfAbsent => request.header("If-None-Match", "*"),
+        };
+
+        let response = request
+            .body(content.to_vec())
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        let status = response.status();
+        tracing::debug!(path, bytes = content.len(), %status, "PUT");
+
+        if status.is_success() {
+            Ok(PutOutcome::Written(response_etag(&response)))
+        } else if status.as_u16() == 412 {
+            Ok(PutOutcome::Conflict)
+        } else {
+            Err(status_error("PUT", status))
+        }
+    }
+
+    /// 确保远程目录存在
+    async fn mkcol(&self, dir: &str) -> Result<(), AppError> {
+        let response = self
+            .request("MKCOL", dir.trim_end_matches('/'))
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        // 201 Created, 405 Already exists, 301 Redirect - all OK
+        let status = response.status().as_u16();
+        if status == 201 || status == 405 || status == 301 || response.status().is_success() {
+            Ok(())
+        } else {
+            Err(status_error("MKCOL", response.status()))
+        }
+    }
+
+    /// 删除远程文件或目录
+    async fn delete_path(&self, path: &str) -> Result<(), AppError> {
+        let response = self
+            .request("DELETE", path)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() || status.as_u16() == 404 {
+            Ok(())
+        } else {
+            Err(status_error("DELETE", status))
+        }
+    }
+
+    /// 移动远程文件或目录，不覆盖已存在的目标；源不存在时返回 `Ok(false)`
+    async fn move_path(&self, from: &str, to: &str) -> Result<bool, AppError> {
+        let response = self
+            .request("MOVE", from)
+            .header("Destination", self.url(to))
+            .header("Overwrite", "F")
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+
+        let status = response.status();
+        tracing::debug!(from, to, %status, "MOVE");
+        match status.as_u16() {
+            201 | 204 => Ok(true),
+            404 => Ok(false),
+            _ => Err(status_error("MOVE", status)),
+        }
+    }
+
+    /// 移动目录时目标已存在则逐项移动（不覆盖同名文件），全部移走后删除旧目录
+    async fn move_merge(&self, from: &str, to: &str) -> Result<bool, AppError> {
+        match self.move_path(from, to).await {
+            // Overwrite: F 时目标已存在返回 412
+            Err(AppError::HttpStatus(412)) if from.ends_with('/') => {}
+            other => return other,
+        }
+
+        let items = self.list_dir(from).await?;
+        let mut errors = Vec::new();
+        for item in &items {
+            let result = if item.is_dir {
+                let (from, to) = (format!("{}{}/", from, item.name), format!("{}{}/", to, item.name));
+                Box::pin(self.move_merge(&from, &to)).await
+            } else {
+                self.move_path(&format!("{}{}", from, item.name), &format!("{}{}", to, item.name)).await
+            };
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", item.name, e));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AppError::Other(errors.join("; ")));
+        }
+
+        self.delete_path(from).await?;
+        Ok(true)
+    }
+
+    /// 布局版本 1 中的路径及其新位置 (旧路径, 新路径)，目录以 / 结尾
+    ///
+    /// 版本 1 把 Codex 配置直接放在根目录下，账号目录拼接时少了斜杠，
+    /// 变成了根目录的兄弟目录（如 `/codex-manageraccounts/`）。
+    fn legacy_moves(&self) -> Vec<(String, String)> {
+        let root = &self.root;
+        let codex = format!("{}codex/", root);
+        let mut moves = Vec::new();
+        let legacy_accounts = normalize_remote_path(&format!("{}accounts", root.trim_end_matches('/')));
+        if legacy_accounts != format!("{}accounts/", root) {
+            moves.push((legacy_accounts, format!("{}accounts/", root)));
+        }
+        moves.push((format!("{}{}", root, AGENTS_MD), format!("{}{}", codex, AGENTS_MD)));
+        moves.push((format!("{}{}", root, CONFIG_TOML), format!("{}{}", codex, CONFIG_TOML)));
+        moves.push((format!("{}prompts/", root), format!("{}prompts/", codex)));
+        moves.push((format!("{}skills/", root), format!("{}skills/", codex)));
+        moves
+    }
+}
+
+/// 远程根目录下的布局版本标记
+const LAYOUT_MARKER_FILE: &str = ".layout";
+const LAYOUT_VERSION: u32 = 2;
+
+#[async_trait]
+impl SyncBackend for WebDavBackend {
+    fn id(&self) -> String {
+        format!("{}{}", self.config.url.trim_end_matches('/'), self.root)
+    }
+
+    /// 首次使用新布局时迁移旧布局的文件
+    ///
+    /// 迁移过的条目记入 `result.migrated`，失败的记入 `result.errors`，
+    /// 全部成功后才写入布局标记，否则下次同步会重试。
+    async fn prepare(&self, result: &mut SyncResult) -> Result<(), AppError> {
+        let marker = format!("{}{}", self.root, LAYOUT_MARKER_FILE);
+        let current = self
+            .get_file(&marker)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|file| String::from_utf8(file.content).ok())
+            .and_then(|content| content.trim().parse::<u32>().ok());
+        if current == Some(LAYOUT_VERSION) {
+            return Ok(());
+        }
+
+        let mut migrated_ok = true;
+        if current.is_none_or(|version| version < LAYOUT_VERSION) {
+            // 根目录必须先存在，新位置的父目录才能创建
+            if let Err(e) = self.mkcol(&self.root).await {
+                tracing::warn!(error = %e, "创建根目录失败");
+            }
+            let _ = self.mkcol(&format!("{}codex/", self.root)).await;
+
+            for (from, to) in self.legacy_moves() {
+                match self.move_merge(&from, &to).await {
+                    Ok(true) => result.migrated.push(format!("{} -> {}", from, to)),
+                    Ok(false) => {}
+                    Err(e) => {
+                        migrated_ok = false;
+                        result.errors.push(t!(MigrateLegacyFailed, from, e));
+                    }
+                }
+            }
+        }
+
+        if migrated_ok {
+            let version = LAYOUT_VERSION.to_string();
+            if let Err(e) = self.put_file(&marker, version.as_bytes(), PutCondition::Always).await {
+                tracing::warn!(error = %e, "写入布局标记失败");
+            }
+        }
+        Ok(())
+    }
+
+    async fn list(&self, dir: &str) -> Result<Vec<RemoteEntry>, AppError> {
+        self.list_dir(&format!("{}{}", self.root, dir)).await
+    }
+
+    async fn get(&self, path: &str) -> Result<Option<RemoteFile>, AppError> {
+        self.get_file(&format!("{}{}", self.root, path)).await
+    }
+
+    async fn put(&self, path: &str, content: &[u8], condition: PutCondition<'_>) -> Result<PutOutcome, AppError> {
+        self.put_file(&format!("{}{}", self.root, path), content, condition).await
+    }
+
+    async fn delete(&self, path: &str) -> Result<(), AppError> {
+        self.delete_path(&format!("{}{}", self.root, path)).await
+    }
+
+    async fn mkdir(&self, dir: &str) -> Result<(), AppError> {
+        self.mkcol(&format!("{}{}", self.root, dir)).await
+    }
+}
+
+/// 测试 WebDAV 连接
+pub async fn webdav_test_connection(config: &WebDavConfig) -> Result<String, AppError> {
+    let backend = WebDavBackend::new(config)?;
+
+    // 尝试 PROPFIND 根目录
+    let response = backend
+        .request("PROPFIND", &backend.root)
+        .header("Depth", "0")
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() || status.as_u16() == 207 {
+        Ok(t!(ConnectionOk))
+    } else if status.as_u16() == 404 {
+        // 目录不存在，尝试创建
+        backend.mkcol(&backend.root).await?;
+        Ok(t!(ConnectionOkCreated))
+    } else {
+        Err(status_error("PROPFIND", status))
+    }
+}
+
+/// 非成功状态码对应的错误，401 单独区分为认证失败
+fn status_error(action: &str, status: reqwest::StatusCode) -> AppError {
+    tracing::warn!(action, %status, "WebDAV 请求失败");
+    match status.as_u16() {
+        401 => AppError::WebDavAuth,
+        status => AppError::HttpStatus(status),
+    }
+}
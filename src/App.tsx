@@ -43,7 +43,12 @@ function App() {
     settings,
     updateSettings,
     renameAccount,
-    bestCandidateId
+    bestCandidateId,
+    bestCandidateReason,
+    vault,
+    unlockVault,
+    savedSyncTarget,
+    saveSyncTarget
   } = useAccounts();
 
   const [currentView, setCurrentView] = useState<ViewType>('accounts');
@@ -51,6 +56,9 @@ function App() {
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
   const [isSyncOpen, setIsSyncOpen] = useState(false);
   const [editingAccount, setEditingAccount] = useState<AccountInfo | null>(null);
+  const [passphrase, setPassphrase] = useState('');
+  const [unlockError, setUnlockError] = useState<string | null>(null);
+  const [unlocking, setUnlocking] = useState(false);
 
   const handleOpenDir = async () => {
     try {
@@ -60,6 +68,19 @@ function App() {
     }
   };
 
+  const handleUnlock = async () => {
+    if (!passphrase) return;
+    setUnlocking(true);
+    const result = await unlockVault(passphrase);
+    setUnlocking(false);
+    if (result.success) {
+      setPassphrase('');
+      setUnlockError(null);
+    } else {
+      setUnlockError(result.message ?? '解锁失败');
+    }
+  };
+
   const handleNavigate = (view: ViewType) => {
     setCurrentView(view);
   };
@@ -101,7 +122,38 @@ function App() {
                 exit={{ opacity: 0, y: -10 }}
                 transition={{ duration: 0.2 }}
               >
-                {accounts.length === 0 && !loading ? (
+                {vault.enabled && !vault.unlocked ? (
+                  <motion.div
+                    variants={emptyStateVariants}
+                    initial="hidden"
+                    animate="visible"
+                  >
+                    <GlassCard className="text-center py-8 sm:py-12 px-4">
+                      <div className="w-16 h-16 sm:w-20 sm:h-20 glass-light rounded-2xl flex items-center justify-center mx-auto mb-4 sm:mb-6">
+                        <svg className="w-8 h-8 sm:w-10 sm:h-10 text-primary-400" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                          <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={1.5} d="M12 15v2m-6 4h12a2 2 0 002-2v-6a2 2 0 00-2-2H6a2 2 0 00-2 2v6a2 2 0 002 2zm10-10V7a4 4 0 00-8 0v4h8z" />
+                        </svg>
+                      </div>
+                      <h3 className="text-lg sm:text-xl font-bold text-gradient mb-2">账号库已加密</h3>
+                      <p className="text-slate-400 text-sm sm:text-base max-w-sm mx-auto mb-6">输入口令解锁后才能查看和切换账号</p>
+                      <div className="flex gap-2 max-w-sm mx-auto">
+                        <input
+                          type="password"
+                          value={passphrase}
+                          onChange={(e) => setPassphrase(e.target.value)}
+                          onKeyDown={(e) => e.key === 'Enter' && handleUnlock()}
+                          placeholder="加密口令"
+                          className="input-glass flex-1 text-sm"
+                          autoFocus
+                        />
+                        <GlassButton variant="primary" onClick={handleUnlock} disabled={unlocking || !passphrase}>
+                          {unlocking ? '解锁中...' : '解锁'}
+                        </GlassButton>
+                      </div>
+                      {unlockError && <p className="text-xs text-red-400 mt-3">{unlockError}</p>}
+                    </GlassCard>
+                  </motion.div>
+                ) : accounts.length === 0 && !loading ? (
                   <motion.div
                     variants={emptyStateVariants}
                     initial="hidden"
@@ -165,6 +217,7 @@ function App() {
                             onEdit={() => setEditingAccount(account)}
                             renameAccount={renameAccount}
                             isBestCandidate={account.id === bestCandidateId}
+                            bestCandidateReason={bestCandidateReason}
                             onRefresh={refresh}
                           />
                         </motion.div>
@@ -250,6 +303,8 @@ function App() {
           onClose={() => setIsSettingsOpen(false)}
           settings={settings}
           onUpdateSettings={updateSettings}
+          savedSyncTarget={savedSyncTarget}
+          onSaveSyncTarget={saveSyncTarget}
         />
 
         <SyncConfirmDialog
@@ -258,6 +313,7 @@ function App() {
           webdavConfig={settings.webdav || { enabled: false, url: '', username: '', password: '', remotePath: '' }}
           syncSettings={settings.sync || DEFAULT_SYNC_SETTINGS}
           onSyncComplete={handleSyncComplete}
+          onSaveSyncTarget={saveSyncTarget}
         />
       </div>
     </div>
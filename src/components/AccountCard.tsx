@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react';
 import { motion } from 'framer-motion';
 import { invoke } from '@tauri-apps/api/core';
-import { AccountInfo } from '../types';
+import { AccountInfo, WindowForecast, errorMessage } from '../types';
 import { GlassCard, CircularProgress } from './ui';
 
 interface AccountCardProps {
@@ -9,6 +9,7 @@ interface AccountCardProps {
   onSwitch: () => void;
   onEdit: () => void;
   isBestCandidate?: boolean;
+  bestCandidateReason?: string;
   renameAccount: (oldPath: string, newName: string) => Promise<any>;
   onRefresh?: () => void;
 }
@@ -41,7 +42,7 @@ export function getPlanBadgeClasses(plan: string): { text: string; classes: stri
   }
 }
 
-export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, renameAccount, onRefresh }: AccountCardProps) {
+export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, bestCandidateReason, renameAccount, onRefresh }: AccountCardProps) {
   const [isEditing, setIsEditing] = useState(false);
   const [newName, setNewName] = useState(account.name);
   const [, setTick] = useState(0);
@@ -85,6 +86,21 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
     });
   };
 
+  // 鼠标悬停在进度环上时显示按当前速度预计用满的时间
+  const forecastTitle = (forecast: WindowForecast | null | undefined) => {
+    if (!forecast || forecast.burnRatePerHour === null) return '用量记录不足，暂无法预测';
+    const rate = `每小时约 ${forecast.burnRatePerHour.toFixed(1)}%`;
+    if (forecast.exhaustsAt === null) return `${rate}，重置前不会用满`;
+    const at = new Date(forecast.exhaustsAt * 1000).toLocaleString('zh-CN', {
+      hour12: false,
+      month: '2-digit',
+      day: '2-digit',
+      hour: '2-digit',
+      minute: '2-digit'
+    });
+    return `${rate}，预计 ${at} 用满`;
+  };
+
   const handleRename = async () => {
     if (newName.trim() && newName !== account.name) {
       await renameAccount(account.filePath, newName);
@@ -101,7 +117,7 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
       onRefresh?.();
     } catch (error) {
       console.error('Token 刷新失败:', error);
-      alert(`刷新失败: ${error}`);
+      alert(`刷新失败: ${errorMessage(error)}`);
     } finally {
       setRefreshing(false);
     }
@@ -185,6 +201,7 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
         {isBestCandidate && !account.isActive && (
           <motion.span
             className="px-1.5 py-0.5 text-[10px] rounded-md font-bold bg-gradient-to-r from-amber-500/30 to-amber-600/20 text-amber-400 border border-amber-500/30 flex items-center gap-0.5"
+            title={bestCandidateReason}
             animate={{ scale: [1, 1.02, 1] }}
             transition={{ duration: 1.5, repeat: Infinity }}
           >
@@ -200,9 +217,10 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
             className="px-1.5 py-0.5 text-[10px] rounded-md font-medium bg-rose-500/20 text-rose-400 border border-rose-500/30 flex items-center gap-0.5"
             animate={{ opacity: [1, 0.7, 1] }}
             transition={{ duration: 1, repeat: Infinity }}
+            title={account.refreshFailure ? account.refreshFailure.message : undefined}
           >
             <span className="w-1 h-1 rounded-full bg-rose-500" />
-            过期
+            {account.refreshFailure ? '需重新登录' : '过期'}
           </motion.span>
         )}
       </div>
@@ -215,7 +233,7 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
             <svg className="w-3 h-3 flex-shrink-0" fill="none" viewBox="0 0 24 24" stroke="currentColor">
               <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M3 8l7.89 5.26a2 2 0 002.22 0L21 8M5 19h14a2 2 0 002-2V7a2 2 0 00-2-2H5a2 2 0 00-2 2v10a2 2 0 002 2z" />
             </svg>
-            <span className="truncate text-xs select-all" title={account.email}>{account.email}</span>
+            <span className="truncate text-xs select-all" title={account.email}>{account.email || '未知'}</span>
           </div>
 
           <div className="flex items-center gap-2 text-slate-400">
@@ -237,18 +255,22 @@ export function AccountCard({ account, onSwitch, onEdit, isBestCandidate, rename
 
         {/* Center: Circular Progress */}
         <div className="flex gap-4 items-center flex-shrink-0">
-          <CircularProgress
-            value={account.usage?.primaryWindow?.usedPercent ?? 0}
-            size={72}
-            strokeWidth={6}
-            label="5小时"
-          />
-          <CircularProgress
-            value={account.usage?.secondaryWindow?.usedPercent ?? 0}
-            size={72}
-            strokeWidth={6}
-            label="每周"
-          />
+          <div title={forecastTitle(account.forecast?.primary)}>
+            <CircularProgress
+              value={account.usage?.primaryWindow?.usedPercent ?? 0}
+              size={72}
+              strokeWidth={6}
+              label="5小时"
+            />
+          </div>
+          <div title={forecastTitle(account.forecast?.secondary)}>
+            <CircularProgress
+              value={account.usage?.secondaryWindow?.usedPercent ?? 0}
+              size={72}
+              strokeWidth={6}
+              label="每周"
+            />
+          </div>
         </div>
 
         {/* Right: Icon Buttons */}
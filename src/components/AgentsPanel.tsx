@@ -3,6 +3,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { motion } from 'framer-motion';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
+import { errorMessage } from '../types';
 import { GlassButton } from './ui';
 
 interface AgentsPanelProps {
@@ -41,7 +42,7 @@ export function AgentsPanel({ onBack }: AgentsPanelProps) {
       setIsEditing(false);
     } catch (error) {
       console.error('保存失败:', error);
-      alert(String(error));
+      alert(errorMessage(error));
     } finally {
       setSaving(false);
     }
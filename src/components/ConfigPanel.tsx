@@ -1,6 +1,7 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { motion } from 'framer-motion';
+import { errorMessage } from '../types';
 import { GlassButton } from './ui';
 
 interface ConfigPanelProps {
@@ -39,7 +40,7 @@ export function ConfigPanel({ onBack }: ConfigPanelProps) {
       setIsEditing(false);
     } catch (error) {
       console.error('保存失败:', error);
-      alert(String(error));
+      alert(errorMessage(error));
     } finally {
       setSaving(false);
     }
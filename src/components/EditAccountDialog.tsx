@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
 import { invoke } from '@tauri-apps/api/core';
-import { AccountInfo } from '../types';
+import { AccountInfo, errorMessage } from '../types';
 import { GlassCard, GlassButton, GlassTextarea } from './ui';
 
 interface EditAccountDialogProps {
@@ -42,8 +42,8 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
     try {
       const content = await invoke<string>('read_account_content', { filePath: account.filePath });
       setJsonContent(content);
-    } catch (e: any) {
-      setError(e.toString());
+    } catch (e) {
+      setError(errorMessage(e));
     } finally {
       setLoadingContent(false);
     }
@@ -66,8 +66,8 @@ export function EditAccountDialog({ isOpen, onClose, account, onSave }: EditAcco
       await invoke('update_account_content', { filePath: account.filePath, content: jsonContent });
       onSave();
       onClose();
-    } catch (e: any) {
-      setError(e.toString());
+    } catch (e) {
+      setError(errorMessage(e));
     } finally {
       setLoading(false);
     }
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { PromptInfo } from '../types';
+import { PromptInfo, errorMessage } from '../types';
 import { motion, AnimatePresence } from 'framer-motion';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
@@ -102,7 +102,7 @@ export function PromptsPanel({ onBack }: PromptsPanelProps) {
       await loadPrompts();
     } catch (error) {
       console.error('创建失败:', error);
-      alert(String(error));
+      alert(errorMessage(error));
     }
   };
 
@@ -1,8 +1,29 @@
 import { useState, useEffect } from 'react';
 import { motion, AnimatePresence } from 'framer-motion';
-import { AppSettings, DEFAULT_SETTINGS, DEFAULT_SYNC_SETTINGS } from '../types';
+import {
+  AppSettings,
+  SwitchStrategyKind,
+  SyncBackendKind,
+  CodexDirection,
+  SyncStatus,
+  VaultStatus,
+  MigrationReport,
+  SavedSyncTarget,
+  NetworkConfig,
+  Locale,
+  SECRET_WEBDAV_PASSWORD,
+  SECRET_S3_SECRET_ACCESS_KEY,
+  SECRET_SYNC_PASSPHRASE,
+  DEFAULT_SETTINGS,
+  DEFAULT_SYNC_SETTINGS,
+  DEFAULT_AUTO_REFRESH_CONFIG,
+  DEFAULT_S3_CONFIG,
+  DEFAULT_GIT_CONFIG,
+  errorMessage,
+} from '../types';
 import { useAccounts } from '../hooks/useAccounts';
-import { open } from '@tauri-apps/plugin-dialog';
+import { useSyncStatus } from '../hooks/useSyncStatus';
+import { open, save } from '@tauri-apps/plugin-dialog';
 import { invoke } from '@tauri-apps/api/core';
 import { GlassButton } from './ui';
 
@@ -11,6 +32,8 @@ interface SettingsDialogProps {
   onClose: () => void;
   settings: AppSettings;
   onUpdateSettings: (settings: Partial<AppSettings>) => void;
+  savedSyncTarget: SavedSyncTarget | null;
+  onSaveSyncTarget: () => Promise<{ success: boolean; message?: string }>;
 }
 
 const overlayVariants = {
@@ -26,21 +49,35 @@ const dialogVariants = {
 
 type SettingsTab = 'general' | 'sync';
 
-export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }: SettingsDialogProps) {
+export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings, savedSyncTarget, onSaveSyncTarget }: SettingsDialogProps) {
   const { getAccountsDir, setAccountsDir } = useAccounts();
   const [localDir, setLocalDir] = useState<string>('');
   const [activeTab, setActiveTab] = useState<SettingsTab>('general');
   const [webdavTesting, setWebdavTesting] = useState(false);
   const [webdavMessage, setWebdavMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
   const [showWebdavPassword, setShowWebdavPassword] = useState(false);
+  const [vaultStatus, setVaultStatus] = useState<VaultStatus | null>(null);
+  const [vaultPassphrase, setVaultPassphrase] = useState('');
+  const [vaultMigrating, setVaultMigrating] = useState(false);
+  const [vaultMessage, setVaultMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
+  const [diagnosticsMessage, setDiagnosticsMessage] = useState<{ type: 'success' | 'error'; text: string } | null>(null);
 
+  const syncStatus = useSyncStatus();
   const webdav = settings.webdav || DEFAULT_SETTINGS.webdav!;
   const sync = settings.sync || DEFAULT_SYNC_SETTINGS;
+  const autoRefresh = { ...DEFAULT_AUTO_REFRESH_CONFIG, ...settings.autoRefresh };
+  const network: NetworkConfig = settings.network || { proxy: null, noProxy: null, caBundle: null };
+  const updateNetwork = (patch: Partial<NetworkConfig>) => onUpdateSettings({ network: { ...network, ...patch } });
+  const backend = webdav.backend ?? 'webdav';
+  const s3 = { ...DEFAULT_S3_CONFIG, ...webdav.s3 };
+  const git = { ...DEFAULT_GIT_CONFIG, ...webdav.git };
 
   useEffect(() => {
     if (isOpen) {
       getAccountsDir().then(setLocalDir);
+      invoke<VaultStatus>('get_vault_status').then(setVaultStatus).catch(() => setVaultStatus(null));
       setWebdavMessage(null);
+      setVaultMessage(null);
     }
   }, [isOpen, getAccountsDir]);
 
@@ -61,12 +98,103 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
     }
   };
 
+  const handleExportDiagnostics = async () => {
+    const stamp = new Date().toISOString().slice(0, 19).replace(/[-:T]/g, '');
+    const filePath = await save({
+      defaultPath: `codex-manager-diagnostics-${stamp}.zip`,
+      filters: [{ name: 'Zip', extensions: ['zip'] }],
+      title: '导出诊断包',
+    });
+    if (!filePath) return;
+    try {
+      await invoke<string>('export_diagnostics', { filePath });
+      setDiagnosticsMessage({ type: 'success', text: `已导出到 ${filePath}` });
+    } catch (error) {
+      setDiagnosticsMessage({ type: 'error', text: errorMessage(error) });
+    }
+  };
+
+  const handleBrowseSyncFolder = async () => {
+    const selected = await open({
+      directory: true,
+      multiple: false,
+      defaultPath: webdav.folderPath || undefined,
+      title: '选择同步目录',
+    });
+    if (selected && typeof selected === 'string') {
+      updateWebdav({ folderPath: selected });
+    }
+  };
+
+  const handleMigrateVault = async () => {
+    setVaultMigrating(true);
+    setVaultMessage(null);
+    try {
+      const report = await invoke<MigrationReport>('migrate_accounts_to_vault', { passphrase: vaultPassphrase });
+      const summary = `已加密 ${report.encrypted.length} 个，跳过 ${report.skipped.length} 个`;
+      setVaultMessage(report.errors.length > 0
+        ? { type: 'error', text: `${summary}，失败: ${report.errors.join('; ')}` }
+        : { type: 'success', text: summary });
+      setVaultPassphrase('');
+      setVaultStatus(await invoke<VaultStatus>('get_vault_status'));
+    } catch (error) {
+      setVaultMessage({ type: 'error', text: errorMessage(error) });
+    } finally {
+      setVaultMigrating(false);
+    }
+  };
+
+  const handleLockVault = async () => {
+    setVaultStatus(await invoke<VaultStatus>('lock_vault'));
+  };
+
+  // 关闭时立即保存尚未提交的同步目标
+  const handleClose = () => {
+    onSaveSyncTarget();
+    onClose();
+  };
+
+  // 已保存的凭据不会回显，输入框留空表示沿用
+  const savedSecretHint = (name: string, fallback: string) => {
+    switch (savedSyncTarget?.saved[name]) {
+      case 'keyring':
+        return '已保存在系统钥匙串，留空则不修改';
+      case 'file':
+        return '已保存在本机文件（仅做混淆，非钥匙串保护），留空则不修改';
+      default:
+        return fallback;
+    }
+  };
+  const hasSecret = (name: string, value?: string) => !!value || !!savedSyncTarget?.saved[name];
+
   const updateWebdav = (updates: Partial<typeof webdav>) => {
     onUpdateSettings({
       webdav: { ...webdav, ...updates },
     });
   };
 
+  const updateS3 = (updates: Partial<typeof s3>) => {
+    updateWebdav({ s3: { ...s3, ...updates } });
+  };
+
+  const updateGit = (updates: Partial<typeof git>) => {
+    updateWebdav({ git: { ...git, ...updates } });
+  };
+
+  // 当前后端的必填项是否已填写
+  const canTestConnection = (() => {
+    switch (backend) {
+      case 's3':
+        return !!(s3.endpoint && s3.bucket && s3.accessKeyId && hasSecret(SECRET_S3_SECRET_ACCESS_KEY, s3.secretAccessKey));
+      case 'folder':
+        return !!webdav.folderPath;
+      case 'git':
+        return !!git.url;
+      default:
+        return !!(webdav.username && hasSecret(SECRET_WEBDAV_PASSWORD, webdav.password));
+    }
+  })();
+
   const updateSync = (updates: Partial<typeof sync>) => {
     onUpdateSettings({
       sync: { ...sync, ...updates },
@@ -77,17 +205,12 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
     setWebdavTesting(true);
     setWebdavMessage(null);
     try {
-      const result = await invoke<string>('webdav_test_connection', {
-        config: {
-          url: webdav.url,
-          username: webdav.username,
-          password: webdav.password,
-          remotePath: webdav.remotePath,
-        },
-      });
+      const saved = await onSaveSyncTarget();
+      if (!saved.success) throw saved.message;
+      const result = await invoke<string>('test_sync_backend');
       setWebdavMessage({ type: 'success', text: result });
     } catch (e: unknown) {
-      setWebdavMessage({ type: 'error', text: String(e) });
+      setWebdavMessage({ type: 'error', text: errorMessage(e) });
     } finally {
       setWebdavTesting(false);
     }
@@ -109,6 +232,25 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
     return d.toLocaleDateString('zh-CN');
   };
 
+  const formatSyncStatus = (status: SyncStatus) => {
+    const time = (timestamp?: number) =>
+      timestamp ? new Date(timestamp).toLocaleTimeString('zh-CN', { hour: '2-digit', minute: '2-digit' }) : '';
+    switch (status.phase) {
+      case 'syncing':
+        return '正在同步…';
+      case 'idle':
+        return `下次同步 ${time(status.nextSync)}`;
+      case 'partial':
+        return `部分文件未同步：${status.message ?? ''}`;
+      case 'failed':
+        return status.nextSync
+          ? `同步失败（${status.message ?? ''}），${time(status.nextSync)} 重试`
+          : `同步失败（${status.message ?? ''}），请检查同步设置`;
+      default:
+        return '未启用';
+    }
+  };
+
   return (
     <AnimatePresence>
       {isOpen && (
@@ -122,7 +264,7 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
           <motion.div
             className="absolute inset-0 bg-black/60 backdrop-blur-sm"
             variants={overlayVariants}
-            onClick={onClose}
+            onClick={handleClose}
           />
 
           {/* Dialog */}
@@ -135,7 +277,7 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
             <div className="flex justify-between items-center mb-4">
               <h3 className="text-xl font-bold text-gradient">设置</h3>
               <button
-                onClick={onClose}
+                onClick={handleClose}
                 className="p-2 rounded-lg hover:bg-white/10 text-slate-400 hover:text-white transition-colors"
               >
                 <svg className="w-5 h-5" fill="none" viewBox="0 0 24 24" stroke="currentColor">
@@ -195,6 +337,86 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
 
                 <div className="h-px bg-white/10" />
 
+                {/* 消息语言 */}
+                <div className="space-y-2">
+                  <label className="text-sm font-medium text-slate-300">消息语言</label>
+                  <select
+                    value={settings.locale ?? 'zh'}
+                    onChange={(e) => onUpdateSettings({ locale: e.target.value as Locale })}
+                    className="input-glass w-full text-sm"
+                  >
+                    <option value="zh">中文</option>
+                    <option value="en">English</option>
+                  </select>
+                  <p className="text-xs text-slate-500">托盘菜单、错误提示和同步结果使用的语言</p>
+                </div>
+
+                <div className="h-px bg-white/10" />
+
+                {/* 诊断 */}
+                <div className="space-y-2">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <div className="font-medium text-slate-200">诊断包</div>
+                      <div className="text-xs text-slate-400">日志保存在 ~/.myswitch/logs，Token 和密码已脱敏</div>
+                    </div>
+                    <GlassButton variant="secondary" size="sm" onClick={handleExportDiagnostics}>
+                      导出
+                    </GlassButton>
+                  </div>
+                  {diagnosticsMessage && (
+                    <p className={`text-xs ${diagnosticsMessage.type === 'success' ? 'text-emerald-400' : 'text-red-400'}`}>
+                      {diagnosticsMessage.text}
+                    </p>
+                  )}
+                </div>
+
+                <div className="h-px bg-white/10" />
+
+                {/* 加密存储 */}
+                <div className="space-y-2">
+                  <div className="flex items-center justify-between">
+                    <div>
+                      <div className="font-medium text-slate-200">加密存储</div>
+                      <div className="text-xs text-slate-400">
+                        {vaultStatus?.enabled
+                          ? (vaultStatus.unlocked ? '已启用，当前已解锁' : '已启用，当前已锁定')
+                          : '账号文件以明文保存在数据目录中'}
+                      </div>
+                    </div>
+                    {vaultStatus?.enabled && vaultStatus.unlocked && (
+                      <GlassButton variant="secondary" size="sm" onClick={handleLockVault}>
+                        锁定
+                      </GlassButton>
+                    )}
+                  </div>
+                  <div className="flex gap-2">
+                    <input
+                      type="password"
+                      value={vaultPassphrase}
+                      onChange={(e) => setVaultPassphrase(e.target.value)}
+                      placeholder={vaultStatus?.enabled ? '输入现有口令' : '设置口令（至少 8 位）'}
+                      className="input-glass flex-1 text-sm"
+                    />
+                    <GlassButton
+                      variant="secondary"
+                      size="sm"
+                      onClick={handleMigrateVault}
+                      disabled={vaultMigrating || !vaultPassphrase}
+                    >
+                      {vaultMigrating ? '加密中...' : (vaultStatus?.enabled ? '加密剩余明文' : '启用加密')}
+                    </GlassButton>
+                  </div>
+                  <p className="text-xs text-slate-500">使用 Argon2id + XChaCha20-Poly1305 加密，口令遗失后无法恢复账号文件</p>
+                  {vaultMessage && (
+                    <p className={`text-xs ${vaultMessage.type === 'success' ? 'text-emerald-400' : 'text-red-400'}`}>
+                      {vaultMessage.text}
+                    </p>
+                  )}
+                </div>
+
+                <div className="h-px bg-white/10" />
+
                 {/* 自动检测 */}
                 <div className="flex items-center justify-between">
                   <div>
@@ -228,6 +450,52 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
 
                 <div className="h-px bg-white/10" />
 
+                {/* 自动刷新 Token */}
+                <div className="flex items-center justify-between">
+                  <div>
+                    <div className="font-medium text-slate-200">自动刷新 Token</div>
+                    <div className="text-xs text-slate-400">在 Token 过期前于后台刷新（当前使用中的账号由 Codex CLI 自行刷新）</div>
+                  </div>
+                  <label className="relative inline-flex items-center cursor-pointer">
+                    <input
+                      type="checkbox"
+                      className="sr-only peer"
+                      checked={autoRefresh.enabled}
+                      onChange={(e) => onUpdateSettings({ autoRefresh: { ...autoRefresh, enabled: e.target.checked } })}
+                    />
+                    <div className="w-11 h-6 bg-slate-700 peer-focus:outline-none peer-focus:ring-2 peer-focus:ring-primary-500/50 rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-primary-600" />
+                  </label>
+                </div>
+
+                {autoRefresh.enabled && (
+                  <div className="flex gap-4">
+                    <div>
+                      <label className="block text-sm font-medium text-slate-400 mb-2">提前 (分钟)</label>
+                      <input
+                        type="number"
+                        min="1"
+                        max="1440"
+                        value={autoRefresh.leadMinutes}
+                        onChange={(e) => onUpdateSettings({ autoRefresh: { ...autoRefresh, leadMinutes: parseInt(e.target.value) || 30 } })}
+                        className="input-glass w-32"
+                      />
+                    </div>
+                    <div>
+                      <label className="block text-sm font-medium text-slate-400 mb-2">随机抖动 (分钟)</label>
+                      <input
+                        type="number"
+                        min="0"
+                        max="120"
+                        value={autoRefresh.jitterMinutes}
+                        onChange={(e) => onUpdateSettings({ autoRefresh: { ...autoRefresh, jitterMinutes: Math.max(0, parseInt(e.target.value) || 0) } })}
+                        className="input-glass w-32"
+                      />
+                    </div>
+                  </div>
+                )}
+
+                <div className="h-px bg-white/10" />
+
                 {/* 智能调度 */}
                 <div className="flex items-center justify-between">
                   <div>
@@ -260,8 +528,68 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
                       className="input-glass w-32"
                     />
                     <p className="text-xs text-slate-500">例如填 5，则剩余≤5% 时切到下一账号</p>
+
+                    <label className="block text-sm font-medium text-slate-400 pt-2">备用账号选择策略</label>
+                    <select
+                      value={settings.strategy ?? DEFAULT_SETTINGS.strategy}
+                      onChange={(e) => onUpdateSettings({ strategy: e.target.value as SwitchStrategyKind })}
+                      className="input-glass w-full"
+                    >
+                      <option value="earliestReset">重置时间最早</option>
+                      <option value="mostRemaining">5 小时窗口剩余最多</option>
+                      <option value="roundRobin">按名称轮询</option>
+                      <option value="priority">自定义优先级</option>
+                    </select>
+
+                    {settings.strategy === 'priority' && (
+                      <>
+                        <textarea
+                          value={(settings.priority ?? []).join('\n')}
+                          onChange={(e) => onUpdateSettings({
+                            priority: e.target.value.split('\n').map(line => line.trim()).filter(Boolean),
+                          })}
+                          rows={4}
+                          placeholder="每行一个账号名称或邮箱，越靠前越优先"
+                          className="input-glass w-full font-mono text-xs"
+                        />
+                        <p className="text-xs text-slate-500">未列出的账号排在最后</p>
+                      </>
+                    )}
                   </div>
                 )}
+
+                <div className="h-px bg-white/10" />
+
+                {/* 网络 */}
+                <div className="space-y-2">
+                  <div>
+                    <div className="font-medium text-slate-200">网络代理</div>
+                    <div className="text-xs text-slate-400">用量查询、Token 刷新和云端同步共用；留空则使用系统环境变量 HTTPS_PROXY</div>
+                  </div>
+                  <input
+                    type="text"
+                    value={network.proxy ?? ''}
+                    onChange={(e) => updateNetwork({ proxy: e.target.value || null })}
+                    placeholder="http://127.0.0.1:7890 或 socks5h://127.0.0.1:1080"
+                    className="input-glass w-full font-mono text-xs"
+                  />
+                  <input
+                    type="text"
+                    value={network.noProxy ?? ''}
+                    onChange={(e) => updateNetwork({ noProxy: e.target.value || null })}
+                    placeholder="不走代理的主机，逗号分隔，如 localhost,.corp.example.com"
+                    className="input-glass w-full font-mono text-xs"
+                  />
+                  <label className="block text-sm font-medium text-slate-400 pt-2">自定义 CA 证书 (PEM)</label>
+                  <input
+                    type="text"
+                    value={network.caBundle ?? ''}
+                    onChange={(e) => updateNetwork({ caBundle: e.target.value || null })}
+                    placeholder="/etc/ssl/corp-ca.pem"
+                    className="input-glass w-full font-mono text-xs"
+                  />
+                  <p className="text-xs text-slate-500">企业代理会替换 TLS 证书时填写其根证书路径</p>
+                </div>
               </div>
             )}
 
@@ -290,11 +618,11 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
                   </GlassButton>
                 </div>
 
-                {/* WebDAV 配置 */}
+                {/* 云端同步配置 */}
                 <div className="space-y-4">
                   <div className="flex items-center justify-between">
                     <div>
-                      <div className="font-medium text-slate-200">☁️ WebDAV 云同步</div>
+                      <div className="font-medium text-slate-200">☁️ 云端同步</div>
                       <div className="text-xs text-slate-400">跨设备同步账号和配置</div>
                     </div>
                     <label className="relative inline-flex items-center cursor-pointer">
@@ -310,70 +638,238 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
 
                   {webdav.enabled && (
                     <div className="space-y-3 pl-1">
-                      {/* 配置说明 */}
-                      <div className="glass-light p-3 text-xs text-slate-400">
-                        <p className="font-medium text-slate-300 mb-2">📋 坚果云配置步骤：</p>
-                        <ol className="list-decimal list-inside space-y-1">
-                          <li>登录 <a href="https://www.jianguoyun.com" target="_blank" rel="noreferrer" className="text-primary-400 hover:underline">坚果云官网</a></li>
-                          <li>账户信息 → 安全选项 → 第三方应用管理</li>
-                          <li>添加应用密码 → 生成密码</li>
-                        </ol>
-                      </div>
-
                       <div>
-                        <label className="block text-xs font-medium text-slate-400 mb-1">服务器地址</label>
-                        <input
-                          type="text"
-                          value={webdav.url}
-                          onChange={(e) => updateWebdav({ url: e.target.value })}
-                          placeholder="https://dav.jianguoyun.com/dav/"
-                          className="input-glass text-sm"
-                        />
+                        <label className="block text-xs font-medium text-slate-400 mb-1">同步方式</label>
+                        <select
+                          value={backend}
+                          onChange={(e) => {
+                            updateWebdav({ backend: e.target.value as SyncBackendKind });
+                            setWebdavMessage(null);
+                          }}
+                          className="input-glass w-full text-sm"
+                        >
+                          <option value="webdav">WebDAV（坚果云、Nextcloud 等）</option>
+                          <option value="s3">S3 兼容存储（AWS S3、MinIO、R2 等）</option>
+                          <option value="folder">本地 / NAS 目录</option>
+                          <option value="git">git 仓库</option>
+                        </select>
                       </div>
 
-                      <div>
-                        <label className="block text-xs font-medium text-slate-400 mb-1">用户名</label>
-                        <input
-                          type="text"
-                          value={webdav.username}
-                          onChange={(e) => updateWebdav({ username: e.target.value })}
-                          placeholder="your@email.com"
-                          className="input-glass text-sm"
-                        />
-                      </div>
+                      {backend === 'webdav' && (
+                        <>
+                          {/* 配置说明 */}
+                          <div className="glass-light p-3 text-xs text-slate-400">
+                            <p className="font-medium text-slate-300 mb-2">📋 坚果云配置步骤：</p>
+                            <ol className="list-decimal list-inside space-y-1">
+                              <li>登录 <a href="https://www.jianguoyun.com" target="_blank" rel="noreferrer" className="text-primary-400 hover:underline">坚果云官网</a></li>
+                              <li>账户信息 → 安全选项 → 第三方应用管理</li>
+                              <li>添加应用密码 → 生成密码</li>
+                            </ol>
+                          </div>
 
-                      <div>
-                        <label className="block text-xs font-medium text-slate-400 mb-1">应用密码</label>
-                        <div className="flex gap-2">
-                          <input
-                            type={showWebdavPassword ? 'text' : 'password'}
-                            value={webdav.password}
-                            onChange={(e) => updateWebdav({ password: e.target.value })}
-                            placeholder="应用专用密码"
-                            className="input-glass text-sm flex-1"
-                          />
-                          <GlassButton variant="secondary" size="sm" onClick={() => setShowWebdavPassword((v) => !v)}>
-                            {showWebdavPassword ? '隐藏' : '显示'}
-                          </GlassButton>
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">服务器地址</label>
+                            <input
+                              type="text"
+                              value={webdav.url}
+                              onChange={(e) => updateWebdav({ url: e.target.value })}
+                              placeholder="https://dav.jianguoyun.com/dav/"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">用户名</label>
+                            <input
+                              type="text"
+                              value={webdav.username}
+                              onChange={(e) => updateWebdav({ username: e.target.value })}
+                              placeholder="your@email.com"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">应用密码</label>
+                            <div className="flex gap-2">
+                              <input
+                                type={showWebdavPassword ? 'text' : 'password'}
+                                value={webdav.password}
+                                onChange={(e) => updateWebdav({ password: e.target.value })}
+                                placeholder={savedSecretHint(SECRET_WEBDAV_PASSWORD, '应用专用密码')}
+                                className="input-glass text-sm flex-1"
+                              />
+                              <GlassButton variant="secondary" size="sm" onClick={() => setShowWebdavPassword((v) => !v)}>
+                                {showWebdavPassword ? '隐藏' : '显示'}
+                              </GlassButton>
+                            </div>
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">远程目录</label>
+                            <input
+                              type="text"
+                              value={webdav.remotePath}
+                              onChange={(e) => updateWebdav({ remotePath: e.target.value })}
+                              placeholder="/codex-manager/"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+                        </>
+                      )}
+
+                      {backend === 's3' && (
+                        <>
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">服务地址</label>
+                            <input
+                              type="text"
+                              value={s3.endpoint}
+                              onChange={(e) => updateS3({ endpoint: e.target.value })}
+                              placeholder="https://s3.us-east-1.amazonaws.com"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <div className="grid grid-cols-2 gap-2">
+                            <div>
+                              <label className="block text-xs font-medium text-slate-400 mb-1">Bucket</label>
+                              <input
+                                type="text"
+                                value={s3.bucket}
+                                onChange={(e) => updateS3({ bucket: e.target.value })}
+                                placeholder="codex-sync"
+                                className="input-glass text-sm"
+                              />
+                            </div>
+                            <div>
+                              <label className="block text-xs font-medium text-slate-400 mb-1">Region</label>
+                              <input
+                                type="text"
+                                value={s3.region}
+                                onChange={(e) => updateS3({ region: e.target.value })}
+                                placeholder="us-east-1"
+                                className="input-glass text-sm"
+                              />
+                            </div>
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">Access Key ID</label>
+                            <input
+                              type="text"
+                              value={s3.accessKeyId}
+                              onChange={(e) => updateS3({ accessKeyId: e.target.value })}
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">Secret Access Key</label>
+                            <div className="flex gap-2">
+                              <input
+                                type={showWebdavPassword ? 'text' : 'password'}
+                                value={s3.secretAccessKey}
+                                onChange={(e) => updateS3({ secretAccessKey: e.target.value })}
+                                placeholder={savedSecretHint(SECRET_S3_SECRET_ACCESS_KEY, '')}
+                                className="input-glass text-sm flex-1"
+                              />
+                              <GlassButton variant="secondary" size="sm" onClick={() => setShowWebdavPassword((v) => !v)}>
+                                {showWebdavPassword ? '隐藏' : '显示'}
+                              </GlassButton>
+                            </div>
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">对象前缀</label>
+                            <input
+                              type="text"
+                              value={s3.prefix}
+                              onChange={(e) => updateS3({ prefix: e.target.value })}
+                              placeholder="codex-manager/"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <label className="flex items-center gap-3 text-sm text-slate-300 cursor-pointer">
+                            <input
+                              type="checkbox"
+                              checked={s3.pathStyle}
+                              onChange={(e) => updateS3({ pathStyle: e.target.checked })}
+                              className="w-4 h-4 rounded border-slate-600 text-primary-500 focus:ring-primary-500 focus:ring-offset-0 bg-slate-700"
+                            />
+                            路径风格地址（MinIO 等自建服务请保持勾选）
+                          </label>
+                        </>
+                      )}
+
+                      {backend === 'folder' && (
+                        <div>
+                          <label className="block text-xs font-medium text-slate-400 mb-1">同步目录</label>
+                          <div className="flex gap-2">
+                            <input
+                              type="text"
+                              value={webdav.folderPath ?? ''}
+                              onChange={(e) => updateWebdav({ folderPath: e.target.value })}
+                              placeholder="/Volumes/NAS/codex-manager"
+                              className="input-glass text-sm flex-1"
+                            />
+                            <GlassButton
+                              variant="secondary"
+                              size="sm"
+                              onClick={handleBrowseSyncFolder}
+                            >
+                              浏览
+                            </GlassButton>
+                          </div>
+                          <p className="text-xs text-slate-500 mt-1">可以是网络共享或网盘客户端的目录，目录需已存在</p>
                         </div>
-                      </div>
+                      )}
+
+                      {backend === 'git' && (
+                        <>
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">仓库地址</label>
+                            <input
+                              type="text"
+                              value={git.url}
+                              onChange={(e) => updateGit({ url: e.target.value })}
+                              placeholder="git@github.com:me/codex-sync.git"
+                              className="input-glass text-sm"
+                            />
+                          </div>
+
+                          <div>
+                            <label className="block text-xs font-medium text-slate-400 mb-1">分支</label>
+                            <input
+                              type="text"
+                              value={git.branch}
+                              onChange={(e) => updateGit({ branch: e.target.value })}
+                              placeholder="main"
+                              className="input-glass text-sm"
+                            />
+                            <p className="text-xs text-slate-500 mt-1">使用本机 git 的 SSH 密钥或凭据管理器认证，建议使用私有仓库</p>
+                          </div>
+                        </>
+                      )}
 
                       <div>
-                        <label className="block text-xs font-medium text-slate-400 mb-1">远程目录</label>
+                        <label className="block text-xs font-medium text-slate-400 mb-1">同步口令</label>
                         <input
-                          type="text"
-                          value={webdav.remotePath}
-                          onChange={(e) => updateWebdav({ remotePath: e.target.value })}
-                          placeholder="/codex-manager/"
+                          type="password"
+                          value={webdav.syncPassphrase ?? ''}
+                          onChange={(e) => updateWebdav({ syncPassphrase: e.target.value })}
+                          placeholder={savedSecretHint(SECRET_SYNC_PASSPHRASE, '所有设备使用同一口令')}
                           className="input-glass text-sm"
                         />
+                        <p className="text-xs text-slate-500 mt-1">账号文件在本机加密后再上传，网盘只能看到密文</p>
                       </div>
 
                       <GlassButton
                         variant="secondary"
                         size="sm"
                         onClick={handleTestConnection}
-                        disabled={webdavTesting || !webdav.username || !webdav.password}
+                        disabled={webdavTesting || !canTestConnection}
                         loading={webdavTesting}
                       >
                         测试连接
@@ -471,12 +967,113 @@ export function SettingsDialog({ isOpen, onClose, settings, onUpdateSettings }:
                       </label>
                     </div>
 
+                    {/* 单文件大小上限 */}
+                    <div>
+                      <label className="block text-sm font-medium text-slate-400 mb-2">单个文件大小上限 (MB，0 表示不限制)</label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={1024}
+                        value={sync.maxFileSizeMb ?? 20}
+                        onChange={(e) => updateSync({ maxFileSizeMb: Math.min(1024, Math.max(0, parseInt(e.target.value) || 0)) })}
+                        className="input-glass w-32"
+                      />
+                      <p className="text-xs text-slate-500 mt-1">超过上限的 Skills 资源、Prompts 等文件会跳过并在同步结果中列出</p>
+                    </div>
+
+                    <div>
+                      <label className="block text-sm font-medium text-slate-400 mb-2">回收站保留天数 (0 表示一直保留)</label>
+                      <input
+                        type="number"
+                        min={0}
+                        max={3650}
+                        value={webdav.trashRetentionDays ?? 30}
+                        onChange={(e) => updateWebdav({ trashRetentionDays: Math.min(3650, Math.max(0, parseInt(e.target.value) || 0)) })}
+                        className="input-glass w-32"
+                      />
+                      <p className="text-xs text-slate-500 mt-1">删除或重命名的账号、Prompts、Skills 会同步到其他设备，远程副本先移入回收站</p>
+                    </div>
+
+                    <div className="h-px bg-white/10" />
+
+                    {/* 后台自动同步 */}
+                    <div className="space-y-3">
+                      <div className="flex items-center justify-between">
+                        <div>
+                          <div className="font-medium text-slate-200">后台自动同步</div>
+                          <div className="text-xs text-slate-400">窗口隐藏到托盘后仍会定时同步，网络错误时逐步延长重试间隔</div>
+                        </div>
+                        <label className="relative inline-flex items-center cursor-pointer">
+                          <input
+                            type="checkbox"
+                            className="sr-only peer"
+                            checked={!!sync.autoSync}
+                            onChange={(e) => updateSync({ autoSync: e.target.checked })}
+                          />
+                          <div className="w-11 h-6 bg-slate-700 peer-focus:outline-none peer-focus:ring-2 peer-focus:ring-primary-500/50 rounded-full peer peer-checked:after:translate-x-full peer-checked:after:border-white after:content-[''] after:absolute after:top-[2px] after:left-[2px] after:bg-white after:border-gray-300 after:border after:rounded-full after:h-5 after:w-5 after:transition-all peer-checked:bg-primary-600" />
+                        </label>
+                      </div>
+
+                      {sync.autoSync && (
+                        <>
+                          <div>
+                            <label className="block text-sm font-medium text-slate-400 mb-2">同步间隔 (分钟)</label>
+                            <input
+                              type="number"
+                              min={5}
+                              max={1440}
+                              value={sync.autoSyncInterval ?? 30}
+                              onChange={(e) => updateSync({ autoSyncInterval: Math.min(1440, Math.max(5, parseInt(e.target.value) || 30)) })}
+                              className="input-glass w-32"
+                            />
+                          </div>
+
+                          <label className="flex items-center gap-3 text-sm text-slate-300 cursor-pointer">
+                            <input
+                              type="checkbox"
+                              checked={sync.autoSyncOnChange ?? true}
+                              onChange={(e) => updateSync({ autoSyncOnChange: e.target.checked })}
+                              className="w-4 h-4 rounded border-slate-600 text-primary-500 focus:ring-primary-500 focus:ring-offset-0 bg-slate-700"
+                            />
+                            账号文件变化时立即同步
+                          </label>
+
+                          <div>
+                            <label className="block text-sm font-medium text-slate-400 mb-2">Codex 配置</label>
+                            <select
+                              value={sync.codexAutoSync ?? 'off'}
+                              onChange={(e) => updateSync({ codexAutoSync: e.target.value as CodexDirection })}
+                              className="input-glass w-full"
+                            >
+                              <option value="off">不自动同步</option>
+                              <option value="upload">上传（以本机为准）</option>
+                              <option value="download">下载（以云端为准）</option>
+                            </select>
+                            <p className="text-xs text-slate-500 mt-1">账号文件双向同步；Codex 配置没有合并，请只让一台设备上传</p>
+                          </div>
+                        </>
+                      )}
+                    </div>
+
                     {/* 上次同步时间 */}
-                    <div className="glass-light p-3 rounded-lg">
+                    <div className="glass-light p-3 rounded-lg space-y-1">
                       <div className="flex items-center justify-between">
                         <span className="text-xs text-slate-400">上次同步</span>
-                        <span className="text-xs text-slate-300">{formatLastSyncTime(sync.lastSyncTime)}</span>
+                        <span className="text-xs text-slate-300">
+                          {formatLastSyncTime(Math.max(sync.lastSyncTime ?? 0, syncStatus?.lastSync ?? 0) || undefined)}
+                        </span>
                       </div>
+                      {sync.autoSync && syncStatus && (
+                        <div className="flex items-center justify-between gap-3">
+                          <span className="text-xs text-slate-400 shrink-0">自动同步</span>
+                          <span
+                            className={`text-xs truncate ${syncStatus.phase === 'failed' ? 'text-rose-400' : syncStatus.phase === 'partial' ? 'text-amber-400' : 'text-slate-300'}`}
+                            title={syncStatus.message}
+                          >
+                            {formatSyncStatus(syncStatus)}
+                          </span>
+                        </div>
+                      )}
                     </div>
                   </>
                 )}
@@ -1,6 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { SkillInfo } from '../types';
+import { SkillInfo, errorMessage } from '../types';
 import { motion, AnimatePresence } from 'framer-motion';
 import ReactMarkdown from 'react-markdown';
 import remarkGfm from 'remark-gfm';
@@ -105,7 +105,7 @@ export function SkillsPanel({ onBack }: SkillsPanelProps) {
       await loadSkills();
     } catch (error) {
       console.error('创建失败:', error);
-      alert(String(error));
+      alert(errorMessage(error));
     }
   };
 
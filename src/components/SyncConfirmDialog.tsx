@@ -1,7 +1,7 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { motion, AnimatePresence } from 'framer-motion';
-import { WebDavConfig, SyncSettings, SyncResult, DEFAULT_SYNC_SETTINGS } from '../types';
+import { WebDavConfig, SyncSettings, SyncResult, DEFAULT_SYNC_SETTINGS, errorMessage } from '../types';
 import { GlassButton } from './ui';
 
 interface SyncConfirmDialogProps {
@@ -10,6 +10,7 @@ interface SyncConfirmDialogProps {
   webdavConfig: WebDavConfig;
   syncSettings: SyncSettings;
   onSyncComplete: (lastSyncTime: number) => void;
+  onSaveSyncTarget: () => Promise<{ success: boolean; message?: string }>;
 }
 
 const overlayVariants = {
@@ -23,15 +24,18 @@ const dialogVariants = {
   exit: { opacity: 0, scale: 0.95, y: 10, transition: { duration: 0.1 } },
 };
 
+type SyncDirection = 'upload' | 'download' | 'both';
+
 export function SyncConfirmDialog({ 
   isOpen, 
   onClose, 
   webdavConfig, 
   syncSettings,
-  onSyncComplete 
+  onSyncComplete,
+  onSaveSyncTarget,
 }: SyncConfirmDialogProps) {
   const [syncing, setSyncing] = useState(false);
-  const [syncDirection, setSyncDirection] = useState<'upload' | 'download' | null>(null);
+  const [syncDirection, setSyncDirection] = useState<SyncDirection | null>(null);
   const [syncResult, setSyncResult] = useState<SyncResult | null>(null);
 
   const sync = syncSettings || DEFAULT_SYNC_SETTINGS;
@@ -54,12 +58,12 @@ export function SyncConfirmDialog({
     return items;
   };
 
-  const handleSync = async (direction: 'upload' | 'download') => {
+  const handleSync = async (direction: SyncDirection) => {
     if (!webdavConfig.enabled) {
       setSyncResult({
         uploaded: [],
         downloaded: [],
-        errors: ['请先在设置中配置并启用 WebDAV'],
+        errors: ['请先在设置中配置并启用云端同步'],
       });
       return;
     }
@@ -69,45 +73,57 @@ export function SyncConfirmDialog({
     setSyncResult(null);
 
     try {
-      const config = {
-        url: webdavConfig.url,
-        username: webdavConfig.username,
-        password: webdavConfig.password,
-        remotePath: webdavConfig.remotePath,
-      };
+      // 设置中刚修改的同步目标可能还未保存到后端
+      const saved = await onSaveSyncTarget();
+      if (!saved.success) throw saved.message;
 
       const syncConfig = {
         syncPrompts: sync.syncPrompts,
         syncSkills: sync.syncSkills,
         syncAgentsMd: sync.syncAgentsMd,
         syncConfigToml: sync.syncConfigToml,
+        maxFileSizeMb: sync.maxFileSizeMb ?? 20,
       };
 
-      let result: SyncResult = { uploaded: [], downloaded: [], errors: [] };
+      let result: SyncResult = { uploaded: [], downloaded: [], errors: [], migrated: [], conflicts: [], skipped: [], deleted: [] };
+
+      const mergeAccounts = (accountResult: SyncResult) => {
+        result.uploaded.push(...accountResult.uploaded.map(f => `账号: ${f}`));
+        result.downloaded.push(...accountResult.downloaded.map(f => `账号: ${f}`));
+        result.migrated!.push(...(accountResult.migrated ?? []).map(f => `账号: ${f}`));
+        result.deleted!.push(...(accountResult.deleted ?? []).map(f => `账号: ${f}`));
+        result.conflicts!.push(...(accountResult.conflicts ?? []));
+        result.errors.push(...accountResult.errors);
+      };
 
-      if (direction === 'upload') {
+      if (direction === 'both') {
+        // 双向同步只针对账号文件，两端都修改时取 Token 较新的一方
+        mergeAccounts(await invoke<SyncResult>('sync_accounts', { direction: 'both' }));
+      } else if (direction === 'upload') {
         // 上传 Codex 配置
-        const codexResult = await invoke<SyncResult>('webdav_sync_codex_upload', { config, syncConfig });
+        const codexResult = await invoke<SyncResult>('sync_codex_upload', { syncConfig });
         result.uploaded.push(...codexResult.uploaded);
+        result.migrated!.push(...(codexResult.migrated ?? []));
+        result.skipped!.push(...(codexResult.skipped ?? []));
+        result.deleted!.push(...(codexResult.deleted ?? []));
         result.errors.push(...codexResult.errors);
 
         // 上传账号文件
         if (sync.syncAccounts) {
-          const accountResult = await invoke<SyncResult>('webdav_sync_upload', { config });
-          result.uploaded.push(...accountResult.uploaded.map(f => `账号: ${f}`));
-          result.errors.push(...accountResult.errors);
+          mergeAccounts(await invoke<SyncResult>('sync_accounts', { direction: 'upload' }));
         }
       } else {
         // 下载 Codex 配置
-        const codexResult = await invoke<SyncResult>('webdav_sync_codex_download', { config, syncConfig });
+        const codexResult = await invoke<SyncResult>('sync_codex_download', { syncConfig });
         result.downloaded.push(...codexResult.downloaded);
+        result.migrated!.push(...(codexResult.migrated ?? []));
+        result.skipped!.push(...(codexResult.skipped ?? []));
+        result.deleted!.push(...(codexResult.deleted ?? []));
         result.errors.push(...codexResult.errors);
 
         // 下载账号文件
         if (sync.syncAccounts) {
-          const accountResult = await invoke<SyncResult>('webdav_sync_download', { config });
-          result.downloaded.push(...accountResult.downloaded.map(f => `账号: ${f}`));
-          result.errors.push(...accountResult.errors);
+          mergeAccounts(await invoke<SyncResult>('sync_accounts', { direction: 'download' }));
         }
       }
 
@@ -121,7 +137,7 @@ export function SyncConfirmDialog({
       setSyncResult({
         uploaded: [],
         downloaded: [],
-        errors: [String(error)],
+        errors: [errorMessage(error)],
       });
     } finally {
       setSyncing(false);
@@ -199,6 +215,50 @@ export function SyncConfirmDialog({
                   </div>
                 )}
 
+                {syncResult.migrated && syncResult.migrated.length > 0 && (
+                  <div className="text-xs">
+                    <div className="text-slate-400 mb-1">已迁移旧版远程文件 ({syncResult.migrated.length})</div>
+                    <div className="text-slate-300 max-h-24 overflow-y-auto custom-scrollbar glass-light p-2 rounded-lg">
+                      {syncResult.migrated.map((item, i) => (
+                        <div key={i} className="truncate">{item}</div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
+                {syncResult.conflicts && syncResult.conflicts.length > 0 && (
+                  <div className="text-xs">
+                    <div className="text-amber-400 mb-1">冲突，两端均未改动 ({syncResult.conflicts.length})</div>
+                    <div className="text-amber-300 max-h-24 overflow-y-auto custom-scrollbar glass-light p-2 rounded-lg">
+                      {syncResult.conflicts.map((item, i) => (
+                        <div key={i} className="truncate" title={item}>{item}</div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
+                {syncResult.deleted && syncResult.deleted.length > 0 && (
+                  <div className="text-xs">
+                    <div className="text-slate-400 mb-1">已删除 ({syncResult.deleted.length})</div>
+                    <div className="text-slate-300 max-h-24 overflow-y-auto custom-scrollbar glass-light p-2 rounded-lg">
+                      {syncResult.deleted.map((item, i) => (
+                        <div key={i} className="truncate" title={item}>{item}</div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
+                {syncResult.skipped && syncResult.skipped.length > 0 && (
+                  <div className="text-xs">
+                    <div className="text-slate-400 mb-1">已跳过 ({syncResult.skipped.length})</div>
+                    <div className="text-slate-300 max-h-24 overflow-y-auto custom-scrollbar glass-light p-2 rounded-lg">
+                      {syncResult.skipped.map((item, i) => (
+                        <div key={i} className="truncate" title={item}>{item}</div>
+                      ))}
+                    </div>
+                  </div>
+                )}
+
                 {syncResult.errors.length > 0 && (
                   <div className="text-xs">
                     <div className="text-rose-400 mb-1">错误</div>
@@ -210,7 +270,7 @@ export function SyncConfirmDialog({
                   </div>
                 )}
 
-                {syncResult.uploaded.length === 0 && syncResult.downloaded.length === 0 && syncResult.errors.length === 0 && (
+                {syncResult.uploaded.length === 0 && syncResult.downloaded.length === 0 && syncResult.errors.length === 0 && !syncResult.conflicts?.length && !syncResult.skipped?.length && !syncResult.deleted?.length && (
                   <div className="text-xs text-slate-400 text-center py-2">没有需要同步的内容</div>
                 )}
 
@@ -220,11 +280,11 @@ export function SyncConfirmDialog({
               </div>
             ) : (
               <>
-                {/* WebDAV 状态 */}
+                {/* 云端同步状态 */}
                 {!webdavConfig.enabled && (
                   <div className="p-3 rounded-xl bg-amber-500/10 border border-amber-500/30 mb-4">
                     <div className="text-sm text-amber-400">
-                      ⚠ WebDAV 未配置，请先在设置中配置
+                      ⚠ 云端同步未配置，请先在设置中配置
                     </div>
                   </div>
                 )}
@@ -279,8 +339,25 @@ export function SyncConfirmDialog({
                   </GlassButton>
                 </div>
 
+                {sync.syncAccounts && (
+                  <GlassButton
+                    variant="secondary"
+                    className="w-full mt-2"
+                    onClick={() => handleSync('both')}
+                    disabled={syncing || !webdavConfig.enabled}
+                    loading={syncing && syncDirection === 'both'}
+                    icon={
+                      <svg className="w-4 h-4" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+                        <path strokeLinecap="round" strokeLinejoin="round" strokeWidth={2} d="M4 4v5h.582m15.356 2A8.001 8.001 0 004.582 9m0 0H9m11 11v-5h-.581m0 0a8.003 8.003 0 01-15.357-2m15.357 2H15" />
+                      </svg>
+                    }
+                  >
+                    双向同步账号
+                  </GlassButton>
+                )}
+
                 <p className="text-xs text-slate-500 text-center mt-3">
-                  上传会覆盖云端，下载会覆盖本地
+                  账号文件只会用较新的 Token 覆盖较旧的一方，两端都修改且无法判断时会列为冲突；Codex 配置上传会覆盖云端，下载会覆盖本地
                 </p>
               </>
             )}
@@ -1,11 +1,22 @@
-import { useState, useEffect, useCallback, useMemo } from 'react';
-import { AccountInfo, ScanResult, UsageInfo, AppSettings, DEFAULT_SETTINGS } from '../types';
+import { useState, useEffect, useCallback, useRef } from 'react';
+import { AccountInfo, ScanResult, AppSettings, AppConfig, AutoSwitchConfig, UsageUpdate, SwitchDecision, VaultStatus, SyncStatus, SavedSyncTarget, UsageHistory, DEFAULT_SETTINGS, errorMessage, toAutoSyncConfig, toSyncTarget, fromSyncTarget, stripSecrets, hasSecrets } from '../types';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+
+// 这些设置由后端 AppConfig 保存，后台任务据此定时检查和自动切换
+const AUTO_SWITCH_KEYS: (keyof AutoSwitchConfig)[] = ['autoCheck', 'checkInterval', 'enableAutoSwitch', 'autoSwitchThreshold', 'strategy', 'priority'];
+
+// 同步凭据由后端保存在系统钥匙串，localStorage 中只保留其余参数
+const persistSettings = (settings: AppSettings) => {
+    const stored = settings.webdav ? { ...settings, webdav: stripSecrets(settings.webdav) } : settings;
+    localStorage.setItem('codex_manager_settings', JSON.stringify(stored));
+};
 
 export function useAccounts() {
     const [accounts, setAccounts] = useState<AccountInfo[]>([]);
     const [accountsDir, setAccountsDirState] = useState<string>('');
     const [loading, setLoading] = useState(true);
+    const [vault, setVault] = useState<VaultStatus>({ enabled: false, unlocked: false });
 
     const [settings, setSettings] = useState<AppSettings>(() => {
         try {
@@ -16,16 +27,105 @@ export function useAccounts() {
         }
     });
 
+    // 上次推送给后端的自动同步配置，只在内容变化时重新推送（推送会触发一次同步）；
+    // 输入连接参数时稍作等待，避免每输入一个字符都触发同步
+    const autoSyncPushed = useRef<string | null>(null);
+    const autoSyncTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+
+    // 同步目标同样稍作等待后保存到后端；测试连接或同步前可立即保存
+    const [savedSyncTarget, setSavedSyncTarget] = useState<SavedSyncTarget | null>(null);
+    const syncTargetTimer = useRef<ReturnType<typeof setTimeout> | null>(null);
+    const pendingSyncTarget = useRef<AppSettings['webdav'] | null>(null);
+
+    const pushSyncTarget = useCallback(async () => {
+        if (syncTargetTimer.current) clearTimeout(syncTargetTimer.current);
+        syncTargetTimer.current = null;
+        const webdav = pendingSyncTarget.current;
+        pendingSyncTarget.current = null;
+        if (!webdav) return;
+        const saved = await invoke<SavedSyncTarget>('save_sync_target', { target: toSyncTarget(webdav) });
+        setSavedSyncTarget(saved);
+    }, []);
+
+    const saveSyncTarget = useCallback(async () => {
+        try {
+            await pushSyncTarget();
+            return { success: true };
+        } catch (error) {
+            return { success: false, message: errorMessage(error) };
+        }
+    }, [pushSyncTarget]);
+
     const updateSettings = useCallback((newSettings: Partial<AppSettings>) => {
         setSettings(prev => {
             const next = { ...prev, ...newSettings };
-            localStorage.setItem('codex_manager_settings', JSON.stringify(next));
+            persistSettings(next);
+            if (AUTO_SWITCH_KEYS.some(key => key in newSettings)) {
+                const config: AutoSwitchConfig = {
+                    autoCheck: next.autoCheck,
+                    checkInterval: next.checkInterval,
+                    enableAutoSwitch: next.enableAutoSwitch,
+                    autoSwitchThreshold: next.autoSwitchThreshold,
+                    strategy: next.strategy ?? DEFAULT_SETTINGS.strategy,
+                    priority: next.priority ?? DEFAULT_SETTINGS.priority,
+                };
+                invoke('set_auto_switch_config', { config }).catch(error => {
+                    console.error('保存自动切换设置失败:', error);
+                });
+            }
+            if (newSettings.webdav) {
+                pendingSyncTarget.current = newSettings.webdav;
+                if (syncTargetTimer.current) clearTimeout(syncTargetTimer.current);
+                syncTargetTimer.current = setTimeout(() => {
+                    pushSyncTarget().catch(error => {
+                        console.error('保存同步目标失败:', error);
+                    });
+                }, 1000);
+            }
+            if (newSettings.autoRefresh) {
+                invoke('set_auto_refresh_config', { config: newSettings.autoRefresh }).catch(error => {
+                    console.error('保存 Token 刷新设置失败:', error);
+                });
+            }
+            if (newSettings.locale) {
+                invoke('set_locale', { locale: newSettings.locale }).catch(error => {
+                    console.error('保存语言设置失败:', error);
+                });
+            }
+            if (newSettings.network) {
+                invoke('set_network_config', { config: newSettings.network }).catch(error => {
+                    console.error('保存网络设置失败:', error);
+                });
+            }
+            if ('webdav' in newSettings || 'sync' in newSettings) {
+                const config = toAutoSyncConfig(next);
+                const serialized = JSON.stringify(config);
+                if (autoSyncTimer.current) clearTimeout(autoSyncTimer.current);
+                autoSyncTimer.current = setTimeout(() => {
+                    if (serialized === autoSyncPushed.current) return;
+                    autoSyncPushed.current = serialized;
+                    invoke('set_auto_sync_config', { config }).catch(error => {
+                        console.error('保存自动同步设置失败:', error);
+                    });
+                }, 1000);
+            }
             return next;
         });
-    }, []);
-
-    const fetchUsage = useCallback(async (filePath: string): Promise<UsageInfo> => {
-        return await invoke<UsageInfo>('fetch_usage', { filePath });
+    }, [pushSyncTarget]);
+
+    // 合并后端推送的用量结果（批量查询时每个账号查完推送一次）
+    const applyUsageUpdates = useCallback((updates: UsageUpdate[]) => {
+        const byPath = new Map(updates.map(u => [u.filePath, u]));
+        setAccounts(prev => prev.map(acc => {
+            const update = byPath.get(acc.filePath);
+            if (!update) return acc;
+            return {
+                ...acc,
+                usage: update.usage,
+                lastUsageUpdate: Date.now(),
+                isTokenExpired: update.isTokenExpired || !!acc.refreshFailure,
+            };
+        }));
     }, []);
 
     const refresh = useCallback(async () => {
@@ -34,45 +134,45 @@ export function useAccounts() {
             const result = await invoke<ScanResult>('scan_accounts');
 
             setAccountsDirState(result.accountsDir);
-
-            const accountsWithUsage = await Promise.all(
-                result.accounts.map(async (account) => {
-                    try {
-                        const usage = await fetchUsage(account.filePath);
-                        return {
-                            ...account,
-                            usage,
-                            lastUsageUpdate: Date.now(),
-                            isTokenExpired: false
-                        };
-                    } catch (error: any) {
-                        const errStr = error.toString();
-                        const isExpired = errStr.includes('401') || errStr.includes('403') || errStr.includes('Status: 401') || errStr.includes('Status: 403');
-                        return {
-                            ...account,
-                            usage: undefined,
-                            lastUsageUpdate: Date.now(),
-                            isTokenExpired: isExpired
-                        };
-                    }
-                })
-            );
-
-            // 排序：优先显示激活的账号，其次按名称
-            accountsWithUsage.sort((a, b) => {
-                if (a.isActive && !b.isActive) return -1;
-                if (!a.isActive && b.isActive) return 1;
-                return a.name.localeCompare(b.name, 'zh-CN');
+            setVault(result.vault);
+
+            // 先显示账号列表（沿用上次的用量），用量由 fetch_all_usage 并发查询后逐个填入
+            setAccounts(prev => {
+                const previous = new Map(prev.map(acc => [acc.filePath, acc]));
+                const next = result.accounts.map(account => {
+                    const old = previous.get(account.filePath);
+                    return {
+                        ...account,
+                        usage: old?.usage,
+                        lastUsageUpdate: old?.lastUsageUpdate,
+                        forecast: old?.forecast,
+                        isTokenExpired: !!old?.isTokenExpired || !!account.refreshFailure,
+                    };
+                });
+
+                // 排序：优先显示激活的账号，其次按名称
+                next.sort((a, b) => {
+                    if (a.isActive && !b.isActive) return -1;
+                    if (!a.isActive && b.isActive) return 1;
+                    return a.name.localeCompare(b.name, 'zh-CN');
+                });
+                return next;
             });
 
-            setAccounts(accountsWithUsage);
+            const updates = await invoke<UsageUpdate[]>('fetch_all_usage');
+            applyUsageUpdates(updates);
+
+            // 用满时间预测基于本地记录的用量历史，days 为 0 时只返回预测
+            const histories = await invoke<UsageHistory[]>('get_usage_history', { days: 0 });
+            const forecasts = new Map(histories.map(h => [h.filePath, h.forecast]));
+            setAccounts(prev => prev.map(acc => ({ ...acc, forecast: forecasts.get(acc.filePath) })));
 
         } catch (error) {
             console.error('扫描账号失败:', error);
         } finally {
             setLoading(false);
         }
-    }, [fetchUsage]);
+    }, [applyUsageUpdates]);
 
     const switchAccount = useCallback(async (filePath: string) => {
         try {
@@ -87,93 +187,88 @@ export function useAccounts() {
 
             await refresh(); // 刷新状态
             return { success: true, message: '已切换账号' };
-        } catch (error: any) {
-            return { success: false, message: error.toString() };
+        } catch (error) {
+            return { success: false, message: errorMessage(error) };
         }
     }, [refresh]);
 
-    const checkAutoSwitch = useCallback(async (currentAccounts: AccountInfo[]) => {
-        if (!settings.enableAutoSwitch) return;
-
-        const activeAccount = currentAccounts.find(a => a.isActive);
-        if (!activeAccount) return;
-
-        const threshold = Math.max(1, Math.min(50, settings.autoSwitchThreshold || DEFAULT_SETTINGS.autoSwitchThreshold));
-        const usedPercentLimit = 100 - threshold;
-
-        // 判断当前账号是否需要切换
-        const isExpired = activeAccount.isTokenExpired;
-        const isPrimaryFull = (activeAccount.usage?.primaryWindow?.usedPercent || 0) >= usedPercentLimit;
-        const isSecondaryFull = (activeAccount.usage?.secondaryWindow?.usedPercent || 0) >= usedPercentLimit;
-
-        if (isExpired || isPrimaryFull || isSecondaryFull) {
-            // 筛选候选账号
-            const candidates = currentAccounts.filter(acc => {
-                if (acc.isActive) return false; // 排除自己
-                if (acc.isTokenExpired) return false; // 排除过期
-                if ((acc.usage?.primaryWindow?.usedPercent || 0) >= usedPercentLimit) return false; // 排除满额
-                if ((acc.usage?.secondaryWindow?.usedPercent || 0) >= usedPercentLimit) return false; // 排除满额
-                return true;
-            });
-
-            if (candidates.length === 0) return;
-
-            // 排序算法：周重置时间最早 (ResetsAt Smallest) 的账号
-            candidates.sort((a, b) => {
-                const resetA = a.usage?.secondaryWindow?.resetsAt || Number.MAX_SAFE_INTEGER;
-                const resetB = b.usage?.secondaryWindow?.resetsAt || Number.MAX_SAFE_INTEGER;
-                return resetA - resetB;
-            });
+    // 最佳候选账号由后端按配置的切换策略计算
+    const [bestCandidate, setBestCandidate] = useState<SwitchDecision | null>(null);
 
-            const bestAccount = candidates[0];
-            // 执行切换
-            await switchAccount(bestAccount.filePath);
+    useEffect(() => {
+        if (accounts.length === 0) {
+            setBestCandidate(null);
+            return;
         }
-    }, [settings.enableAutoSwitch, switchAccount]);
-
-    // 计算最佳候选账号 (Best Candidate)
-    const bestCandidateId = useMemo(() => {
-        const candidates = accounts.filter(acc => {
-            // if (acc.isActive) return false; // Don't exclude active. If active is best, we shouldn't show badge on others.
-            if (acc.isTokenExpired) return false;
-            // Strict usage check. If current account is active but near full, maybe it shouldn't be best?
-            // Actually, if it's active and functioning, it's valid.
-            // But if it's > 99%, we probably shouldn't count it as "Best" available.
-            if ((acc.usage?.primaryWindow?.usedPercent || 0) >= 99) return false;
-            if ((acc.usage?.secondaryWindow?.usedPercent || 0) >= 99) return false;
-            return true;
-        });
-
-        if (candidates.length === 0) return null;
-
-        // 排序：仅依据周重置时间最早 (ResetsAt Smallest)
-        candidates.sort((a, b) => {
-            const resetA = a.usage?.secondaryWindow?.resetsAt || Number.MAX_SAFE_INTEGER;
-            const resetB = b.usage?.secondaryWindow?.resetsAt || Number.MAX_SAFE_INTEGER;
-            return resetA - resetB;
-        });
-
-        return candidates[0].id;
-    }, [accounts]);
+        const usages: UsageUpdate[] = accounts.map(acc => ({
+            filePath: acc.filePath,
+            usage: acc.usage,
+            isTokenExpired: !!acc.isTokenExpired,
+        }));
+        invoke<SwitchDecision | null>('preview_switch_candidate', { usages })
+            .then(setBestCandidate)
+            .catch(() => setBestCandidate(null));
+    }, [accounts, settings.strategy, settings.priority, settings.autoSwitchThreshold]);
 
     useEffect(() => {
         refresh();
+        // 自动切换设置以后端为准
+        invoke<AppConfig>('get_app_config')
+            .then(config => {
+                setSettings(prev => ({ ...prev, ...config.auto_switch, autoRefresh: config.auto_refresh ?? prev.autoRefresh, network: config.network ?? prev.network, locale: config.locale ?? prev.locale }));
+                if (config.auto_sync) autoSyncPushed.current = JSON.stringify(config.auto_sync);
+            })
+            .catch(error => console.error('读取应用配置失败:', error));
+        // 同步目标以后端为准；旧版本保存在 localStorage 中的凭据先移交给后端
+        const local = settings.webdav;
+        const migrate = local && hasSecrets(local)
+            ? invoke<SavedSyncTarget>('save_sync_target', { target: toSyncTarget(local) })
+            : invoke<SavedSyncTarget>('get_sync_target');
+        migrate
+            .then(saved => {
+                setSavedSyncTarget(saved);
+                setSettings(prev => {
+                    const base = prev.webdav ?? { ...DEFAULT_SETTINGS.webdav!, enabled: true };
+                    const next = saved.target ? { ...prev, webdav: fromSyncTarget(saved.target, base) } : prev;
+                    persistSettings(next);
+                    return next;
+                });
+            })
+            .catch(error => console.error('读取同步目标失败:', error));
         // eslint-disable-next-line react-hooks/exhaustive-deps
     }, []);
 
+    // 后台任务定时推送用量，窗口隐藏到托盘时也会继续检查
     useEffect(() => {
-        if (!settings.autoCheck || settings.checkInterval <= 0) return;
-        const intervalId = setInterval(() => {
+        const unlistenUsage = listen<UsageUpdate[]>('usage-updated', (event) => {
+            applyUsageUpdates(event.payload);
+        });
+        const unlistenSwitch = listen('account-switched', () => {
             refresh();
-        }, settings.checkInterval * 60 * 1000);
-        return () => clearInterval(intervalId);
-    }, [settings.autoCheck, settings.checkInterval, refresh]);
-
-    useEffect(() => {
-        if (!settings.enableAutoSwitch) return;
-        if (accounts.length === 0) return;
-        checkAutoSwitch(accounts);
-    }, [accounts, settings.enableAutoSwitch, checkAutoSwitch]);
+        });
+        // Codex CLI 刷新的 Token 已写回账号文件
+        const unlistenTokens = listen('account-tokens-updated', () => {
+            refresh();
+        });
+        // 后台刷新发现 Refresh Token 已失效（同时会弹出系统通知）
+        const unlistenFailed = listen('token-refresh-failed', () => {
+            refresh();
+        });
+        // 后台同步下载或删除了账号文件时重新扫描
+        const unlistenSync = listen<SyncStatus>('sync-status', (event) => {
+            const result = event.payload.result;
+            if (event.payload.phase !== 'syncing' && (result?.downloaded.length || result?.deleted?.length)) {
+                refresh();
+            }
+        });
+        return () => {
+            unlistenUsage.then(fn => fn());
+            unlistenSwitch.then(fn => fn());
+            unlistenTokens.then(fn => fn());
+            unlistenFailed.then(fn => fn());
+            unlistenSync.then(fn => fn());
+        };
+    }, [refresh, applyUsageUpdates]);
 
     const renameAccount = useCallback(async (oldPath: string, newName: string) => {
         // Find the account ID first to ensure we update the right one reliably
@@ -204,10 +299,10 @@ export function useAccounts() {
             await new Promise(resolve => setTimeout(resolve, 800)); // Increased to 800ms for safety
             await refresh();
             return { success: true };
-        } catch (error: any) {
+        } catch (error) {
             console.error("Rename failed:", error);
             await refresh(); // Force sync
-            return { success: false, message: error.toString() };
+            return { success: false, message: errorMessage(error) };
         }
     }, [accounts, refresh]); // Added accounts dependency
 
@@ -216,8 +311,8 @@ export function useAccounts() {
             await invoke('set_accounts_dir', { path });
             await refresh();
             return { success: true };
-        } catch (error: any) {
-            return { success: false, message: error.toString() };
+        } catch (error) {
+            return { success: false, message: errorMessage(error) };
         }
     }, [refresh]);
 
@@ -226,8 +321,18 @@ export function useAccounts() {
             await invoke('add_account', { name, content });
             await refresh();
             return { success: true };
-        } catch (error: any) {
-            return { success: false, message: error.toString() };
+        } catch (error) {
+            return { success: false, message: errorMessage(error) };
+        }
+    }, [refresh]);
+
+    const unlockVault = useCallback(async (passphrase: string) => {
+        try {
+            await invoke<VaultStatus>('unlock_vault', { passphrase });
+            await refresh();
+            return { success: true };
+        } catch (error) {
+            return { success: false, message: errorMessage(error) };
         }
     }, [refresh]);
 
@@ -251,6 +356,11 @@ export function useAccounts() {
         setAccountsDir,
         addAccount,
         getAccountsDir,
-        bestCandidateId,
+        vault,
+        unlockVault,
+        savedSyncTarget,
+        saveSyncTarget,
+        bestCandidateId: bestCandidate?.accountId ?? null,
+        bestCandidateReason: bestCandidate?.reason,
     };
 }
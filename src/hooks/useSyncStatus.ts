@@ -0,0 +1,23 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import { SyncStatus } from '../types';
+
+// 后台自动同步状态，由 sync-status 事件推送
+export function useSyncStatus() {
+    const [status, setStatus] = useState<SyncStatus | null>(null);
+
+    useEffect(() => {
+        invoke<SyncStatus>('get_sync_status')
+            .then(setStatus)
+            .catch(error => console.error('读取同步状态失败:', error));
+        const unlisten = listen<SyncStatus>('sync-status', (event) => {
+            setStatus(event.payload);
+        });
+        return () => {
+            unlisten.then(fn => fn());
+        };
+    }, []);
+
+    return status;
+}
@@ -25,19 +25,237 @@ export interface AccountInfo {
     lastRefresh: string;
     lastUsageUpdate?: number;
     isTokenExpired?: boolean;
+    refreshFailure?: RefreshFailure; // Refresh Token 已永久失效，需要重新登录
+    forecast?: UsageForecast;
+}
+
+// 后端 usage_history::WindowForecast（时间为秒级时间戳）
+export interface WindowForecast {
+    usedPercent: number;
+    burnRatePerHour: number | null;
+    exhaustsAt: number | null;
+    resetsAt: number | null;
+}
+
+export interface UsageForecast {
+    primary: WindowForecast | null;
+    secondary: WindowForecast | null;
+}
+
+// 后端 usage_history::UsageSample，字段与 UsageInfo 相同并带查询时间
+export interface UsageSample extends UsageInfo {
+    at: number;
+}
+
+// get_usage_history 的返回值
+export interface UsageHistory {
+    filePath: string;
+    name: string;
+    email: string;
+    samples: UsageSample[];
+    forecast: UsageForecast;
+}
+
+// 后端 token::RefreshFailure
+export interface RefreshFailure {
+    kind: 'expired' | 'reused' | 'invalidated';
+    message: string;
+    failedAt: string;
+}
+
+// 后端 AppConfig.auto_refresh（由 Rust 后台任务在 Token 过期前刷新）
+export interface AutoRefreshConfig {
+    enabled: boolean;
+    leadMinutes: number;
+    jitterMinutes: number;
+}
+
+// 后端 AppConfig.locale：托盘菜单、错误提示等后端文字的语言
+export type Locale = 'zh' | 'en';
+
+// 后端 AppConfig.network：接口地址一般无需修改，界面只编辑代理与 CA
+export interface NetworkConfig {
+    oauthTokenUrl?: string;
+    clientId?: string;
+    usageUrls?: string[];
+    proxy: string | null;
+    noProxy: string | null;
+    caBundle: string | null;
+}
+
+export const DEFAULT_AUTO_REFRESH_CONFIG: AutoRefreshConfig = {
+    enabled: true,
+    leadMinutes: 30,
+    jitterMinutes: 10,
+};
+
+export interface VaultStatus {
+    enabled: boolean;
+    unlocked: boolean;
+}
+
+export interface MigrationReport {
+    encrypted: string[];
+    skipped: string[];
+    errors: string[];
 }
 
 export interface ScanResult {
     accounts: AccountInfo[];
     accountsDir: string;
+    vault: VaultStatus;
+}
+
+// 同步后端类型
+export type SyncBackendKind = 'webdav' | 's3' | 'folder' | 'git';
+
+export interface S3Config {
+    endpoint: string;        // https://s3.amazonaws.com 或 MinIO 地址
+    region: string;          // 如 us-east-1
+    bucket: string;
+    accessKeyId: string;
+    secretAccessKey: string;
+    prefix: string;          // 对象键前缀，如 codex-manager/
+    pathStyle: boolean;      // 路径风格寻址（MinIO 等自建服务需要）
+}
+
+export interface GitRepoConfig {
+    url: string;             // git@github.com:me/codex-sync.git
+    branch: string;
 }
 
+// 云端同步设置（沿用 webdav 字段名以兼容已保存的设置）
+// 密码、Secret Key 和同步口令只在输入后、保存到后端前暂存于内存，不写入 localStorage
 export interface WebDavConfig {
     enabled: boolean;
+    backend?: SyncBackendKind; // 缺省为 webdav
     url: string;        // https://dav.jianguoyun.com/dav/
     username: string;   // 坚果云登录邮箱
     password: string;   // 应用专用密码
     remotePath: string; // 远程目录路径，如 /codex-manager/
+    s3?: S3Config;
+    folderPath?: string; // 本地 / NAS 同步目录
+    git?: GitRepoConfig;
+    syncPassphrase?: string; // 同步口令，账号文件加密后再上传
+    trashRetentionDays?: number; // 远程回收站保留天数，0 表示一直保留
+}
+
+export const DEFAULT_S3_CONFIG: S3Config = {
+    endpoint: '',
+    region: 'us-east-1',
+    bucket: '',
+    accessKeyId: '',
+    secretAccessKey: '',
+    prefix: 'codex-manager/',
+    pathStyle: true,
+};
+
+export const DEFAULT_GIT_CONFIG: GitRepoConfig = {
+    url: '',
+    branch: 'main',
+};
+
+// 后端 sync_backend::BackendConfig
+export type BackendConfig =
+    | { kind: 'webdav'; url: string; username: string; password: string; remotePath: string }
+    | ({ kind: 's3' } & S3Config)
+    | { kind: 'folder'; path: string }
+    | ({ kind: 'git' } & GitRepoConfig);
+
+// 后端 sync_backend::SyncTarget
+export interface SyncTarget {
+    backend: BackendConfig;
+    syncPassphrase: string;
+    trashRetentionDays: number;
+}
+
+export const toBackendConfig = (config: WebDavConfig): BackendConfig => {
+    switch (config.backend ?? 'webdav') {
+        case 's3':
+            return { kind: 's3', ...DEFAULT_S3_CONFIG, ...config.s3 };
+        case 'folder':
+            return { kind: 'folder', path: config.folderPath ?? '' };
+        case 'git':
+            return { kind: 'git', ...DEFAULT_GIT_CONFIG, ...config.git };
+        default:
+            return {
+                kind: 'webdav',
+                url: config.url,
+                username: config.username,
+                password: config.password,
+                remotePath: config.remotePath,
+            };
+    }
+};
+
+// 凭据字段为空时后端沿用已保存的值
+export const toSyncTarget = (config: WebDavConfig): SyncTarget => ({
+    backend: toBackendConfig(config),
+    syncPassphrase: config.syncPassphrase ?? '',
+    trashRetentionDays: config.trashRetentionDays ?? 30,
+});
+
+// 由后端保存的同步目标还原表单，保留本地的启用状态和其他后端的参数
+export const fromSyncTarget = (target: SyncTarget, base: WebDavConfig): WebDavConfig => {
+    const config: WebDavConfig = {
+        ...base,
+        syncPassphrase: '',
+        trashRetentionDays: target.trashRetentionDays,
+    };
+    const backend = target.backend;
+    switch (backend.kind) {
+        case 's3':
+            return {
+                ...config,
+                backend: 's3',
+                s3: {
+                    endpoint: backend.endpoint,
+                    region: backend.region,
+                    bucket: backend.bucket,
+                    accessKeyId: backend.accessKeyId,
+                    secretAccessKey: '',
+                    prefix: backend.prefix,
+                    pathStyle: backend.pathStyle,
+                },
+            };
+        case 'folder':
+            return { ...config, backend: 'folder', folderPath: backend.path };
+        case 'git':
+            return { ...config, backend: 'git', git: { url: backend.url, branch: backend.branch } };
+        default:
+            return {
+                ...config,
+                backend: 'webdav',
+                url: backend.url,
+                username: backend.username,
+                password: '',
+                remotePath: backend.remotePath,
+            };
+    }
+};
+
+// 去掉凭据字段，用于写入 localStorage
+export const stripSecrets = (config: WebDavConfig): WebDavConfig => ({
+    ...config,
+    password: '',
+    s3: config.s3 ? { ...config.s3, secretAccessKey: '' } : undefined,
+    syncPassphrase: '',
+});
+
+export const hasSecrets = (config: WebDavConfig): boolean =>
+    !!(config.password || config.s3?.secretAccessKey || config.syncPassphrase);
+
+// 后端 credentials 模块中的凭据名
+export const SECRET_WEBDAV_PASSWORD = 'webdav-password';
+export const SECRET_S3_SECRET_ACCESS_KEY = 's3-secret-access-key';
+export const SECRET_SYNC_PASSPHRASE = 'sync-passphrase';
+
+export type SecretStore = 'keyring' | 'file';
+
+// 后端 credentials::SavedSyncTarget，凭据字段为空
+export interface SavedSyncTarget {
+    target?: SyncTarget;
+    saved: Record<string, SecretStore>;
 }
 
 export interface SyncSettings {
@@ -47,26 +265,172 @@ export interface SyncSettings {
     syncSkills: boolean;      // Skills
     syncAgentsMd: boolean;    // AGENTS.MD
     syncConfigToml: boolean;  // config.toml
+    maxFileSizeMb?: number;   // Codex 配置中单个文件的大小上限（MB），0 表示不限制
+    // 后台自动同步
+    autoSync?: boolean;
+    autoSyncInterval?: number;        // minutes
+    autoSyncOnChange?: boolean;       // 账号目录变化时立即同步
+    codexAutoSync?: CodexDirection;   // Codex 配置的自动同步方向
     // 上次同步时间
     lastSyncTime?: number;
 }
 
+export type CodexDirection = 'off' | 'upload' | 'download';
+
 export const DEFAULT_SYNC_SETTINGS: SyncSettings = {
     syncAccounts: true,
     syncPrompts: true,
     syncSkills: true,
     syncAgentsMd: true,
     syncConfigToml: false,  // 默认不同步（MCP路径因设备而异）
+    maxFileSizeMb: 20,
+    autoSync: false,
+    autoSyncInterval: 30,
+    autoSyncOnChange: true,
+    codexAutoSync: 'off',
+};
+
+// 备用账号选择策略
+export type SwitchStrategyKind = 'mostRemaining' | 'earliestReset' | 'roundRobin' | 'priority';
+
+// 后端 AppConfig.auto_switch（由 Rust 后台任务使用）
+export interface AutoSwitchConfig {
+    autoCheck: boolean;
+    checkInterval: number; // minutes
+    enableAutoSwitch: boolean;
+    autoSwitchThreshold: number; // percent remaining to trigger switch
+    strategy: SwitchStrategyKind;
+    priority: string[]; // account name / email / id, used by 'priority'
+}
+
+export interface SwitchDecision {
+    accountId: string;
+    filePath: string;
+    email: string;
+    strategy: SwitchStrategyKind;
+    reason: string;
+}
+
+// 后端 AppConfig.auto_sync（由 Rust 后台同步任务使用）
+export interface AutoSyncConfig {
+    enabled: boolean;
+    interval: number; // minutes
+    onChange: boolean;
+    syncAccounts: boolean;
+    codexDirection: CodexDirection;
+    codexConfig: {
+        syncPrompts: boolean;
+        syncSkills: boolean;
+        syncAgentsMd: boolean;
+        syncConfigToml: boolean;
+        maxFileSizeMb: number;
+    };
+}
+
+export interface AppConfig {
+    accounts_dir?: string;
+    auto_switch: AutoSwitchConfig;
+    auto_sync?: AutoSyncConfig;
+    auto_refresh?: AutoRefreshConfig;
+    network?: NetworkConfig;
+    usage_fetch?: UsageFetchConfig;
+    locale?: Locale;
+    logging?: LoggingConfig;
+}
+
+// 后端 AppConfig.logging：级别为 tracing 过滤指令，日志按天滚动
+export interface LoggingConfig {
+    level: string;
+    retentionDays: number;
+}
+
+// 后端 AppConfig.usage_fetch：fetch_all_usage 的并发数与单个账号超时
+export interface UsageFetchConfig {
+    concurrency: number;
+    timeoutSeconds: number;
+}
+
+// 后台同步状态（sync-status 事件）
+export type SyncPhase = 'disabled' | 'syncing' | 'idle' | 'partial' | 'failed';
+
+export interface SyncStatus {
+    phase: SyncPhase;
+    lastSync?: number;
+    nextSync?: number; // 失败后不再重试时为空
+    failures: number;
+    message?: string;
+    result?: SyncResult;
+}
+
+// 后端 error::AppError：命令失败时 invoke 抛出该对象。按 code 判断错误类型，message 只用于显示
+export type AppErrorCode =
+    | 'notFound'
+    | 'alreadyExists'
+    | 'invalidAuthJson'
+    | 'invalidInput'
+    | 'vaultLocked'
+    | 'vaultNotEnabled'
+    | 'wrongPassphrase'
+    | 'decryptFailed'
+    | 'corrupt'
+    | 'network'
+    | 'httpStatus'
+    | 'tokenExpired'
+    | 'refreshTokenReused'
+    | 'refreshTokenInvalidated'
+    | 'webDavAuth'
+    | 's3Auth'
+    | 'syncNotConfigured'
+    | 'keyring'
+    | 'conflict'
+    | 'io'
+    | 'other';
+
+export interface AppError {
+    code: AppErrorCode;
+    message: string;
+    status?: number; // httpStatus 的状态码
+    detail?: string;
+}
+
+export const isAppError = (e: unknown): e is AppError =>
+    typeof e === 'object' && e !== null && 'code' in e && 'message' in e;
+
+// 显示给用户的错误文字；前端自身抛出的异常按原样转为文本
+export const errorMessage = (e: unknown): string => {
+    if (isAppError(e)) return e.message;
+    if (e instanceof Error) return e.message;
+    return String(e);
 };
 
+// 后台任务事件 payload
+export interface UsageUpdate {
+    filePath: string;
+    usage?: UsageInfo;
+    error?: AppError;
+    isTokenExpired: boolean;
+}
+
+export interface AccountSwitchedEvent {
+    from?: string;
+    to: string;
+    filePath: string;
+    reason: string;
+}
+
 export interface AppSettings {
     accountsDir?: string;
     autoCheck: boolean;
     checkInterval: number; // minutes
     enableAutoSwitch: boolean;
     autoSwitchThreshold: number; // percent remaining to trigger switch
+    strategy: SwitchStrategyKind;
+    priority: string[];
     webdav?: WebDavConfig;
     sync?: SyncSettings;
+    autoRefresh?: AutoRefreshConfig;
+    network?: NetworkConfig;
+    locale?: Locale;
 }
 
 export const DEFAULT_SETTINGS: AppSettings = {
@@ -74,6 +438,8 @@ export const DEFAULT_SETTINGS: AppSettings = {
     checkInterval: 30,
     enableAutoSwitch: false,
     autoSwitchThreshold: 5,
+    strategy: 'earliestReset',
+    priority: [],
     webdav: {
         enabled: false,
         url: 'https://dav.jianguoyun.com/dav/',
@@ -84,6 +450,26 @@ export const DEFAULT_SETTINGS: AppSettings = {
     sync: DEFAULT_SYNC_SETTINGS,
 };
 
+// 由前端设置生成后端自动同步配置，同步目标由 save_sync_target 单独保存
+export const toAutoSyncConfig = (settings: AppSettings): AutoSyncConfig => {
+    const sync = { ...DEFAULT_SYNC_SETTINGS, ...settings.sync };
+    const webdav = settings.webdav;
+    return {
+        enabled: !!sync.autoSync && !!webdav?.enabled,
+        interval: sync.autoSyncInterval ?? 30,
+        onChange: sync.autoSyncOnChange ?? true,
+        syncAccounts: sync.syncAccounts,
+        codexDirection: sync.codexAutoSync ?? 'off',
+        codexConfig: {
+            syncPrompts: sync.syncPrompts,
+            syncSkills: sync.syncSkills,
+            syncAgentsMd: sync.syncAgentsMd,
+            syncConfigToml: sync.syncConfigToml,
+            maxFileSizeMb: sync.maxFileSizeMb ?? 20,
+        },
+    };
+};
+
 // ========== Prompts & Skills ==========
 
 export interface PromptInfo {
@@ -109,6 +495,7 @@ export interface CodexSyncConfig {
     syncSkills: boolean;
     syncAgentsMd: boolean;
     syncConfigToml: boolean;
+    maxFileSizeMb?: number;
 }
 
 export const DEFAULT_CODEX_SYNC_CONFIG: CodexSyncConfig = {
@@ -116,6 +503,7 @@ export const DEFAULT_CODEX_SYNC_CONFIG: CodexSyncConfig = {
     syncSkills: true,
     syncAgentsMd: true,
     syncConfigToml: false,  // 默认不同步（MCP路径因设备而异）
+    maxFileSizeMb: 20,
 };
 
 export interface SyncPreviewItem {
@@ -137,4 +525,8 @@ export interface SyncResult {
     uploaded: string[];
     downloaded: string[];
     errors: string[];
+    migrated?: string[]; // 旧版遗留的远程文件（明文账号、旧目录结构）已迁移
+    conflicts?: string[]; // 两端都修改且无法自动判断，均保持不变
+    skipped?: string[]; // 超过大小限制等原因未传输的文件及原因
+    deleted?: string[]; // 因删除或重命名而移除的文件，a → b 表示重命名
 }